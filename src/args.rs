@@ -16,6 +16,14 @@ pub struct Args {
     #[structopt(short, long)]
     debug: bool,
 
+    /// Run the tests declared in the input file instead of the program
+    #[structopt(short, long)]
+    test: bool,
+
+    /// Only run the tests whose name contains the given filter
+    #[structopt(long, requires = "test")]
+    filter: Option<String>,
+
     #[structopt(parse(from_os_str))]
     input: Option<PathBuf>,
 }
@@ -49,6 +57,16 @@ impl Args {
         self.debug
     }
 
+    /// Is the interpreter launched in test mode
+    pub fn test(&self) -> bool {
+        self.test
+    }
+
+    /// Filter on the name of the tests to run
+    pub fn filter(&self) -> Option<&str> {
+        self.filter.as_deref()
+    }
+
     /// File input given to the interpreter
     pub fn input(&self) -> Option<&PathBuf> {
         self.input.as_ref()
@@ -0,0 +1,134 @@
+//! Assertions, used to write tests. A failed assertion is an error, which displays the
+//! values that were compared and is located on the call to the assertion.
+
+use super::Builtin;
+use crate::{
+    FromObjectInstance, InstrKind, Interpreter, JkBool, JkErrKind, JkError, ObjectInstance,
+};
+
+pub(super) const FUNCTIONS: [Builtin; 4] = [
+    ("assert", "ext func assert(condition: bool);", assert),
+    (
+        "assert_eq",
+        "ext func assert_eq<T>(left: T, right: T);",
+        assert_eq,
+    ),
+    (
+        "assert_ne",
+        "ext func assert_ne<T>(left: T, right: T);",
+        assert_ne,
+    ),
+    (
+        "assert_err",
+        "ext func assert_err<T, E>(result: Result<T, E>);",
+        assert_err,
+    ),
+];
+
+/// Error emitted when an assertion does not hold
+fn failure(msg: &str, input: &str) -> JkError {
+    JkError::new(
+        JkErrKind::Interpreter,
+        format!("assertion failed: {}", msg),
+        None,
+        input.to_owned(),
+    )
+}
+
+/// Check that two values are equal. Both values have the same type, so comparing
+/// their bytes and elements is enough
+fn equal(left: &ObjectInstance, right: &ObjectInstance) -> bool {
+    left.same_value(right)
+}
+
+/// Check that a condition holds
+fn assert(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    match JkBool::from_instance(&args[0]).0 {
+        true => Ok(InstrKind::Statement),
+        false => Err(failure("condition is `false`", "assert")),
+    }
+}
+
+/// Check that two values are equal
+fn assert_eq(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    match equal(&args[0], &args[1]) {
+        true => Ok(InstrKind::Statement),
+        false => Err(failure("`left == right`", "assert_eq")
+            .with_note(format!("left: `{}`", args[0].display(interpreter)))
+            .with_note(format!("right: `{}`", args[1].display(interpreter)))),
+    }
+}
+
+/// Check that two values are different
+fn assert_ne(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    match equal(&args[0], &args[1]) {
+        false => Ok(InstrKind::Statement),
+        true => Err(failure("`left != right`", "assert_ne")
+            .with_note(format!("left: `{}`", args[0].display(interpreter)))
+            .with_note(format!("right: `{}`", args[1].display(interpreter)))),
+    }
+}
+
+/// Check that a `Result` contains an error
+fn assert_err(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let err = args[0].ty().and_then(|ty| ty.variant_tag("Err"));
+
+    match err.is_some() && args[0].tag() == err {
+        true => Ok(InstrKind::Statement),
+        false => Err(failure("expected an `Err` value", "assert_err")
+            .with_note(format!("value: `{}`", args[0].display(interpreter)))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::execute;
+    use crate::InstrKind;
+
+    #[test]
+    fn t_assertions_hold() {
+        assert_eq!(execute("assert(1 < 2)").unwrap(), InstrKind::Statement);
+        assert_eq!(
+            execute("mut a = [1]; a.push(2); assert_eq([1, 2], a)").unwrap(),
+            InstrKind::Statement
+        );
+        assert_eq!(
+            execute("assert_eq({ 1: 'a', 2: 'b' }, { 2: 'b', 1: 'a' })").unwrap(),
+            InstrKind::Statement
+        );
+        assert_eq!(
+            execute("assert_ne(\"jinko\", \"rust\")").unwrap(),
+            InstrKind::Statement
+        );
+        assert_eq!(
+            execute("assert_err(int::from_str(\"twelve\"))").unwrap(),
+            InstrKind::Statement
+        );
+    }
+
+    #[test]
+    fn t_assertions_fail() {
+        assert!(execute("assert(2 < 1)").is_err());
+        assert!(execute("assert_ne(1.5, 1.5)").is_err());
+        assert!(execute("assert_eq([1, 2], [1, 3])").is_err());
+        assert!(execute("assert_err(int::from_str(\"12\"))").is_err());
+    }
+
+    #[test]
+    fn t_assert_eq_displays_values() {
+        let error = execute("assert_eq(1 + 1, 3)").err().unwrap();
+        let error = error.to_string();
+
+        assert!(error.contains("left: `2`"));
+        assert!(error.contains("right: `3`"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Conversions between primitive types. Conversions which can fail return a `Result`,
+//! whose error describes why the conversion failed.
+
+use std::convert::TryFrom;
+
+use super::{result_instance, Builtin};
+use crate::instruction::StringInterpolation;
+use crate::{
+    FromObjectInstance, InstrKind, Interpreter, JkChar, JkErrKind, JkError, JkFloat, JkInt,
+    JkString, ObjectInstance, ToObjectInstance,
+};
+
+pub(super) const FUNCTIONS: [Builtin; 7] = [
+    (
+        "int::from",
+        "ext func int::from(value: float) -> int;",
+        int_from,
+    ),
+    (
+        "float::from",
+        "ext func float::from(value: int) -> float;",
+        float_from,
+    ),
+    (
+        "int::from_str",
+        "ext func int::from_str(value: string) -> Result<int, string>;",
+        int_from_str,
+    ),
+    (
+        "float::from_str",
+        "ext func float::from_str(value: string) -> Result<float, string>;",
+        float_from_str,
+    ),
+    (
+        "int::from_char",
+        "ext func int::from_char(value: char) -> int;",
+        int_from_char,
+    ),
+    (
+        "char::from_int",
+        "ext func char::from_int(value: int) -> Result<char, string>;",
+        char_from_int,
+    ),
+    (
+        "string::from",
+        "ext func string::from<T>(value: T) -> string;",
+        string_from,
+    ),
+];
+
+/// Convert a floating point number to an integer, truncating its decimal part. The
+/// conversion fails if the number cannot be represented as an integer
+fn int_from(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let value = JkFloat::from_instance(&args[0]).0;
+
+    // `i64::MAX as f64` is rounded up to 2^63, which is not a valid integer
+    if !value.is_finite() || value < i64::MIN as f64 || value >= i64::MAX as f64 {
+        return Err(JkError::new(
+            JkErrKind::Interpreter,
+            format!("cannot convert `{}` to an integer", value),
+            None,
+            format!("int::from({})", value),
+        ));
+    }
+
+    Ok(InstrKind::Expression(Some(
+        JkInt::from(value as i64).to_instance(),
+    )))
+}
+
+/// Convert an integer to a floating point number
+fn float_from(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let value = JkInt::from_instance(&args[0]).0;
+
+    Ok(InstrKind::Expression(Some(
+        JkFloat::from(value as f64).to_instance(),
+    )))
+}
+
+/// Parse an integer out of a string
+fn int_from_str(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let value = JkString::from_instance(&args[0]).0;
+
+    let result = value
+        .trim()
+        .parse::<i64>()
+        .map(|int| JkInt::from(int).to_instance())
+        .map_err(|e| format!("cannot convert `{}` to an integer: {}", value, e));
+
+    result_instance(interpreter, "int", result)
+}
+
+/// Parse a floating point number out of a string
+fn float_from_str(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let value = JkString::from_instance(&args[0]).0;
+
+    let result = value
+        .trim()
+        .parse::<f64>()
+        .map(|float| JkFloat::from(float).to_instance())
+        .map_err(|e| format!("cannot convert `{}` to a float: {}", value, e));
+
+    result_instance(interpreter, "float", result)
+}
+
+/// Get the unicode code point of a character
+fn int_from_char(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let value = JkChar::from_instance(&args[0]).0;
+
+    Ok(InstrKind::Expression(Some(
+        JkInt::from(value as i64).to_instance(),
+    )))
+}
+
+/// Get the character associated with a unicode code point
+fn char_from_int(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let value = JkInt::from_instance(&args[0]).0;
+
+    let result = u32::try_from(value)
+        .ok()
+        .and_then(char::from_u32)
+        .map(|c| JkChar::from(c).to_instance())
+        .ok_or_else(|| format!("`{}` is not a valid unicode code point", value));
+
+    result_instance(interpreter, "char", result)
+}
+
+/// Convert any value to a string, the same way it would be interpolated in a string
+fn string_from(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    Ok(InstrKind::Expression(Some(
+        JkString::from(StringInterpolation::stringify(&args[0], interpreter)).to_instance(),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::execute;
+    use crate::{InstrKind, JkBool, JkChar, JkFloat, JkInt, JkString, ToObjectInstance};
+
+    #[test]
+    fn t_int_from_float() {
+        assert_eq!(
+            execute("int::from(-2.7)").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(-2).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_int_from_invalid_float() {
+        assert!(execute("int::from(4611686018427387904.0 * 4.0)").is_err());
+        assert!(execute("int::from(0.0 / 0.0)").is_err());
+    }
+
+    #[test]
+    fn t_float_from_int() {
+        assert_eq!(
+            execute("float::from(3)").unwrap(),
+            InstrKind::Expression(Some(JkFloat::from(3.0).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_from_str() {
+        assert_eq!(
+            execute("int::from_str(\" -12 \").ok().unwrap()").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(-12).to_instance()))
+        );
+        assert_eq!(
+            execute("float::from_str(\"1.5\").ok().unwrap()").unwrap(),
+            InstrKind::Expression(Some(JkFloat::from(1.5).to_instance()))
+        );
+        assert_eq!(
+            execute("int::from_str(\"1.5\").is_err()").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_char_conversions() {
+        assert_eq!(
+            execute("int::from_char('a')").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(97).to_instance()))
+        );
+        assert_eq!(
+            execute("char::from_int(233).ok().unwrap()").unwrap(),
+            InstrKind::Expression(Some(JkChar::from('é').to_instance()))
+        );
+        assert_eq!(
+            execute("char::from_int(0 - 1).is_err()").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_string_from() {
+        assert_eq!(
+            execute("string::from(1.5)").unwrap(),
+            InstrKind::Expression(Some(JkString::from("1.5").to_instance()))
+        );
+        assert_eq!(
+            execute("string::from(\"jinko\")").unwrap(),
+            InstrKind::Expression(Some(JkString::from("jinko").to_instance()))
+        );
+    }
+}
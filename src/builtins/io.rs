@@ -0,0 +1,194 @@
+//! Console and file input and output. Strings are written as is: Other values can be
+//! printed using string interpolation, such as `println("x = {x}")`. Accessing files
+//! can fail, so file builtins return a `Result` whose error describes the failure.
+//! Relative paths are relative to the directory the interpreter is launched from.
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use super::{result_instance, variant_instance, Builtin};
+use crate::instruction::TypeId;
+use crate::{
+    FromObjectInstance, InstrKind, Interpreter, JkErrKind, JkError, JkInt, JkString,
+    ObjectInstance, ToObjectInstance,
+};
+
+pub(super) const FUNCTIONS: [Builtin; 7] = [
+    ("print", "ext func print(value: string);", print),
+    ("println", "ext func println(value: string);", println),
+    ("eprint", "ext func eprint(value: string);", eprint),
+    ("eprintln", "ext func eprintln(value: string);", eprintln),
+    (
+        "read_line",
+        "ext func read_line() -> Option<string>;",
+        read_line,
+    ),
+    (
+        "file::read",
+        "ext func file::read(path: string) -> Result<string, string>;",
+        file_read,
+    ),
+    (
+        "file::write",
+        "ext func file::write(path: string, content: string) -> Result<int, string>;",
+        file_write,
+    ),
+];
+
+/// Error emitted when the console cannot be read from or written to
+fn io_error(error: io::Error, input: &str) -> JkError {
+    JkError::new(
+        JkErrKind::Interpreter,
+        format!("console error: {}", error),
+        None,
+        input.to_owned(),
+    )
+}
+
+/// Write a string to an output and flush it, so that the string is visible even if
+/// it does not end with a newline
+fn write(mut output: impl Write, value: &str, input: &str) -> Result<InstrKind, JkError> {
+    output
+        .write_all(value.as_bytes())
+        .and_then(|_| output.flush())
+        .map_err(|e| io_error(e, input))?;
+
+    Ok(InstrKind::Statement)
+}
+
+/// Write a string to the standard output
+fn print(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let value = JkString::from_instance(&args[0]).0;
+
+    write(io::stdout(), &value, "print")
+}
+
+/// Write a string to the standard output, followed by a newline
+fn println(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let value = JkString::from_instance(&args[0]).0;
+
+    write(io::stdout(), &format!("{}\n", value), "println")
+}
+
+/// Write a string to the standard error
+fn eprint(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let value = JkString::from_instance(&args[0]).0;
+
+    write(io::stderr(), &value, "eprint")
+}
+
+/// Write a string to the standard error, followed by a newline
+fn eprintln(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let value = JkString::from_instance(&args[0]).0;
+
+    write(io::stderr(), &format!("{}\n", value), "eprintln")
+}
+
+/// Read a line from the standard input, without its trailing newline. Returns `None`
+/// once the end of the input has been reached
+fn read_line(interpreter: &mut Interpreter, _: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let mut line = String::new();
+    let read = io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| io_error(e, "read_line"))?;
+
+    let ty = TypeId::generic("Option".to_owned(), vec![TypeId::from("string")]);
+    let option = match read {
+        0 => variant_instance(interpreter, &ty, "None", vec![]),
+        _ => {
+            let line = line.strip_suffix('\n').unwrap_or(&line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+
+            variant_instance(
+                interpreter,
+                &ty,
+                "Some",
+                vec![JkString::from(line).to_instance()],
+            )
+        }
+    };
+
+    Ok(InstrKind::Expression(Some(option)))
+}
+
+/// Read the whole content of a file
+fn file_read(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let path = JkString::from_instance(&args[0]).0;
+
+    let result = fs::read_to_string(&path)
+        .map(|content| JkString::from(content).to_instance())
+        .map_err(|e| format!("cannot read file `{}`: {}", path, e));
+
+    result_instance(interpreter, "string", result)
+}
+
+/// Write a string to a file, replacing its content. The file is created if it does not
+/// exist. Returns the number of bytes written
+fn file_write(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let path = JkString::from_instance(&args[0]).0;
+    let content = JkString::from_instance(&args[1]).0;
+
+    let result = fs::write(&path, &content)
+        .map(|_| JkInt::from(content.len() as i64).to_instance())
+        .map_err(|e| format!("cannot write file `{}`: {}", path, e));
+
+    result_instance(interpreter, "int", result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::execute;
+    use crate::{InstrKind, JkBool, JkInt, JkString, ToObjectInstance};
+
+    #[test]
+    fn t_print() {
+        assert_eq!(execute("print(\"\")").unwrap(), InstrKind::Statement);
+        assert_eq!(
+            execute("eprintln(\"jinko\")").unwrap(),
+            InstrKind::Statement
+        );
+    }
+
+    #[test]
+    fn t_file_write_read() {
+        let path = std::env::temp_dir().join("jinko_t_file_write_read.txt");
+        let path = path.to_str().unwrap();
+
+        assert_eq!(
+            execute(&format!(
+                "file::write(\"{}\", \"jïnko\").ok().unwrap()",
+                path
+            ))
+            .unwrap(),
+            InstrKind::Expression(Some(JkInt::from(6).to_instance()))
+        );
+        assert_eq!(
+            execute(&format!("file::read(\"{}\").ok().unwrap()", path)).unwrap(),
+            InstrKind::Expression(Some(JkString::from("jïnko").to_instance()))
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn t_file_errors() {
+        let missing = std::env::temp_dir().join("jinko_t_file_errors/missing.txt");
+        let missing = missing.to_str().unwrap();
+
+        assert_eq!(
+            execute(&format!("file::read(\"{}\").is_err()", missing)).unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+        assert_eq!(
+            execute(&format!("file::write(\"{}\", \"\").is_err()", missing)).unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Math functions on integers and floating point numbers. Integer builtins are
+//! declared in the `int` namespace, and floating point ones in the `float` namespace.
+
+use super::Builtin;
+use crate::{
+    FromObjectInstance, InstrKind, Interpreter, JkErrKind, JkError, JkFloat, JkInt, ObjectInstance,
+    ToObjectInstance,
+};
+
+pub(super) const FUNCTIONS: [Builtin; 17] = [
+    (
+        "int::wrapping_add",
+        "ext func int::wrapping_add(lhs: int, rhs: int) -> int;",
+        int_wrapping_add,
+    ),
+    (
+        "int::wrapping_sub",
+        "ext func int::wrapping_sub(lhs: int, rhs: int) -> int;",
+        int_wrapping_sub,
+    ),
+    (
+        "int::wrapping_mul",
+        "ext func int::wrapping_mul(lhs: int, rhs: int) -> int;",
+        int_wrapping_mul,
+    ),
+    (
+        "int::saturating_add",
+        "ext func int::saturating_add(lhs: int, rhs: int) -> int;",
+        int_saturating_add,
+    ),
+    (
+        "int::saturating_sub",
+        "ext func int::saturating_sub(lhs: int, rhs: int) -> int;",
+        int_saturating_sub,
+    ),
+    (
+        "int::saturating_mul",
+        "ext func int::saturating_mul(lhs: int, rhs: int) -> int;",
+        int_saturating_mul,
+    ),
+    ("int::abs", "ext func int::abs(value: int) -> int;", int_abs),
+    (
+        "int::min",
+        "ext func int::min(lhs: int, rhs: int) -> int;",
+        int_min,
+    ),
+    (
+        "int::max",
+        "ext func int::max(lhs: int, rhs: int) -> int;",
+        int_max,
+    ),
+    (
+        "float::abs",
+        "ext func float::abs(value: float) -> float;",
+        float_abs,
+    ),
+    (
+        "float::min",
+        "ext func float::min(lhs: float, rhs: float) -> float;",
+        float_min,
+    ),
+    (
+        "float::max",
+        "ext func float::max(lhs: float, rhs: float) -> float;",
+        float_max,
+    ),
+    (
+        "float::sqrt",
+        "ext func float::sqrt(value: float) -> float;",
+        float_sqrt,
+    ),
+    (
+        "float::pow",
+        "ext func float::pow(base: float, exponent: float) -> float;",
+        float_pow,
+    ),
+    (
+        "float::floor",
+        "ext func float::floor(value: float) -> float;",
+        float_floor,
+    ),
+    (
+        "float::ceil",
+        "ext func float::ceil(value: float) -> float;",
+        float_ceil,
+    ),
+    (
+        "float::round",
+        "ext func float::round(value: float) -> float;",
+        float_round,
+    ),
+];
+
+/// Implement a builtin applying an integer operation which cannot fail, unlike jinko's
+/// arithmetic operators which error out on overflow
+macro_rules! int_op_builtin {
+    ($name:ident, $op:ident) => {
+        fn $name(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+            let lhs = JkInt::from_instance(&args[0]).0;
+            let rhs = JkInt::from_instance(&args[1]).0;
+
+            Ok(InstrKind::Expression(Some(
+                JkInt::from(lhs.$op(rhs)).to_instance(),
+            )))
+        }
+    };
+}
+
+int_op_builtin!(int_wrapping_add, wrapping_add);
+int_op_builtin!(int_wrapping_sub, wrapping_sub);
+int_op_builtin!(int_wrapping_mul, wrapping_mul);
+int_op_builtin!(int_saturating_add, saturating_add);
+int_op_builtin!(int_saturating_sub, saturating_sub);
+int_op_builtin!(int_saturating_mul, saturating_mul);
+int_op_builtin!(int_min, min);
+int_op_builtin!(int_max, max);
+
+/// Get the absolute value of an integer. The absolute value of the smallest integer
+/// cannot be represented, and is an error
+fn int_abs(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let value = JkInt::from_instance(&args[0]).0;
+
+    match value.checked_abs() {
+        Some(abs) => Ok(InstrKind::Expression(Some(JkInt::from(abs).to_instance()))),
+        None => Err(JkError::new(
+            JkErrKind::Interpreter,
+            format!("overflow when taking the absolute value of `{}`", value),
+            None,
+            format!("int::abs({})", value),
+        )),
+    }
+}
+
+/// Implement a builtin applying an operation to a floating point number
+macro_rules! float_fn_builtin {
+    ($name:ident, $fn:ident) => {
+        fn $name(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+            let value = JkFloat::from_instance(&args[0]).0;
+
+            Ok(InstrKind::Expression(Some(
+                JkFloat::from(value.$fn()).to_instance(),
+            )))
+        }
+    };
+}
+
+float_fn_builtin!(float_abs, abs);
+float_fn_builtin!(float_sqrt, sqrt);
+float_fn_builtin!(float_floor, floor);
+float_fn_builtin!(float_ceil, ceil);
+float_fn_builtin!(float_round, round);
+
+/// Implement a builtin applying an operation to two floating point numbers
+macro_rules! float_op_builtin {
+    ($name:ident, $op:ident) => {
+        fn $name(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+            let lhs = JkFloat::from_instance(&args[0]).0;
+            let rhs = JkFloat::from_instance(&args[1]).0;
+
+            Ok(InstrKind::Expression(Some(
+                JkFloat::from(lhs.$op(rhs)).to_instance(),
+            )))
+        }
+    };
+}
+
+float_op_builtin!(float_min, min);
+float_op_builtin!(float_max, max);
+float_op_builtin!(float_pow, powf);
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::execute;
+    use crate::{InstrKind, JkFloat, JkInt, ToObjectInstance};
+
+    #[test]
+    fn t_wrapping_and_saturating() {
+        let max = i64::MAX;
+
+        assert_eq!(
+            execute(&format!("int::wrapping_add({}, 1)", max)).unwrap(),
+            InstrKind::Expression(Some(JkInt::from(i64::MIN).to_instance()))
+        );
+        assert_eq!(
+            execute(&format!("int::saturating_mul({}, 2)", max)).unwrap(),
+            InstrKind::Expression(Some(JkInt::from(max).to_instance()))
+        );
+        assert_eq!(
+            execute("int::saturating_sub(0 - 9223372036854775807, 2)").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(i64::MIN).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_int_math() {
+        assert_eq!(
+            execute("int::abs(-4) + int::min(2, -3) + int::max(2, -3)").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+        assert!(execute("int::abs(int::wrapping_sub(0 - 9223372036854775807, 1))").is_err());
+    }
+
+    #[test]
+    fn t_float_math() {
+        assert_eq!(
+            execute("float::sqrt(float::pow(3.0, 2.0)) + float::floor(1.5) + float::ceil(1.5)")
+                .unwrap(),
+            InstrKind::Expression(Some(JkFloat::from(6.0).to_instance()))
+        );
+        assert_eq!(
+            execute("float::round(float::abs(-2.5)) + float::max(1.0, float::min(2.0, 3.0))")
+                .unwrap(),
+            InstrKind::Expression(Some(JkFloat::from(5.0).to_instance()))
+        );
+    }
+}
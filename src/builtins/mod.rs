@@ -0,0 +1,757 @@
+//! Builtins are functions and types implemented directly inside the interpreter, in
+//! Rust. They are available in every jinko program without having to include anything.
+//! Each builtin is declared using jinko syntax, as an `ext func`, which allows the
+//! typechecker to check calls to builtins like calls to any other function.
+//! Some builtin functions are simple enough to be written in jinko directly, on top of
+//! the builtin types, and are part of the prelude.
+//! The standard library is split into modules: console IO, string manipulation, math,
+//! conversions between types and assertions.
+
+mod assertion;
+mod conversion;
+mod io;
+mod math;
+mod string;
+
+use std::collections::HashMap;
+
+use crate::instruction::{FunctionDec, TypeDec, TypeId, ARRAY_TYPE, HASHABLE_TYPES, MAP_TYPE};
+use crate::parser::Construct;
+use crate::{
+    FromObjectInstance, InstrKind, Interpreter, JkBool, JkErrKind, JkError, JkInt, JkString,
+    ObjectInstance, ToObjectInstance,
+};
+
+/// Rust implementation of a builtin function. The arguments given to the function have
+/// already been executed and checked against the builtin's declaration
+pub type BuiltinFn = fn(&mut Interpreter, Vec<ObjectInstance>) -> Result<InstrKind, JkError>;
+
+/// Rust implementation of a builtin function which modifies its first argument in
+/// place, such as `push`. The other arguments are given like for regular builtins.
+/// When the first argument is a variable, the variable is updated after the call
+pub type InPlaceBuiltinFn =
+    fn(&mut Interpreter, &mut ObjectInstance, Vec<ObjectInstance>) -> Result<InstrKind, JkError>;
+
+/// A builtin function: its name, its declaration and its implementation
+type Builtin = (&'static str, &'static str, BuiltinFn);
+
+/// A builtin function modifying its first argument in place: its name, its declaration
+/// and its implementation
+type InPlaceBuiltin = (&'static str, &'static str, InPlaceBuiltinFn);
+
+/// Builtin functions, their declaration and their implementation
+const BUILTIN_FUNCTIONS: [Builtin; 9] = [
+    (
+        "range",
+        "ext func range(start: int, end: int) -> range;",
+        range,
+    ),
+    ("array::new", "ext func array::new<T>() -> [T];", array_new),
+    ("len", "ext func len<T>(array: [T]) -> int;", array_len),
+    (
+        "last",
+        "ext func last<T>(array: [T]) -> Option<T>;",
+        array_last,
+    ),
+    (
+        "slice",
+        "ext func slice<T>(array: [T], start: int, end: int) -> [T];",
+        array_slice,
+    ),
+    (
+        "map::new",
+        "ext func map::new<K, V>() -> map<K, V>;",
+        map_new,
+    ),
+    (
+        "get",
+        "ext func get<K, V>(map: map<K, V>, key: K) -> V;",
+        map_get,
+    ),
+    (
+        "contains",
+        "ext func contains<K, V>(map: map<K, V>, key: K) -> bool;",
+        map_contains,
+    ),
+    (
+        "unwrap",
+        "ext func unwrap<T>(option: Option<T>) -> T;",
+        option_unwrap,
+    ),
+];
+
+/// Builtin functions modifying their first argument in place, their declaration and
+/// their implementation
+const IN_PLACE_FUNCTIONS: [InPlaceBuiltin; 4] = [
+    (
+        "push",
+        "ext func push<T>(array: [T], value: T);",
+        array_push,
+    ),
+    (
+        "pop",
+        "ext func pop<T>(array: [T]) -> Option<T>;",
+        array_pop,
+    ),
+    (
+        "insert",
+        "ext func insert<K, V>(map: map<K, V>, key: K, value: V);",
+        map_insert,
+    ),
+    (
+        "remove",
+        "ext func remove<K, V>(map: map<K, V>, key: K) -> Option<V>;",
+        map_remove,
+    ),
+];
+
+/// All the builtin functions implemented in Rust, including the standard library
+fn builtin_functions() -> impl Iterator<Item = &'static Builtin> {
+    BUILTIN_FUNCTIONS
+        .iter()
+        .chain(io::FUNCTIONS.iter())
+        .chain(string::FUNCTIONS.iter())
+        .chain(math::FUNCTIONS.iter())
+        .chain(conversion::FUNCTIONS.iter())
+        .chain(assertion::FUNCTIONS.iter())
+}
+
+/// Builtin functions written in jinko, and their declaration
+const PRELUDE_FUNCTIONS: [(&str, &str); 7] = [
+    (
+        "is_some",
+        "func is_some<T>(option: Option<T>) -> bool {
+            match option { Some(_) => true, None => false }
+        }",
+    ),
+    (
+        "is_none",
+        "func is_none<T>(option: Option<T>) -> bool {
+            match option { Some(_) => false, None => true }
+        }",
+    ),
+    (
+        "unwrap_or",
+        "func unwrap_or<T>(option: Option<T>, default: T) -> T {
+            match option { Some(value) => value, None => default }
+        }",
+    ),
+    (
+        "is_ok",
+        "func is_ok<T, E>(result: Result<T, E>) -> bool {
+            match result { Ok(_) => true, Err(_) => false }
+        }",
+    ),
+    (
+        "is_err",
+        "func is_err<T, E>(result: Result<T, E>) -> bool {
+            match result { Ok(_) => false, Err(_) => true }
+        }",
+    ),
+    (
+        "ok",
+        "func ok<T, E>(result: Result<T, E>) -> Option<T> {
+            match result { Ok(value) => Some(value), Err(_) => None<T>() }
+        }",
+    ),
+    (
+        "err",
+        "func err<T, E>(result: Result<T, E>) -> Option<E> {
+            match result { Ok(_) => None<E>(), Err(error) => Some(error) }
+        }",
+    ),
+];
+
+/// Builtin types, which can be used and instantiated like user-defined types
+const BUILTIN_TYPES: [(&str, &str); 3] = [
+    ("range", "type range(start: int, end: int)"),
+    ("Option", "type Option<T> = Some(value: T) | None;"),
+    (
+        "Result",
+        "type Result<T, E> = Ok(value: T) | Err(error: E);",
+    ),
+];
+
+/// The builtins registry keeps track of the implementation of every builtin function
+pub struct Builtins {
+    functions: HashMap<String, BuiltinFn>,
+    in_place: HashMap<String, InPlaceBuiltinFn>,
+}
+
+impl Builtins {
+    /// Create a new registry containing all of jinko's builtin functions
+    pub fn new() -> Builtins {
+        let functions = builtin_functions()
+            .map(|(name, _, function)| (name.to_string(), *function))
+            .collect();
+        let in_place = IN_PLACE_FUNCTIONS
+            .iter()
+            .map(|(name, _, function)| (name.to_string(), *function))
+            .collect();
+
+        Builtins {
+            functions,
+            in_place,
+        }
+    }
+
+    /// Get the implementation of a builtin function, if it exists
+    pub fn get(&self, name: &str) -> Option<BuiltinFn> {
+        self.functions.get(name).copied()
+    }
+
+    /// Get the implementation of a builtin function modifying its first argument in
+    /// place, if it exists
+    pub fn get_in_place(&self, name: &str) -> Option<InPlaceBuiltinFn> {
+        self.in_place.get(name).copied()
+    }
+
+    /// Check if a function is a builtin. This includes the prelude, as well as the
+    /// constructors of builtin types. Calls to builtins do not get renamed when
+    /// including a source file
+    pub fn is_builtin(name: &str) -> bool {
+        builtin_functions().any(|(builtin, _, _)| *builtin == name)
+            || IN_PLACE_FUNCTIONS
+                .iter()
+                .any(|(builtin, _, _)| *builtin == name)
+            || PRELUDE_FUNCTIONS
+                .iter()
+                .any(|(builtin, _)| *builtin == name)
+            || Builtins::types()
+                .iter()
+                .any(|ty| ty.variant_tag(name).is_some())
+    }
+
+    /// Check if a type is a builtin type. Builtin types do not get renamed when
+    /// including a source file
+    pub fn is_builtin_type(name: &str) -> bool {
+        name == ARRAY_TYPE
+            || name == MAP_TYPE
+            || BUILTIN_TYPES.iter().any(|(builtin, _)| *builtin == name)
+    }
+
+    /// Declarations of all the builtin functions, including the prelude and the
+    /// constructors of builtin types
+    pub fn declarations() -> Vec<FunctionDec> {
+        // We can unwrap since the declarations of builtins are always valid
+        builtin_functions()
+            .map(|(_, declaration, _)| declaration)
+            .chain(
+                IN_PLACE_FUNCTIONS
+                    .iter()
+                    .map(|(_, declaration, _)| declaration),
+            )
+            .map(|declaration| Construct::ext_declaration(declaration).unwrap().1)
+            .chain(Builtins::types().iter().flat_map(|ty| ty.constructors()))
+            .chain(
+                PRELUDE_FUNCTIONS.iter().map(|(_, declaration)| {
+                    Construct::function_declaration(declaration).unwrap().1
+                }),
+            )
+            .collect()
+    }
+
+    /// Declarations of all the builtin types
+    pub fn types() -> Vec<TypeDec> {
+        // We can unwrap since the declarations of builtins are always valid
+        BUILTIN_TYPES
+            .iter()
+            .map(|(_, declaration)| Construct::type_declaration(declaration).unwrap().1)
+            .chain(std::iter::once(Builtins::array_type()))
+            .chain(std::iter::once(Builtins::map_type()))
+            .collect()
+    }
+
+    /// Declaration of the generic array type. Arrays have a dedicated syntax, `[T]`,
+    /// and cannot be declared using regular jinko syntax
+    fn array_type() -> TypeDec {
+        let mut array = TypeDec::new(ARRAY_TYPE.to_owned(), vec![]);
+        array.set_generics(vec![TypeId::from("T")]);
+
+        array
+    }
+
+    /// Declaration of the generic map type. Maps keep their entries in insertion order,
+    /// and do not expose any field
+    fn map_type() -> TypeDec {
+        let mut map = TypeDec::new(MAP_TYPE.to_owned(), vec![]);
+        map.set_generics(vec![TypeId::from("K"), TypeId::from("V")]);
+
+        map
+    }
+}
+
+impl Default for Builtins {
+    fn default() -> Self {
+        Builtins::new()
+    }
+}
+
+/// Create a new range, going from `start` (included) to `end` (excluded)
+fn range(interpreter: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let mut data = args[0].data().to_vec();
+    data.extend_from_slice(args[1].data());
+
+    let fields = vec![
+        ("start".to_owned(), args[0].size()),
+        ("end".to_owned(), args[1].size()),
+    ];
+
+    // We can unwrap since builtin types are always declared
+    let ty = interpreter
+        .get_type(&"range".into())
+        .map(|ty| (*ty).clone())
+        .unwrap();
+
+    Ok(InstrKind::Expression(Some(ObjectInstance::new(
+        Some(ty),
+        data.len(),
+        data,
+        Some(fields),
+    ))))
+}
+
+/// Create an instance of one of the variants of a builtin sum type, such as `Some(1)`
+/// for `Option<int>`. The fields are given in the order of the variant's declaration
+fn variant_instance(
+    interpreter: &Interpreter,
+    ty: &TypeId,
+    variant: &str,
+    fields: Vec<ObjectInstance>,
+) -> ObjectInstance {
+    // We can unwrap since builtin types and their variants are always declared
+    let ty = interpreter.get_type(ty).map(|ty| (*ty).clone()).unwrap();
+    let tag = ty.variant_tag(variant).unwrap();
+
+    let fields: Vec<(String, ObjectInstance)> = ty.variants()[tag]
+        .fields()
+        .iter()
+        .map(|field| field.name().to_owned())
+        .zip(fields)
+        .collect();
+
+    ObjectInstance::from_variant(Some(ty), tag, &fields)
+}
+
+/// Wrap the result of a builtin which can fail in a `Result`: Either a value of type
+/// `ty`, or an error message
+fn result_instance(
+    interpreter: &Interpreter,
+    ty: &str,
+    result: Result<ObjectInstance, String>,
+) -> Result<InstrKind, JkError> {
+    let result_ty = TypeId::generic(
+        "Result".to_owned(),
+        vec![TypeId::from(ty), TypeId::from("string")],
+    );
+
+    let instance = match result {
+        Ok(value) => variant_instance(interpreter, &result_ty, "Ok", vec![value]),
+        Err(msg) => variant_instance(
+            interpreter,
+            &result_ty,
+            "Err",
+            vec![JkString::from(msg).to_instance()],
+        ),
+    };
+
+    Ok(InstrKind::Expression(Some(instance)))
+}
+
+/// Error emitted by array builtins
+fn array_error(msg: String, input: &str) -> JkError {
+    JkError::new(JkErrKind::Interpreter, msg, None, input.to_owned())
+}
+
+/// Create a new, empty array. The type of its elements needs to be given explicitly:
+/// `array::new<int>()`
+fn array_new(interpreter: &mut Interpreter, _: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    // The generic parameter `T` stands for the type of the elements in the scope of
+    // the call
+    let ty = interpreter
+        .get_type(&TypeId::array(TypeId::from("T")))
+        .map(|ty| (*ty).clone());
+
+    Ok(InstrKind::Expression(Some(ObjectInstance::from_elements(
+        ty,
+        vec![],
+    ))))
+}
+
+/// Get the amount of elements contained in an array
+fn array_len(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    Ok(InstrKind::Expression(Some(
+        JkInt::from(args[0].len() as i64).to_instance(),
+    )))
+}
+
+/// Add a value at the end of an array
+fn array_push(
+    _: &mut Interpreter,
+    array: &mut ObjectInstance,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    array.push(args[0].clone());
+
+    Ok(InstrKind::Statement)
+}
+
+/// Remove the last element of an array and return it, or `None` if the array is empty
+fn array_pop(
+    interpreter: &mut Interpreter,
+    array: &mut ObjectInstance,
+    _: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let ty = TypeId::generic("Option".to_owned(), vec![TypeId::from("T")]);
+
+    let option = match array.pop() {
+        Some(last) => variant_instance(interpreter, &ty, "Some", vec![last]),
+        None => variant_instance(interpreter, &ty, "None", vec![]),
+    };
+
+    Ok(InstrKind::Expression(Some(option)))
+}
+
+/// Get the last element of an array, or `None` if the array is empty
+fn array_last(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    // The generic parameter `T` stands for the type of the elements
+    let element_ty = interpreter
+        .get_type(&TypeId::from("T"))
+        .map(|ty| (*ty).clone());
+    let ty = TypeId::generic("Option".to_owned(), vec![TypeId::from("T")]);
+
+    let last = args[0]
+        .len()
+        .checked_sub(1)
+        .and_then(|idx| args[0].element(idx, element_ty));
+
+    let option = match last {
+        Some(last) => variant_instance(interpreter, &ty, "Some", vec![last]),
+        None => variant_instance(interpreter, &ty, "None", vec![]),
+    };
+
+    Ok(InstrKind::Expression(Some(option)))
+}
+
+/// Create a new array containing the elements of an array going from `start`
+/// (included) to `end` (excluded)
+fn array_slice(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let len = args[0].len();
+    let start = JkInt::from_instance(&args[1]).0;
+    let end = JkInt::from_instance(&args[2]).0;
+
+    if start < 0 || start > end || end > len as i64 {
+        return Err(array_error(
+            format!(
+                "invalid slice `{}..{}` of an array of length {}",
+                start, end, len
+            ),
+            "slice",
+        ));
+    }
+
+    let elements = (start as usize..end as usize)
+        .filter_map(|idx| args[0].element(idx, None))
+        .collect();
+
+    Ok(InstrKind::Expression(Some(ObjectInstance::from_elements(
+        args[0].ty().cloned(),
+        elements,
+    ))))
+}
+
+/// Error emitted by map builtins
+fn map_error(msg: String, input: &str) -> JkError {
+    JkError::new(JkErrKind::Interpreter, msg, None, input.to_owned())
+}
+
+/// Create a new, empty map. The types of its keys and values need to be given
+/// explicitly: `map::new<string, int>()`. Only some primitive types can be used as keys
+fn map_new(interpreter: &mut Interpreter, _: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    // The generic parameters `K` and `V` stand for the types of the keys and values
+    // in the scope of the call
+    let ty = interpreter
+        .get_type(&TypeId::generic(
+            MAP_TYPE.to_owned(),
+            vec![TypeId::from("K"), TypeId::from("V")],
+        ))
+        .map(|ty| (*ty).clone());
+
+    if let Some((key_ty, _)) = ty
+        .as_ref()
+        .map(|ty| ty.type_id())
+        .as_ref()
+        .and_then(|ty| ty.entry())
+    {
+        if !key_ty.is_hashable() {
+            return Err(map_error(
+                format!(
+                    "cannot use `{}` as the keys of a map: keys must be one of {}",
+                    key_ty.id(),
+                    HASHABLE_TYPES.join(", ")
+                ),
+                "map::new",
+            ));
+        }
+    }
+
+    Ok(InstrKind::Expression(Some(ObjectInstance::from_entries(
+        ty,
+        vec![],
+    ))))
+}
+
+/// Get the value associated with a key. Getting a key which is not in the map is an
+/// error
+fn map_get(interpreter: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    // The generic parameter `V` stands for the type of the values
+    let ty = interpreter
+        .get_type(&TypeId::from("V"))
+        .map(|ty| (*ty).clone());
+
+    match args[0].get(&args[1], ty) {
+        Some(value) => Ok(InstrKind::Expression(Some(value))),
+        None => Err(map_error(
+            format!("key `{}` not found in map", args[1]),
+            "get",
+        )),
+    }
+}
+
+/// Associate a value with a key. If the key is already present, its value is replaced
+/// and the entry keeps its position
+fn map_insert(
+    _: &mut Interpreter,
+    map: &mut ObjectInstance,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let mut args = args.into_iter();
+
+    // We can unwrap since the arguments were checked against the declaration
+    map.insert(args.next().unwrap(), args.next().unwrap());
+
+    Ok(InstrKind::Statement)
+}
+
+/// Remove the entry associated with a key, and return its value, or `None` if the key
+/// was not present
+fn map_remove(
+    interpreter: &mut Interpreter,
+    map: &mut ObjectInstance,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let ty = TypeId::generic("Option".to_owned(), vec![TypeId::from("V")]);
+
+    let option = match map.remove(&args[0]) {
+        Some(value) => variant_instance(interpreter, &ty, "Some", vec![value]),
+        None => variant_instance(interpreter, &ty, "None", vec![]),
+    };
+
+    Ok(InstrKind::Expression(Some(option)))
+}
+
+/// Check if a key is present in a map
+fn map_contains(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    Ok(InstrKind::Expression(Some(
+        JkBool::from(args[0].contains(&args[1])).to_instance(),
+    )))
+}
+
+/// Get the value contained in an option. Unwrapping `None` is an error
+fn option_unwrap(
+    interpreter: &mut Interpreter,
+    args: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let some = args[0].ty().and_then(|ty| ty.variant_tag("Some"));
+
+    if some.is_none() || args[0].tag() != some {
+        return Err(JkError::new(
+            JkErrKind::Interpreter,
+            String::from("called `unwrap` on a `None` value"),
+            None,
+            String::from("unwrap"),
+        ));
+    }
+
+    // The generic parameter `T` stands for the type of the contained value
+    let ty = interpreter
+        .get_type(&TypeId::from("T"))
+        .map(|ty| (*ty).clone());
+
+    Ok(InstrKind::Expression(args[0].field_instance("value", ty)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::execute;
+    use crate::JkChar;
+
+    #[test]
+    fn t_declarations_match_names() {
+        Builtins::declarations()
+            .iter()
+            .zip(builtin_functions())
+            .for_each(|(dec, (name, _, _))| assert_eq!(dec.name(), *name));
+    }
+
+    #[test]
+    fn t_types_match_names() {
+        Builtins::types()
+            .iter()
+            .zip(BUILTIN_TYPES.iter())
+            .for_each(|(ty, (name, _))| assert_eq!(ty.name(), *name));
+    }
+
+    #[test]
+    fn t_array_builtins() {
+        assert_eq!(
+            execute("mut a = array::new<int>(); a.push(1); a.push(2); a.len()").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(2).to_instance()))
+        );
+        assert_eq!(
+            execute("mut a = [1, 2]; a.pop(); a.len()").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(1).to_instance()))
+        );
+        assert_eq!(
+            execute("len(slice([1, 2], 1, 2))").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(1).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_array_pop() {
+        assert_eq!(
+            execute("mut a = [1, 2]; a.pop().unwrap()").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(2).to_instance()))
+        );
+        assert_eq!(
+            execute("mut a = array::new<int>(); a.pop().is_none()").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_array_copies_untouched() {
+        assert_eq!(
+            execute("mut a = [1]; b = a; a.push(2); b.len()").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(1).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_array_last() {
+        assert_eq!(
+            execute("unwrap(last([1, 2]))").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(2).to_instance()))
+        );
+        assert_eq!(
+            execute("is_none(last(array::new<int>()))").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_array_builtins_invalid() {
+        assert!(execute("a = [1]; a.push(2)").is_err());
+        assert!(execute("slice([1], 0, 2)").is_err());
+        assert!(execute("slice([1], 1, 0)").is_err());
+    }
+
+    #[test]
+    fn t_map_builtins() {
+        let map = "mut m = map::new<string, int>(); m.insert(\"a\", 1); m.insert(\"b\", 2);";
+
+        assert_eq!(
+            execute(&format!("{} m.insert(\"a\", 3); m.get(\"a\")", map)).unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+        assert_eq!(
+            execute(&format!("{} m.remove(\"a\"); m.contains(\"a\")", map)).unwrap(),
+            InstrKind::Expression(Some(JkBool::from(false).to_instance()))
+        );
+        assert_eq!(
+            execute(&format!("{} m.remove(\"a\").unwrap()", map)).unwrap(),
+            InstrKind::Expression(Some(JkInt::from(1).to_instance()))
+        );
+        assert_eq!(
+            execute(&format!("{} m.remove(\"c\").is_none()", map)).unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_map_copies_untouched() {
+        assert_eq!(
+            execute("mut m = { 'a': 1 }; n = m; m.insert('b', 2); n.contains('b')").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(false).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_map_keeps_insertion_order() {
+        let (keys, values) = match execute("mut m = { 1: 'a', 2: 'b', 3: 'c' }; m.remove(2); m.insert(4, 'd'); m.insert(1, 'e'); m").unwrap() {
+            InstrKind::Expression(Some(map)) => (map.keys(None), map.values(None)),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(
+            keys,
+            vec![
+                JkInt::from(1).to_instance(),
+                JkInt::from(3).to_instance(),
+                JkInt::from(4).to_instance()
+            ]
+        );
+        assert_eq!(values[0], JkChar::from('e').to_instance());
+    }
+
+    #[test]
+    fn t_map_builtins_invalid() {
+        assert!(execute("map::new<int, int>().get(1)").is_err());
+        assert!(execute("map::new<float, int>()").is_err());
+    }
+
+    #[test]
+    fn t_option_builtins() {
+        assert_eq!(
+            execute("Some(4).unwrap()").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(4).to_instance()))
+        );
+        assert_eq!(
+            execute("None<int>().unwrap_or(2)").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(2).to_instance()))
+        );
+        assert_eq!(
+            execute("None<int>().is_none()").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+        assert!(execute("None<int>().unwrap()").is_err());
+    }
+
+    #[test]
+    fn t_result_builtins() {
+        assert_eq!(
+            execute("Ok<int, string>(1).ok().unwrap()").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(1).to_instance()))
+        );
+        assert_eq!(
+            execute("Err<int, string>(\"nope\").ok().is_some()").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(false).to_instance()))
+        );
+        assert_eq!(
+            execute("Err<int, char>('e').is_err()").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_prelude_is_builtin() {
+        assert!(Builtins::is_builtin("unwrap_or"));
+        assert!(Builtins::is_builtin("Some"));
+        assert!(!Builtins::is_builtin("Circle"));
+    }
+}
@@ -0,0 +1,321 @@
+//! String manipulation. Strings are indexed by characters rather than by bytes, and
+//! are never modified in place: Each builtin returns a new string.
+
+use super::{variant_instance, Builtin};
+use crate::instruction::TypeId;
+use crate::{
+    FromObjectInstance, InstrKind, Interpreter, JkBool, JkChar, JkErrKind, JkError, JkInt,
+    JkString, ObjectInstance, ToObjectInstance,
+};
+
+pub(super) const FUNCTIONS: [Builtin; 13] = [
+    (
+        "string::len",
+        "ext func string::len(s: string) -> int;",
+        len,
+    ),
+    (
+        "string::concat",
+        "ext func string::concat(lhs: string, rhs: string) -> string;",
+        concat,
+    ),
+    (
+        "string::contains",
+        "ext func string::contains(s: string, pattern: string) -> bool;",
+        contains,
+    ),
+    (
+        "string::starts_with",
+        "ext func string::starts_with(s: string, pattern: string) -> bool;",
+        starts_with,
+    ),
+    (
+        "string::ends_with",
+        "ext func string::ends_with(s: string, pattern: string) -> bool;",
+        ends_with,
+    ),
+    (
+        "string::find",
+        "ext func string::find(s: string, pattern: string) -> Option<int>;",
+        find,
+    ),
+    (
+        "string::trim",
+        "ext func string::trim(s: string) -> string;",
+        trim,
+    ),
+    (
+        "string::to_upper",
+        "ext func string::to_upper(s: string) -> string;",
+        to_upper,
+    ),
+    (
+        "string::to_lower",
+        "ext func string::to_lower(s: string) -> string;",
+        to_lower,
+    ),
+    (
+        "string::replace",
+        "ext func string::replace(s: string, from: string, to: string) -> string;",
+        replace,
+    ),
+    (
+        "string::split",
+        "ext func string::split(s: string, separator: string) -> [string];",
+        split,
+    ),
+    (
+        "string::slice",
+        "ext func string::slice(s: string, start: int, end: int) -> string;",
+        slice,
+    ),
+    (
+        "string::chars",
+        "ext func string::chars(s: string) -> [char];",
+        chars,
+    ),
+];
+
+/// Get the string contained in each argument
+fn strings(args: &[ObjectInstance]) -> Vec<String> {
+    args.iter()
+        .map(|arg| JkString::from_instance(arg).0)
+        .collect()
+}
+
+/// Wrap a new string in an instruction's result
+fn string_result(value: String) -> Result<InstrKind, JkError> {
+    Ok(InstrKind::Expression(Some(
+        JkString::from(value).to_instance(),
+    )))
+}
+
+/// Wrap a boolean in an instruction's result
+fn bool_result(value: bool) -> Result<InstrKind, JkError> {
+    Ok(InstrKind::Expression(Some(
+        JkBool::from(value).to_instance(),
+    )))
+}
+
+/// Create a new array out of some elements of a primitive type
+fn array_result(
+    interpreter: &Interpreter,
+    element: &str,
+    elements: Vec<ObjectInstance>,
+) -> Result<InstrKind, JkError> {
+    let ty = interpreter
+        .get_type(&TypeId::array(TypeId::from(element)))
+        .map(|ty| (*ty).clone());
+
+    Ok(InstrKind::Expression(Some(ObjectInstance::from_elements(
+        ty, elements,
+    ))))
+}
+
+/// Get the amount of characters in a string
+fn len(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let len = JkString::from_instance(&args[0]).0.chars().count();
+
+    Ok(InstrKind::Expression(Some(
+        JkInt::from(len as i64).to_instance(),
+    )))
+}
+
+/// Create a new string containing two strings, one after the other
+fn concat(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let strings = strings(&args);
+
+    string_result(format!("{}{}", strings[0], strings[1]))
+}
+
+/// Check if a string contains a pattern
+fn contains(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let strings = strings(&args);
+
+    bool_result(strings[0].contains(strings[1].as_str()))
+}
+
+/// Check if a string starts with a pattern
+fn starts_with(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let strings = strings(&args);
+
+    bool_result(strings[0].starts_with(strings[1].as_str()))
+}
+
+/// Check if a string ends with a pattern
+fn ends_with(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let strings = strings(&args);
+
+    bool_result(strings[0].ends_with(strings[1].as_str()))
+}
+
+/// Get the index of the first character of the first occurence of a pattern in a
+/// string, if the pattern is present
+fn find(interpreter: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let strings = strings(&args);
+    let ty = TypeId::generic("Option".to_owned(), vec![TypeId::from("int")]);
+
+    let option = match strings[0].find(strings[1].as_str()) {
+        Some(byte_idx) => {
+            let idx = strings[0][..byte_idx].chars().count();
+            variant_instance(
+                interpreter,
+                &ty,
+                "Some",
+                vec![JkInt::from(idx as i64).to_instance()],
+            )
+        }
+        None => variant_instance(interpreter, &ty, "None", vec![]),
+    };
+
+    Ok(InstrKind::Expression(Some(option)))
+}
+
+/// Create a new string without the leading and trailing whitespaces of a string
+fn trim(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    string_result(JkString::from_instance(&args[0]).0.trim().to_owned())
+}
+
+/// Create a new string with all the characters of a string in uppercase
+fn to_upper(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    string_result(JkString::from_instance(&args[0]).0.to_uppercase())
+}
+
+/// Create a new string with all the characters of a string in lowercase
+fn to_lower(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    string_result(JkString::from_instance(&args[0]).0.to_lowercase())
+}
+
+/// Create a new string where every occurence of a pattern is replaced by another
+/// string
+fn replace(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let strings = strings(&args);
+
+    string_result(strings[0].replace(strings[1].as_str(), &strings[2]))
+}
+
+/// Split a string on each occurence of a separator. Splitting on an empty separator
+/// is an error
+fn split(interpreter: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let strings = strings(&args);
+
+    if strings[1].is_empty() {
+        return Err(JkError::new(
+            JkErrKind::Interpreter,
+            String::from("cannot split a string on an empty separator"),
+            None,
+            String::from("string::split"),
+        ));
+    }
+
+    let parts = strings[0]
+        .split(strings[1].as_str())
+        .map(|part| JkString::from(part).to_instance())
+        .collect();
+
+    array_result(interpreter, "string", parts)
+}
+
+/// Create a new string containing the characters of a string going from `start`
+/// (included) to `end` (excluded)
+fn slice(_: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let chars: Vec<char> = JkString::from_instance(&args[0]).0.chars().collect();
+    let start = JkInt::from_instance(&args[1]).0;
+    let end = JkInt::from_instance(&args[2]).0;
+
+    if start < 0 || start > end || end > chars.len() as i64 {
+        return Err(JkError::new(
+            JkErrKind::Interpreter,
+            format!(
+                "invalid slice `{}..{}` of a string of length {}",
+                start,
+                end,
+                chars.len()
+            ),
+            None,
+            String::from("string::slice"),
+        ));
+    }
+
+    string_result(chars[start as usize..end as usize].iter().collect())
+}
+
+/// Get the characters of a string
+fn chars(interpreter: &mut Interpreter, args: Vec<ObjectInstance>) -> Result<InstrKind, JkError> {
+    let chars = JkString::from_instance(&args[0])
+        .0
+        .chars()
+        .map(|c| JkChar::from(c).to_instance())
+        .collect();
+
+    array_result(interpreter, "char", chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::execute;
+    use crate::{InstrKind, JkBool, JkInt, JkString, ToObjectInstance};
+
+    fn string(value: &str) -> InstrKind {
+        InstrKind::Expression(Some(JkString::from(value).to_instance()))
+    }
+
+    #[test]
+    fn t_string_builtins() {
+        assert_eq!(
+            execute("string::len(\"été\")").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+        assert_eq!(
+            execute("string::concat(\"jin\", \"ko\")").unwrap(),
+            string("jinko")
+        );
+        assert_eq!(
+            execute("string::to_upper(string::trim(\"  jinko \"))").unwrap(),
+            string("JINKO")
+        );
+        assert_eq!(
+            execute("string::replace(\"a-b-c\", \"-\", \"+\")").unwrap(),
+            string("a+b+c")
+        );
+        assert_eq!(
+            execute("string::slice(\"été\", 1, 3)").unwrap(),
+            string("té")
+        );
+        assert_eq!(
+            execute("string::starts_with(\"jinko\", \"jin\")").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_string_find() {
+        assert_eq!(
+            execute("string::find(\"éléphant\", \"ph\").unwrap()").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+        assert_eq!(
+            execute("string::find(\"jinko\", \"rust\").is_none()").unwrap(),
+            InstrKind::Expression(Some(JkBool::from(true).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_string_split() {
+        assert_eq!(
+            execute("len(string::split(\"a, b, c\", \", \"))").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+        assert_eq!(
+            execute("len(string::chars(\"été\"))").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_string_builtins_invalid() {
+        assert!(execute("string::split(\"abc\", \"\")").is_err());
+        assert!(execute("string::slice(\"abc\", 2, 4)").is_err());
+        assert!(execute("string::slice(\"abc\", 2, 1)").is_err());
+    }
+}
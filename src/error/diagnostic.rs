@@ -0,0 +1,165 @@
+//! Errors are rendered as diagnostics, in a way similar to rustc's. A diagnostic shows
+//! what kind of error happened, where it happened in the source code, and the source
+//! line containing the error, with the faulty part of it underlined. Notes and help
+//! messages are displayed last, in the order they were added to the error.
+//!
+//! ```text
+//! type error: wrong type for argument `x` of function `f`: expected `int`, got `float`
+//!  --> source.jk:5:7
+//!   |
+//! 5 | c = f(2.5);
+//!   |       ^^^
+//!   = note: function declared as `func f(x: int) -> int` at source.jk:4:1
+//! ```
+
+use std::fmt::{Formatter, Result};
+
+use colored::Colorize;
+
+use super::{Annotation, JkErrKind, JkErrSpaceLocation, JkError};
+
+/// Display a short description of the kind of error
+fn kind_str(kind: JkErrKind) -> &'static str {
+    match kind {
+        JkErrKind::Parsing => "parsing error",
+        JkErrKind::Interpreter => "interpreter error",
+        JkErrKind::IO => "io error",
+        JkErrKind::TypeChecker => "type error",
+    }
+}
+
+/// Render the line of source code containing the location, and underline the part of
+/// it that the location spans over. Locations spanning over multiple lines are
+/// underlined until the end of their first line
+fn render_snippet(f: &mut Formatter<'_>, loc: &JkErrSpaceLocation, padding: &str) -> Result {
+    let line = loc.source_line();
+    let column = loc.column();
+
+    // Keep the tabulations of the source line so that the underline is aligned
+    let indent: String = line
+        .chars()
+        .take(column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+
+    let width = match loc.snippet().lines().next() {
+        Some(first_line) => first_line.chars().count().max(1),
+        None => 1,
+    };
+
+    writeln!(f, "{} {}", padding, "|".blue().bold())?;
+    writeln!(
+        f,
+        "{} {} {}",
+        loc.line().to_string().blue().bold(),
+        "|".blue().bold(),
+        line
+    )?;
+    write!(
+        f,
+        "{} {} {}{}",
+        padding,
+        "|".blue().bold(),
+        indent,
+        "^".repeat(width).red().bold()
+    )
+}
+
+/// Render an error as a complete diagnostic
+pub(super) fn render(f: &mut Formatter<'_>, error: &JkError) -> Result {
+    write!(
+        f,
+        "{}: {}",
+        kind_str(error.kind).red().bold(),
+        error.msg.bold()
+    )?;
+
+    // The gutter needs to be as wide as the line number displayed in it
+    let padding = match &error.loc {
+        Some(loc) => " ".repeat(loc.line().to_string().len()),
+        None => String::from(" "),
+    };
+
+    if let Some(loc) = &error.loc {
+        writeln!(f)?;
+        writeln!(f, "{}{} {}", padding, "-->".blue().bold(), loc)?;
+        render_snippet(f, loc, &padding)?;
+    }
+
+    error.annotations.iter().try_for_each(|annotation| {
+        let (label, content) = match annotation {
+            Annotation::Note(note) => ("= note:", note),
+            Annotation::Help(help) => ("= help:", help),
+        };
+
+        write!(f, "\n{} {} {}", padding, label.bold(), content)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Source;
+
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    fn located_error(input: &str, start: usize, end: usize) -> JkError {
+        let source = Source::new(input.to_owned(), Some(PathBuf::from("src.jk")));
+        let loc = JkErrSpaceLocation::new(Rc::new(source), start, end);
+
+        JkError::new(
+            JkErrKind::TypeChecker,
+            "invalid types".to_owned(),
+            Some(loc),
+            input.to_owned(),
+        )
+    }
+
+    #[test]
+    fn t_render_located_error() {
+        colored::control::set_override(false);
+
+        let err = located_error("a = 1;\nb = a + 'c';", 11, 18);
+
+        assert_eq!(
+            err.to_string(),
+            "type error: invalid types\n --> src.jk:2:5\n  |\n2 | b = a + 'c';\n  |     ^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn t_render_notes_and_help() {
+        colored::control::set_override(false);
+
+        let err = JkError::new(JkErrKind::IO, "no file".to_owned(), None, String::new())
+            .with_note("first note".to_owned())
+            .with_note("second note".to_owned())
+            .with_help("some help".to_owned());
+
+        assert_eq!(
+            err.to_string(),
+            "io error: no file\n  = note: first note\n  = note: second note\n  = help: some help"
+        );
+    }
+
+    #[test]
+    fn t_render_empty_location() {
+        colored::control::set_override(false);
+
+        let err = located_error("a = ", 4, 4);
+
+        assert!(err.to_string().ends_with("1 | a = \n  |     ^"));
+    }
+
+    #[test]
+    fn t_render_multiline_location() {
+        colored::control::set_override(false);
+
+        let err = located_error("\tif true {\n\t}", 1, 13);
+
+        assert!(err
+            .to_string()
+            .ends_with("1 | \tif true {\n  | \t^^^^^^^^^"));
+    }
+}
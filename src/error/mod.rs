@@ -1,35 +1,158 @@
 //! The Error module contains helpful wrapper around possible errors in jinko. They
 //! are used by the interpreter as well as the parser.
 
-use colored::Colorize;
+mod diagnostic;
 
-/// What kind of error we are dealing with: Either a parsing error, or a behavioural one.
+use crate::parser::Parser;
+use crate::ObjectInstance;
+
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// What kind of error we are dealing with: Either a parsing error, a behavioural one,
+/// or an error detected by the typechecker before executing anything.
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(u8)]
 pub enum JkErrKind {
     Parsing,
     Interpreter,
     IO,
+    TypeChecker,
 }
 
-/// Contains indications vis-a-vis the error's location in the source file
+/// Source code given to the interpreter, either from a file or from the REPL. Every
+/// location points into a source
 #[derive(Debug, PartialEq)]
-pub struct JkErrSpaceLocation(pub usize, pub usize);
+pub struct Source {
+    path: Option<PathBuf>,
+    content: String,
+}
+
+impl Source {
+    /// Create a new source from its content and the path of the file it was read from
+    pub fn new(content: String, path: Option<PathBuf>) -> Source {
+        Source { path, content }
+    }
+
+    /// Path of the source, if it comes from a file
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// Content of the source
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+}
+
+/// Contains indications vis-a-vis the error's location in the source file. A location
+/// spans from a starting byte to an ending byte in a given source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JkErrSpaceLocation {
+    source: Rc<Source>,
+    start: usize,
+    end: usize,
+}
 
 impl JkErrSpaceLocation {
-    /// Line of the error
+    /// Create a new location spanning from `start` to `end` (excluded) in `source`
+    pub fn new(source: Rc<Source>, start: usize, end: usize) -> JkErrSpaceLocation {
+        JkErrSpaceLocation { source, start, end }
+    }
+
+    /// Create a new location spanning from the start of `self` to the end of `other`.
+    /// Both locations must point into the same source
+    pub fn until(&self, other: &JkErrSpaceLocation) -> JkErrSpaceLocation {
+        JkErrSpaceLocation::new(self.source.clone(), self.start, other.end)
+    }
+
+    /// Source the location points into
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Path of the file containing the location, if any
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.source.path()
+    }
+
+    /// First byte of the location
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Byte following the last byte of the location
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Line of the error, starting at 1
     pub fn line(&self) -> usize {
-        self.0
+        self.source.content[..self.start].matches('\n').count() + 1
     }
 
-    /// Offset of the error on the contained line
-    pub fn offset(&self) -> usize {
-        self.1
+    /// Column of the error on the contained line, starting at 1
+    pub fn column(&self) -> usize {
+        let line_start = self.line_start();
+
+        self.source.content[line_start..self.start].chars().count() + 1
+    }
+
+    /// Full line of source code containing the start of the location
+    pub fn source_line(&self) -> &str {
+        let line_start = self.line_start();
+
+        self.source.content[line_start..]
+            .lines()
+            .next()
+            .unwrap_or_default()
+    }
+
+    /// Source code the location spans over
+    pub fn snippet(&self) -> &str {
+        &self.source.content[self.start..self.end]
+    }
+
+    /// Byte at which the line containing the start of the location begins
+    fn line_start(&self) -> usize {
+        match self.source.content[..self.start].rfind('\n') {
+            Some(idx) => idx + 1,
+            None => 0,
+        }
     }
 }
 
-/// The actual error type
-// FIXME: Remove `Option` once input tracking is implemented
+impl std::fmt::Display for JkErrSpaceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = match self.path() {
+            Some(path) => path.display().to_string(),
+            None => String::from("<input>"),
+        };
+
+        write!(f, "{}:{}:{}", path, self.line(), self.column())
+    }
+}
+
+/// Additional information given alongside an error
+#[derive(Debug, PartialEq)]
+enum Annotation {
+    Note(String),
+    Help(String),
+}
+
+/// Signals used to leave a loop or a function early. They travel through the error
+/// channel, so that every instruction forwards them untouched until they reach the loop
+/// or function call they target. A signal escaping its target is reported as a regular
+/// error
+#[derive(Debug, PartialEq)]
+pub enum ControlFlow {
+    Break(Option<ObjectInstance>),
+    Continue,
+    Return(Option<ObjectInstance>),
+}
+
+/// The actual error type. Errors which do not originate from the source code, such as
+/// IO errors, do not have a location
 #[derive(Debug, PartialEq)]
 pub struct JkError {
     kind: JkErrKind,
@@ -37,6 +160,12 @@ pub struct JkError {
 
     loc: Option<JkErrSpaceLocation>,
     input: String,
+
+    /// Notes and help messages, displayed after the error
+    annotations: Vec<Annotation>,
+
+    /// Control flow signal carried by the error, if it is not an actual error
+    signal: Option<Box<ControlFlow>>,
 }
 
 impl JkError {
@@ -52,17 +181,88 @@ impl JkError {
             msg,
             loc,
             input,
+            annotations: Vec::new(),
+            signal: None,
         }
     }
 
+    /// Create a new control flow signal, located on the instruction emitting it
+    pub fn from_signal(
+        signal: ControlFlow,
+        loc: Option<JkErrSpaceLocation>,
+        input: String,
+    ) -> JkError {
+        let msg = match signal {
+            ControlFlow::Break(_) => "`break` outside of a loop",
+            ControlFlow::Continue => "`continue` outside of a loop",
+            ControlFlow::Return(_) => "`return` outside of a function",
+        };
+
+        let mut error = JkError::new(JkErrKind::Interpreter, String::from(msg), loc, input);
+        error.signal = Some(Box::new(signal));
+
+        error
+    }
+
+    /// Get the control flow signal carried by the error, if any
+    pub fn signal(&self) -> Option<&ControlFlow> {
+        self.signal.as_deref()
+    }
+
+    /// Consume the error and get the control flow signal it carries, if any
+    pub fn into_signal(self) -> Option<ControlFlow> {
+        self.signal.map(|signal| *signal)
+    }
+
+    /// Add a note to the error, giving more context about it
+    pub fn with_note(mut self, note: String) -> JkError {
+        self.annotations.push(Annotation::Note(note));
+
+        self
+    }
+
+    /// Add a help message to the error, suggesting a way to fix it
+    pub fn with_help(mut self, help: String) -> JkError {
+        self.annotations.push(Annotation::Help(help));
+
+        self
+    }
+
     /// Display the error on stderr before exiting the program
-    pub fn exit(&self) {
-        eprintln!("{}", self.to_string());
+    pub fn exit(&self) -> ! {
+        eprintln!("{}", self);
 
         // The exit code depends on the kind of error
         std::process::exit(self.kind as i32 + 1);
     }
 
+    /// Display multiple errors on stderr before exiting the program. The exit code
+    /// is decided by the first error of the list
+    pub fn exit_many(errors: &[JkError]) -> ! {
+        errors.iter().for_each(|e| eprintln!("{}\n", e));
+
+        match errors.first() {
+            Some(first) => std::process::exit(first.kind as i32 + 1),
+            None => std::process::exit(0),
+        }
+    }
+
+    /// Location of the error in the source code, if known
+    pub fn loc(&self) -> Option<&JkErrSpaceLocation> {
+        self.loc.as_ref()
+    }
+
+    /// Attach a location to the error, if it does not have one already. This allows an
+    /// instruction to locate errors emitted by the instructions it contains, without
+    /// overriding their more precise locations
+    pub fn locate(mut self, loc: Option<&JkErrSpaceLocation>) -> JkError {
+        if self.loc.is_none() {
+            self.loc = loc.cloned();
+        }
+
+        self
+    }
+
     /// What kind of error the error is
     #[cfg(test)]
     pub fn kind(&self) -> JkErrKind {
@@ -78,8 +278,7 @@ impl JkError {
 
 impl std::fmt::Display for JkError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // FIXME: Add better formatting
-        write!(f, "ErrorKind: {:?}\nInfo: {}", self.kind, self.msg.red())
+        diagnostic::render(f, self)
     }
 }
 
@@ -89,9 +288,76 @@ impl std::convert::From<std::io::Error> for JkError {
     }
 }
 
-// FIXME: Improve formatting, current output is barren
 impl std::convert::From<nom::Err<(&str, nom::error::ErrorKind)>> for JkError {
     fn from(e: nom::Err<(&str, nom::error::ErrorKind)>) -> Self {
-        JkError::new(JkErrKind::Parsing, e.to_string(), None, "".to_owned())
+        // If the error points into the source code, we can show exactly where the
+        // parser stopped. Otherwise, the parser might have given us a message instead
+        let (msg, loc) = match &e {
+            nom::Err::Error((input, kind)) | nom::Err::Failure((input, kind)) => {
+                match Parser::location(input, input) {
+                    Some(loc) => (format!("invalid syntax: {}", kind.description()), Some(loc)),
+                    None => (e.to_string(), None),
+                }
+            }
+            nom::Err::Incomplete(_) => (e.to_string(), None),
+        };
+
+        JkError::new(JkErrKind::Parsing, msg, loc, "".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(input: &str, start: usize, end: usize) -> JkErrSpaceLocation {
+        JkErrSpaceLocation::new(Rc::new(Source::new(input.to_owned(), None)), start, end)
+    }
+
+    #[test]
+    fn t_location_first_line() {
+        let loc = location("a = 12;", 4, 6);
+
+        assert_eq!(loc.line(), 1);
+        assert_eq!(loc.column(), 5);
+        assert_eq!(loc.snippet(), "12");
+        assert_eq!(loc.source_line(), "a = 12;");
+    }
+
+    #[test]
+    fn t_location_other_line() {
+        let loc = location("a = 12;\nb = 15;\n\nc = a + b;", 17, 22);
+
+        assert_eq!(loc.line(), 4);
+        assert_eq!(loc.column(), 1);
+        assert_eq!(loc.snippet(), "c = a");
+        assert_eq!(loc.source_line(), "c = a + b;");
+    }
+
+    #[test]
+    fn t_location_until() {
+        let lhs = location("x = 1 + 2", 4, 5);
+        let rhs = location("x = 1 + 2", 8, 9);
+
+        assert_eq!(lhs.until(&rhs).snippet(), "1 + 2");
+    }
+
+    #[test]
+    fn t_location_display() {
+        let source = Source::new("\n  a".to_owned(), Some(PathBuf::from("file.jk")));
+        let loc = JkErrSpaceLocation::new(Rc::new(source), 3, 4);
+
+        assert_eq!(loc.to_string(), "file.jk:2:3");
+    }
+
+    #[test]
+    fn t_locate_keeps_first_location() {
+        let first = location("a\nb", 0, 1);
+        let second = location("a\nb", 2, 3);
+
+        let err = JkError::new(JkErrKind::Interpreter, String::new(), None, String::new());
+        let err = err.locate(Some(&first)).locate(Some(&second));
+
+        assert_eq!(err.loc(), Some(&first));
     }
 }
@@ -0,0 +1,180 @@
+//! Instances are displayed the way they would be written in jinko: Arrays and maps
+//! like their literals, variants of sum types like calls to their constructor, and
+//! other custom types along with the name of their fields, such as `Point(x: 1, y: 2)`.
+//! The types of fields and elements are looked up in an interpreter when one is
+//! available. Values whose type cannot be found are displayed as `..`.
+
+use super::ObjectInstance;
+use crate::instruction::{DecArg, TypeDec, TypeId};
+use crate::{FromObjectInstance, Instruction, Interpreter, JkConstant};
+
+/// Value displayed in place of values whose type is unknown
+const UNKNOWN: &str = "..";
+
+impl ObjectInstance {
+    /// Display an instance, looking up the types of its fields and elements in the
+    /// given interpreter
+    pub fn display(&self, interpreter: &Interpreter) -> String {
+        display(self, Some(interpreter))
+    }
+}
+
+impl std::fmt::Display for ObjectInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", display(self, None))
+    }
+}
+
+/// Get the declaration of a type. Primitive types are always known, while other
+/// types can only be found in an interpreter
+fn type_dec(type_id: &TypeId, interpreter: Option<&Interpreter>) -> Option<TypeDec> {
+    match type_id.is_primitive() {
+        true => Some(TypeDec::from(type_id.id())),
+        false => interpreter?.get_type(type_id).map(|ty| (*ty).clone()),
+    }
+}
+
+/// Display a list of values, separated by commas
+fn join(values: impl Iterator<Item = String>) -> String {
+    values.collect::<Vec<String>>().join(", ")
+}
+
+/// Display an instance, and the values it contains recursively
+fn display(instance: &ObjectInstance, interpreter: Option<&Interpreter>) -> String {
+    let ty = match instance.ty() {
+        Some(ty) => ty,
+        None => return String::new(),
+    };
+
+    match ty.name() {
+        "int" => JkConstant::<i64>::from_instance(instance).print(),
+        "float" => JkConstant::<f64>::from_instance(instance).print(),
+        "char" => JkConstant::<char>::from_instance(instance).print(),
+        "string" => JkConstant::<String>::from_instance(instance).print(),
+        "bool" => JkConstant::<bool>::from_instance(instance).print(),
+        _ if ty.is_sum() => match instance.variant() {
+            Some(variant) if variant.fields().is_empty() => variant.name().to_owned(),
+            Some(variant) => format!(
+                "{}({})",
+                variant.name(),
+                join(
+                    fields(instance, variant.fields(), interpreter)
+                        .into_iter()
+                        .map(|(_, value)| value)
+                )
+            ),
+            None => String::from(UNKNOWN),
+        },
+        _ => {
+            let type_id = ty.type_id();
+            match (type_id.element(), type_id.entry()) {
+                (Some(element), _) => match type_dec(element, interpreter) {
+                    Some(element) => format!(
+                        "[{}]",
+                        join(
+                            instance
+                                .elements(Some(element))
+                                .iter()
+                                .map(|element| display(element, interpreter))
+                        )
+                    ),
+                    None => format!("[{}]", UNKNOWN),
+                },
+                (_, Some((key, value))) => {
+                    match (type_dec(key, interpreter), type_dec(value, interpreter)) {
+                        (Some(key), Some(value)) => format!(
+                            "{{{}}}",
+                            join(
+                                instance
+                                    .keys(Some(key))
+                                    .iter()
+                                    .zip(instance.values(Some(value)).iter())
+                                    .map(|(key, value)| format!(
+                                        "{}: {}",
+                                        display(key, interpreter),
+                                        display(value, interpreter)
+                                    ))
+                            )
+                        ),
+                        _ => format!("{{{}}}", UNKNOWN),
+                    }
+                }
+                _ if ty.fields().is_empty() => ty.name().to_owned(),
+                _ => format!(
+                    "{}({})",
+                    ty.name(),
+                    join(
+                        fields(instance, ty.fields(), interpreter)
+                            .into_iter()
+                            .map(|(name, value)| format!("{}: {}", name, value))
+                    )
+                ),
+            }
+        }
+    }
+}
+
+/// Display each field of an instance, along with its name
+fn fields<'a>(
+    instance: &ObjectInstance,
+    fields: &'a [DecArg],
+    interpreter: Option<&Interpreter>,
+) -> Vec<(&'a str, String)> {
+    fields
+        .iter()
+        .map(|field| {
+            let value = type_dec(field.get_type(), interpreter)
+                .and_then(|ty| instance.field_instance(field.name(), Some(ty)))
+                .map(|value| display(&value, interpreter))
+                .unwrap_or_else(|| String::from(UNKNOWN));
+
+            (field.name(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::execute_in;
+    use crate::{InstrKind, Interpreter};
+
+    /// Display the value of the input, with and without the interpreter it was
+    /// executed in
+    fn display(input: &str) -> (String, String) {
+        let mut interpreter = Interpreter::new();
+
+        match execute_in(input, &mut interpreter).unwrap() {
+            InstrKind::Expression(Some(instance)) => {
+                (instance.to_string(), instance.display(&interpreter))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn t_display_primitives() {
+        assert_eq!(display("[1, 2]").1, "[1, 2]");
+        assert_eq!(display("Some(\"jinko\")").1, "Some(\"jinko\")");
+    }
+
+    #[test]
+    fn t_display_custom_types() {
+        let input = "type P(x: int); type Line(start: P, end: P); Line { P { 1 }, P { 2 } }";
+
+        assert_eq!(display(input).1, "Line(start: P(x: 1), end: P(x: 2))");
+    }
+
+    #[test]
+    fn t_display_nested_elements() {
+        let input = "type P(x: int); [P { 1 }, P { 2 }]";
+
+        assert_eq!(display(input).1, "[P(x: 1), P(x: 2)]");
+    }
+
+    #[test]
+    fn t_display_without_interpreter() {
+        let input = "type P(x: int); type Line(start: P, len: int); Line { P { 1 }, 4 }";
+
+        assert_eq!(display(input).0, "Line(start: .., len: 4)");
+    }
+}
@@ -0,0 +1,315 @@
+//! Iterators are used by `for` loops to go over the values contained in an instance.
+//! Each call to `next()` returns a new instance, or `None` once the iterator is
+//! exhausted. Ranges yield integers, strings yield characters, arrays yield their
+//! elements, and maps yield their keys in insertion order.
+//!
+//! `for i in range(0, 15) { ... }`
+//! `for c in "jinko" { ... }`
+//! `for elt in [1, 2, 3] { ... }`
+//! `for key in { "a": 1, "b": 2 } { ... }`
+//!
+//! Custom types can be iterated over by declaring two functions associated to them.
+//! `next` returns the item at the current position of the iterator, or `None` once
+//! there are no more items, and `advance` returns the iterator positioned on the
+//! following item. The value being iterated over is the first one given to both.
+//!
+//! `func List::next(l: List) -> Option<int> { ... }`
+//! `func List::advance(l: List) -> List { ... }`
+//! `for value in list { ... }`
+
+use std::convert::TryFrom;
+
+use super::ObjectInstance;
+use crate::instruction::{FunctionCall, TypeId};
+use crate::{
+    FromObjectInstance, InstrKind, Interpreter, JkChar, JkErrKind, JkError, JkInt, JkString,
+    ToObjectInstance,
+};
+
+/// Iterator over the values contained in an instance
+pub enum InstanceIter {
+    /// Integers going from `current` (included) to `end` (excluded)
+    Range { current: i64, end: i64 },
+
+    /// Characters of a string, in order
+    Chars(std::vec::IntoIter<char>),
+
+    /// Elements of an array or keys of a map, in order
+    Elements(std::vec::IntoIter<ObjectInstance>),
+
+    /// Items returned by the `next` function of a custom type, starting from
+    /// `current`. Once an item is yielded, the `advance` function gives the iterator
+    /// positioned on the following one
+    Custom {
+        next: String,
+        advance: String,
+        current: Box<ObjectInstance>,
+    },
+}
+
+impl InstanceIter {
+    /// Create a new iterator over an instance. Returns `None` if the type of the
+    /// instance cannot be iterated over. The interpreter is used to find the type of
+    /// the elements of arrays and the keys of maps, as well as the functions iterating
+    /// over custom types
+    pub fn new(instance: &ObjectInstance, interpreter: &Interpreter) -> Option<InstanceIter> {
+        let ty = instance.ty()?;
+        let type_id = ty.type_id();
+
+        if let Some(element) = type_id.element() {
+            let element_ty = interpreter.get_type(element).map(|ty| (*ty).clone());
+
+            return Some(InstanceIter::Elements(
+                instance.elements(element_ty).into_iter(),
+            ));
+        }
+
+        if let Some((key, _)) = type_id.entry() {
+            let key_ty = interpreter.get_type(key).map(|ty| (*ty).clone());
+
+            return Some(InstanceIter::Elements(instance.keys(key_ty).into_iter()));
+        }
+
+        match ty.name() {
+            "range" => {
+                let current = InstanceIter::int_field(instance, "start")?;
+                let end = InstanceIter::int_field(instance, "end")?;
+
+                Some(InstanceIter::Range { current, end })
+            }
+            "string" => {
+                let chars: Vec<char> = JkString::from_instance(instance).0.chars().collect();
+
+                Some(InstanceIter::Chars(chars.into_iter()))
+            }
+            _ => {
+                let next = InstanceIter::next_function(&type_id);
+                let advance = InstanceIter::advance_function(&type_id);
+                interpreter.get_function(&next)?;
+                interpreter.get_function(&advance)?;
+
+                Some(InstanceIter::Custom {
+                    next,
+                    advance,
+                    current: Box::new(instance.clone()),
+                })
+            }
+        }
+    }
+
+    /// Name of the function returning the current item of a custom type, such as
+    /// `Countdown::next`. Generic types share the same function, whatever their
+    /// generic arguments
+    pub fn next_function(ty: &TypeId) -> String {
+        format!("{}::next", ty.base())
+    }
+
+    /// Name of the function moving a custom type to its following item, such as
+    /// `Countdown::advance`
+    pub fn advance_function(ty: &TypeId) -> String {
+        format!("{}::advance", ty.base())
+    }
+
+    /// Get the next value of the iterator, or `None` if it is exhausted. Getting
+    /// the next value of a custom type calls its `next` and `advance` functions,
+    /// which may fail
+    pub fn next(
+        &mut self,
+        interpreter: &mut Interpreter,
+    ) -> Result<Option<ObjectInstance>, JkError> {
+        match self {
+            InstanceIter::Range { current, end } => match *current < *end {
+                true => {
+                    *current += 1;
+                    Ok(Some(JkInt::from(*current - 1).to_instance()))
+                }
+                false => Ok(None),
+            },
+            InstanceIter::Chars(chars) => Ok(chars.next().map(|c| JkChar::from(c).to_instance())),
+            InstanceIter::Elements(elements) => Ok(elements.next()),
+            InstanceIter::Custom {
+                next,
+                advance,
+                current,
+            } => {
+                let option = InstanceIter::call(next, current, interpreter)?;
+
+                let some = match option.ty().and_then(|ty| ty.variant_tag("Some")) {
+                    Some(some) => some,
+                    None => return Err(InstanceIter::invalid_next(next)),
+                };
+
+                if option.tag() != Some(some) {
+                    return Ok(None);
+                }
+
+                // The items are of the type given to `Option` by the `next` function
+                let item_ty = option
+                    .ty()
+                    .and_then(|ty| ty.type_id().generics().first().cloned())
+                    .and_then(|item| interpreter.get_type(&item))
+                    .map(|ty| (*ty).clone());
+
+                let item = match option.field_instance("value", item_ty) {
+                    Some(item) => item,
+                    None => return Err(InstanceIter::invalid_next(next)),
+                };
+
+                **current = InstanceIter::call(advance, current, interpreter)?;
+
+                Ok(Some(item))
+            }
+        }
+    }
+
+    /// Call one of the functions iterating over a custom type with its current value
+    fn call(
+        function: &str,
+        current: &ObjectInstance,
+        interpreter: &mut Interpreter,
+    ) -> Result<ObjectInstance, JkError> {
+        let call = FunctionCall::new(function.to_owned());
+
+        match call.call_with(vec![current.clone()], interpreter)? {
+            InstrKind::Expression(Some(value)) => Ok(value),
+            _ => Err(JkError::new(
+                JkErrKind::Interpreter,
+                format!("`{}` did not return a value", function),
+                None,
+                function.to_owned(),
+            )),
+        }
+    }
+
+    /// Error emitted when a `next` function does not return an optional value
+    fn invalid_next(function: &str) -> JkError {
+        JkError::new(
+            JkErrKind::Interpreter,
+            format!("`{}` did not return an `Option` value", function),
+            None,
+            function.to_owned(),
+        )
+    }
+
+    /// Get the type yielded when iterating over a type, if that type can be iterated
+    /// over
+    pub fn item_type(ty: &TypeId) -> Option<TypeId> {
+        match ty.id() {
+            "range" => Some(TypeId::from("int")),
+            "string" => Some(TypeId::from("char")),
+            _ => ty
+                .element()
+                .or_else(|| ty.entry().map(|(key, _)| key))
+                .cloned(),
+        }
+    }
+
+    fn int_field(instance: &ObjectInstance, name: &str) -> Option<i64> {
+        let bytes = instance.field(name)?;
+
+        Some(i64::from_ne_bytes(TryFrom::try_from(bytes).ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::TypeDec;
+    use crate::utils::execute_in;
+
+    fn range(start: i64, end: i64) -> ObjectInstance {
+        let mut data = JkInt::from(start).to_instance().data().to_vec();
+        data.extend_from_slice(JkInt::from(end).to_instance().data());
+
+        ObjectInstance::new(
+            Some(TypeDec::from("range")),
+            16,
+            data,
+            Some(vec![("start".to_owned(), 8), ("end".to_owned(), 8)]),
+        )
+    }
+
+    /// Collect all the values yielded when iterating over an instance
+    fn values(instance: &ObjectInstance, interpreter: &mut Interpreter) -> Vec<ObjectInstance> {
+        let mut iter = InstanceIter::new(instance, interpreter).unwrap();
+        let mut values = vec![];
+
+        while let Some(value) = iter.next(interpreter).unwrap() {
+            values.push(value);
+        }
+
+        values
+    }
+
+    #[test]
+    fn t_iter_range() {
+        let values: Vec<i64> = values(&range(2, 5), &mut Interpreter::new())
+            .iter()
+            .map(|i| JkInt::from_instance(i).0)
+            .collect();
+
+        assert_eq!(values, vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn t_iter_empty_range() {
+        assert!(values(&range(5, 2), &mut Interpreter::new()).is_empty());
+    }
+
+    #[test]
+    fn t_iter_string() {
+        let values: String = values(
+            &JkString::from("jïnko").to_instance(),
+            &mut Interpreter::new(),
+        )
+        .iter()
+        .map(|i| JkChar::from_instance(i).0)
+        .collect();
+
+        assert_eq!(values, "jïnko");
+    }
+
+    #[test]
+    fn t_iter_array() {
+        let mut interpreter = Interpreter::new();
+        let array = ObjectInstance::from_elements(
+            interpreter
+                .get_type(&TypeId::array(TypeId::from("int")))
+                .map(|ty| (*ty).clone()),
+            vec![JkInt::from(1).to_instance(), JkInt::from(2).to_instance()],
+        );
+
+        assert_eq!(
+            values(&array, &mut interpreter),
+            vec![JkInt::from(1).to_instance(), JkInt::from(2).to_instance()]
+        );
+    }
+
+    #[test]
+    fn t_iter_custom_type() {
+        let mut interpreter = Interpreter::new();
+        let input = "type Countdown(n: int);
+            func Countdown::next(c: Countdown) -> Option<int> {
+                if c.n == 0 { None<int>() } else { Some(c.n) }
+            }
+            func Countdown::advance(c: Countdown) -> Countdown { Countdown { c.n - 1 } }
+            Countdown { 3 }";
+
+        let countdown = match execute_in(input, &mut interpreter).unwrap() {
+            InstrKind::Expression(countdown) => countdown,
+            _ => unreachable!(),
+        };
+
+        let values: Vec<i64> = values(&countdown.unwrap(), &mut interpreter)
+            .iter()
+            .map(|n| JkInt::from_instance(n).0)
+            .collect();
+
+        assert_eq!(values, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn t_iter_invalid_type() {
+        assert!(InstanceIter::new(&JkInt::from(12).to_instance(), &Interpreter::new()).is_none());
+    }
+}
@@ -8,8 +8,14 @@
 //! different passes of the typechecker.
 
 use std::collections::HashMap;
+use std::rc::Rc;
 
-use crate::instruction::TypeDec;
+use crate::instruction::{TypeDec, Variant};
+use crate::JkInt;
+
+mod display;
+mod iterator;
+pub use iterator::InstanceIter;
 
 pub type Ty = TypeDec;
 pub type Name = String;
@@ -17,15 +23,95 @@ type Offset = usize;
 pub type Size = usize;
 type FieldsMap = HashMap<Name, (Offset, Size)>;
 
+/// Name of the hidden field storing the variant of a sum type's instance
+pub const TAG_FIELD: &str = "@tag";
+
 /// The type is optional. At first, the type might not be known, and will only be
 /// revealed during the typechecking phase. `size` is the size of the instance in bytes.
 /// It's the same as `data.len()`. `data` is the raw byte value of the instance.
+/// The elements of an array are not stored as raw bytes, but as instances of their
+/// own. They are shared between the copies of an array, and only get copied when one
+/// of the copies is modified. The same goes for the entries of a map.
 #[derive(Debug, PartialEq, Clone)]
 pub struct ObjectInstance {
-    ty: Option<Ty>,
+    ty: Option<Rc<Ty>>,
     size: Size,
     data: Vec<u8>,
     fields: Option<FieldsMap>,
+    elements: Option<Rc<Vec<ObjectInstance>>>,
+    entries: Option<Rc<Entries>>,
+
+    /// Arrays and maps stored in the instance's fields, by path. They are not part of
+    /// the raw data of the instance
+    collections: HashMap<Name, ObjectInstance>,
+}
+
+/// Entries of a map, in insertion order. The position of each entry is looked up using
+/// the raw bytes of its key, since keys are always of a primitive type. Removed entries
+/// leave a hole behind them, so that other entries do not move, until holes make up
+/// half of the entries
+#[derive(Debug, PartialEq, Clone, Default)]
+struct Entries {
+    entries: Vec<Option<(ObjectInstance, ObjectInstance)>>,
+    positions: HashMap<Vec<u8>, usize>,
+}
+
+impl Entries {
+    /// Iterate over the entries, in insertion order
+    fn iter(&self) -> impl Iterator<Item = &(ObjectInstance, ObjectInstance)> {
+        self.entries.iter().flatten()
+    }
+
+    /// Get the amount of entries
+    fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Get the value associated with a key
+    fn get(&self, key: &ObjectInstance) -> Option<&ObjectInstance> {
+        let idx = self.positions.get(key.data())?;
+
+        self.entries[*idx].as_ref().map(|(_, value)| value)
+    }
+
+    /// Associate a value with a key. If the key is already present, its value is
+    /// replaced and the entry keeps its position
+    fn insert(&mut self, key: ObjectInstance, value: ObjectInstance) {
+        match self.positions.get(key.data()) {
+            Some(idx) => self.entries[*idx] = Some((key, value)),
+            None => {
+                self.positions
+                    .insert(key.data().to_vec(), self.entries.len());
+                self.entries.push(Some((key, value)));
+            }
+        }
+    }
+
+    /// Remove the entry associated with a key, and return its value
+    fn remove(&mut self, key: &ObjectInstance) -> Option<ObjectInstance> {
+        let idx = self.positions.remove(key.data())?;
+        let (_, value) = self.entries[idx].take()?;
+
+        if self.entries.len() > 2 * self.len() {
+            self.compact();
+        }
+
+        Some(value)
+    }
+
+    /// Get rid of the holes left by removed entries
+    fn compact(&mut self) {
+        self.entries.retain(Option::is_some);
+
+        let positions = &mut self.positions;
+        self.entries
+            .iter()
+            .flatten()
+            .enumerate()
+            .for_each(|(idx, (key, _))| {
+                positions.insert(key.data().to_vec(), idx);
+            });
+    }
 }
 
 impl ObjectInstance {
@@ -44,10 +130,13 @@ impl ObjectInstance {
         let fields = fields.map(|vec| ObjectInstance::fields_vec_to_hash_map(vec));
 
         ObjectInstance {
-            ty,
+            ty: ty.map(Rc::new),
             size,
             data,
             fields,
+            elements: None,
+            entries: None,
+            collections: HashMap::new(),
         }
     }
 
@@ -63,12 +152,12 @@ impl ObjectInstance {
 
     /// Get a reference to the type of the instance
     pub fn ty(&self) -> Option<&Ty> {
-        self.ty.as_ref()
+        self.ty.as_deref()
     }
 
     /// Set the type of the instance
     pub fn set_ty(&mut self, ty: Option<Ty>) {
-        self.ty = ty;
+        self.ty = ty.map(Rc::new);
     }
 
     /// Get a reference to the raw data bytes of the ObjectInstance
@@ -84,6 +173,348 @@ impl ObjectInstance {
         &self.fields
     }
 
+    /// Get the raw data bytes of one of the instance's fields, if it exists
+    pub fn field(&self, name: &str) -> Option<&[u8]> {
+        self.fields
+            .as_ref()?
+            .get(name)
+            .map(|(offset, size)| &self.data[*offset..*offset + *size])
+    }
+
+    /// Get one of the instance's fields as a new instance of the given type. The
+    /// layout of the field's own fields is kept, so that they can be accessed as well
+    pub fn field_instance(&self, name: &str, ty: Option<Ty>) -> Option<ObjectInstance> {
+        if let Some(collection) = self.collections.get(name) {
+            let mut collection = collection.clone();
+            collection.set_ty(ty);
+
+            return Some(collection);
+        }
+
+        let fields = self.fields.as_ref()?;
+        let (offset, size) = *fields.get(name)?;
+
+        let prefix = format!("{}.", name);
+        let nested: FieldsMap = fields
+            .iter()
+            .filter_map(|(field, (field_offset, field_size))| {
+                field
+                    .strip_prefix(&prefix)
+                    .map(|nested| (nested.to_string(), (field_offset - offset, *field_size)))
+            })
+            .collect();
+        let collections = self
+            .collections
+            .iter()
+            .filter_map(|(field, collection)| {
+                field
+                    .strip_prefix(&prefix)
+                    .map(|nested| (nested.to_string(), collection.clone()))
+            })
+            .collect();
+
+        Some(ObjectInstance {
+            ty: ty.map(Rc::new),
+            size,
+            data: self.data[offset..offset + size].to_vec(),
+            fields: match nested.is_empty() {
+                true => None,
+                false => Some(nested),
+            },
+            elements: None,
+            entries: None,
+            collections,
+        })
+    }
+
+    /// Keep track of the layout of an instance stored in one of the instance's fields.
+    /// Its fields are recorded as `<field>.<nested_field>`, at their offset inside
+    /// the instance. Arrays and maps are kept as they are, along with the ones stored
+    /// in the fields of the instance
+    pub fn nest_fields(&mut self, name: &str, field: &ObjectInstance) {
+        if field.elements.is_some() || field.entries.is_some() {
+            self.collections.insert(name.to_owned(), field.clone());
+        }
+
+        field
+            .collections
+            .iter()
+            .for_each(|(nested_name, collection)| {
+                self.collections
+                    .insert(format!("{}.{}", name, nested_name), collection.clone());
+            });
+
+        let (fields, nested) = match (self.fields.as_mut(), field.fields()) {
+            (Some(fields), Some(nested)) => (fields, nested),
+            _ => return,
+        };
+
+        let offset = match fields.get(name) {
+            Some((offset, _)) => *offset,
+            None => return,
+        };
+
+        nested
+            .iter()
+            .for_each(|(nested_name, (nested_offset, size))| {
+                fields.insert(
+                    format!("{}.{}", name, nested_name),
+                    (offset + nested_offset, *size),
+                );
+            });
+    }
+
+    /// Replace the value of one of the instance's fields. Nested fields are designated
+    /// by their full path: `<field>.<nested_field>`. Since the new value might not be
+    /// the same size as the old one, the following fields are moved accordingly.
+    /// Returns `None` if the field does not exist
+    pub fn set_field(&mut self, name: &str, value: &ObjectInstance) -> Option<()> {
+        let fields = self.fields.as_mut()?;
+        let (offset, old_size) = *fields.get(name)?;
+        let end = offset + old_size;
+        let new_size = value.size();
+
+        self.data.splice(offset..end, value.data().iter().cloned());
+        self.size = self.size + new_size - old_size;
+
+        // The layout of the old value is not valid anymore
+        let prefix = format!("{}.", name);
+        fields.retain(|field, _| !field.starts_with(&prefix));
+        self.collections
+            .retain(|field, _| field != name && !field.starts_with(&prefix));
+
+        fields
+            .iter_mut()
+            .for_each(|(field, (field_offset, field_size))| {
+                if field.as_str() == name {
+                    *field_size = new_size;
+                } else if *field_offset >= end {
+                    // Fields placed after the modified one are moved
+                    *field_offset = *field_offset + new_size - old_size;
+                } else if *field_offset <= offset && *field_offset + *field_size >= end {
+                    // Fields containing the modified one are resized
+                    *field_size = *field_size + new_size - old_size;
+                }
+            });
+
+        self.nest_fields(name, value);
+
+        Some(())
+    }
+
+    /// Create a new instance out of the instances of its fields, in order. The layout
+    /// of each field is kept, so that their own fields can be accessed as well
+    pub fn from_fields(ty: Option<Ty>, fields: &[(Name, ObjectInstance)]) -> ObjectInstance {
+        let data: Vec<u8> = fields
+            .iter()
+            .flat_map(|(_, field)| field.data().iter().cloned())
+            .collect();
+        let sizes = fields
+            .iter()
+            .map(|(name, field)| (name.clone(), field.size()))
+            .collect();
+
+        let mut instance = ObjectInstance::new(ty, data.len(), data, Some(sizes));
+
+        fields
+            .iter()
+            .for_each(|(name, field)| instance.nest_fields(name, field));
+
+        instance
+    }
+
+    /// Create a new array instance containing the given elements, in order. Elements
+    /// keep their own type and layout, so that elements of custom types can be
+    /// accessed like any other instance
+    pub fn from_elements(ty: Option<Ty>, elements: Vec<ObjectInstance>) -> ObjectInstance {
+        let mut array = ObjectInstance::new(ty, 0, vec![], None);
+        array.elements = Some(Rc::new(elements));
+
+        array
+    }
+
+    /// Create a new map instance out of its entries. A key given multiple times keeps
+    /// the position of its first entry and the value of its last one
+    pub fn from_entries(
+        ty: Option<Ty>,
+        entries: Vec<(ObjectInstance, ObjectInstance)>,
+    ) -> ObjectInstance {
+        let mut map = ObjectInstance::new(ty, 0, vec![], None);
+        map.entries = Some(Rc::new(Entries::default()));
+
+        entries
+            .into_iter()
+            .for_each(|(key, value)| map.insert(key, value));
+
+        map
+    }
+
+    /// Create a new instance of one of the variants of a sum type. The index of the
+    /// variant is stored as a hidden first field, followed by the variant's own fields
+    pub fn from_variant(
+        ty: Option<Ty>,
+        tag: usize,
+        fields: &[(Name, ObjectInstance)],
+    ) -> ObjectInstance {
+        let mut tagged = vec![(TAG_FIELD.to_owned(), JkInt::from(tag as i64).to_instance())];
+        tagged.extend_from_slice(fields);
+
+        ObjectInstance::from_fields(ty, &tagged)
+    }
+
+    /// Get the index of the variant stored in an instance of a sum type
+    pub fn tag(&self) -> Option<usize> {
+        self.field_instance(TAG_FIELD, None)
+            .map(|tag| JkInt::from_instance(&tag).0 as usize)
+    }
+
+    /// Get the variant stored in an instance of a sum type
+    pub fn variant(&self) -> Option<&Variant> {
+        self.ty()?.variants().get(self.tag()?)
+    }
+
+    /// Get the keys of a map instance, in insertion order, as new instances of the
+    /// given type
+    pub fn keys(&self, ty: Option<Ty>) -> Vec<ObjectInstance> {
+        let ty = ty.map(Rc::new);
+
+        self.entries.as_ref().map_or_else(Vec::new, |entries| {
+            entries
+                .iter()
+                .map(|(key, _)| ObjectInstance::typed(key, &ty))
+                .collect()
+        })
+    }
+
+    /// Get the values of a map instance, in insertion order, as new instances of the
+    /// given type
+    pub fn values(&self, ty: Option<Ty>) -> Vec<ObjectInstance> {
+        let ty = ty.map(Rc::new);
+
+        self.entries.as_ref().map_or_else(Vec::new, |entries| {
+            entries
+                .iter()
+                .map(|(_, value)| ObjectInstance::typed(value, &ty))
+                .collect()
+        })
+    }
+
+    /// Get the value associated with a key in a map instance, as a new instance of the
+    /// given type
+    pub fn get(&self, key: &ObjectInstance, ty: Option<Ty>) -> Option<ObjectInstance> {
+        let value = self.entries.as_ref()?.get(key)?;
+
+        Some(ObjectInstance::typed(value, &ty.map(Rc::new)))
+    }
+
+    /// Check if a key is present in a map instance
+    pub fn contains(&self, key: &ObjectInstance) -> bool {
+        self.entries
+            .as_ref()
+            .and_then(|entries| entries.get(key))
+            .is_some()
+    }
+
+    /// Associate a value with a key in a map instance. If the key is already present,
+    /// its value is replaced. The entries are only copied if they are shared with
+    /// another copy of the map
+    pub fn insert(&mut self, key: ObjectInstance, value: ObjectInstance) {
+        Rc::make_mut(self.entries.get_or_insert_with(Default::default)).insert(key, value)
+    }
+
+    /// Remove the entry associated with a key from a map instance, and return its
+    /// value, or `None` if the key was not present
+    pub fn remove(&mut self, key: &ObjectInstance) -> Option<ObjectInstance> {
+        let entries = self.entries.as_mut()?;
+        entries.get(key)?;
+
+        Rc::make_mut(entries).remove(key)
+    }
+
+    /// Get the amount of elements contained in an array instance
+    pub fn len(&self) -> usize {
+        self.elements.as_ref().map_or(0, |elements| elements.len())
+    }
+
+    /// Does the array instance contain no elements at all
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get one of the elements of an array instance as a new instance of the given
+    /// type. Elements keep their own type if none is given
+    pub fn element(&self, idx: usize, ty: Option<Ty>) -> Option<ObjectInstance> {
+        self.typed_element(idx, &ty.map(Rc::new))
+    }
+
+    /// Get all the elements of an array instance, in order, as new instances of the
+    /// given type. Elements keep their own type if none is given
+    pub fn elements(&self, ty: Option<Ty>) -> Vec<ObjectInstance> {
+        let ty = ty.map(Rc::new);
+
+        (0..self.len())
+            .filter_map(|idx| self.typed_element(idx, &ty))
+            .collect()
+    }
+
+    /// Get one of the elements of an array instance, sharing the given type with
+    /// the other elements
+    fn typed_element(&self, idx: usize, ty: &Option<Rc<Ty>>) -> Option<ObjectInstance> {
+        let element = self.elements.as_ref()?.get(idx)?;
+
+        Some(ObjectInstance::typed(element, ty))
+    }
+
+    /// Copy an instance stored in an array or a map, giving it a type shared with
+    /// the other instances. Instances keep their own type if none is given
+    fn typed(instance: &ObjectInstance, ty: &Option<Rc<Ty>>) -> ObjectInstance {
+        let mut instance = instance.clone();
+        if ty.is_some() {
+            instance.ty = ty.clone();
+        }
+
+        instance
+    }
+
+    /// Add an element at the end of an array instance. The elements are only copied
+    /// if they are shared with another copy of the array
+    pub fn push(&mut self, element: ObjectInstance) {
+        Rc::make_mut(self.elements.get_or_insert_with(Default::default)).push(element)
+    }
+
+    /// Remove the last element of an array instance and return it, or `None` if the
+    /// array is empty
+    pub fn pop(&mut self) -> Option<ObjectInstance> {
+        Rc::make_mut(self.elements.as_mut()?).pop()
+    }
+
+    /// Check if two instances hold the same value. Their raw bytes are compared, as
+    /// well as the elements of the arrays and the entries of the maps they contain.
+    /// Types are not compared, since values of different types are never compared
+    /// with each other
+    pub fn same_value(&self, other: &ObjectInstance) -> bool {
+        let same_elements = match (&self.elements, &other.elements) {
+            (Some(lhs), Some(rhs)) => {
+                lhs.len() == rhs.len() && lhs.iter().zip(rhs.iter()).all(|(l, r)| l.same_value(r))
+            }
+            (lhs, rhs) => lhs.is_none() && rhs.is_none(),
+        };
+        // Maps holding the same entries are equal, whatever the order of insertion
+        let same_entries = match (&self.entries, &other.entries) {
+            (Some(lhs), Some(rhs)) => lhs.len() == rhs.len()
+                && lhs.iter().all(
+                    |(key, value)| matches!(rhs.get(key), Some(other) if value.same_value(other)),
+                ),
+            (lhs, rhs) => lhs.is_none() && rhs.is_none(),
+        };
+        let same_collections = self.collections.len() == other.collections.len()
+            && self.collections.iter().all(|(name, collection)| {
+                matches!(other.collections.get(name), Some(other) if collection.same_value(other))
+            });
+
+        self.data == other.data && same_elements && same_entries && same_collections
+    }
+
     fn fields_vec_to_hash_map(vec: Vec<(String, Size)>) -> FieldsMap {
         let mut current_offset: usize = 0;
         let mut hashmap = FieldsMap::new();
@@ -0,0 +1,183 @@
+//! ArrayIndexes are used to get one of the elements of an array: `a[0]`. The index
+//! must be an integer, and needs to be lower than the length of the array.
+
+use crate::{
+    CheckedType, FromObjectInstance, InstrKind, Instruction, Interpreter, JkErrKind,
+    JkErrSpaceLocation, JkError, JkInt, Rename, TypeCheck, TypeCtx,
+};
+
+#[derive(Clone)]
+pub struct ArrayIndex {
+    array: Box<dyn Instruction>,
+    index: Box<dyn Instruction>,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl ArrayIndex {
+    /// Create a new access to one of the elements of an array
+    pub fn new(array: Box<dyn Instruction>, index: Box<dyn Instruction>) -> ArrayIndex {
+        ArrayIndex {
+            array,
+            index,
+            location: None,
+        }
+    }
+
+    fn error(&self, msg: String) -> JkError {
+        JkError::new(
+            JkErrKind::Interpreter,
+            msg,
+            self.location.clone(),
+            self.print(),
+        )
+    }
+}
+
+impl Instruction for ArrayIndex {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Expression(None)
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!("{}[{}]", self.array.print(), self.index.print())
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("INDEX", &self.print());
+
+        let array = self.array.execute_expression(interpreter)?;
+        let index = self.index.execute_expression(interpreter)?;
+
+        let array_ty = array.ty().map(|ty| ty.type_id());
+        let element_ty = match array_ty.as_ref().and_then(|ty| ty.element()) {
+            Some(element_ty) => element_ty,
+            None => {
+                return Err(self.error(format!(
+                    "cannot index into a value of type `{}`",
+                    array_ty.as_ref().map_or("{unknown}", |ty| ty.id())
+                )))
+            }
+        };
+
+        match index.ty().map(|ty| ty.name()) {
+            Some("int") => {}
+            ty => {
+                return Err(self.error(format!(
+                    "array indexes must be of type `int`, got `{}`",
+                    ty.unwrap_or("{unknown}")
+                )))
+            }
+        }
+
+        let idx = JkInt::from_instance(&index).0;
+        let len = array.len();
+
+        if idx < 0 || idx as usize >= len {
+            return Err(self.error(format!(
+                "index out of bounds: the length is {} but the index is {}",
+                len, idx
+            )));
+        }
+
+        let element_ty = interpreter.get_type(element_ty).map(|ty| (*ty).clone());
+
+        // We can unwrap since we checked that the index is in bounds
+        Ok(InstrKind::Expression(Some(
+            array.element(idx as usize, element_ty).unwrap(),
+        )))
+    }
+}
+
+impl Rename for ArrayIndex {
+    fn prefix(&mut self, prefix: &str) {
+        self.array.prefix(prefix);
+        self.index.prefix(prefix);
+    }
+}
+
+impl TypeCheck for ArrayIndex {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let array_ty = self.array.resolve_type(ctx);
+        let index_ty = self.index.resolve_type(ctx);
+
+        if !index_ty.compatible(&CheckedType::resolved("int")) {
+            ctx.error(
+                format!("array indexes must be of type `int`, got `{}`", index_ty),
+                self.index.location(),
+                self.index.print(),
+            );
+        }
+
+        match array_ty {
+            CheckedType::Resolved(ty) => match ty.element() {
+                Some(element_ty) => CheckedType::Resolved(element_ty.clone()),
+                None => {
+                    ctx.error(
+                        format!("cannot index into a value of type `{}`", ty.id()),
+                        self.location(),
+                        self.print(),
+                    );
+                    CheckedType::Unknown
+                }
+            },
+            CheckedType::Void => {
+                ctx.error(
+                    String::from("cannot index into a statement"),
+                    self.location(),
+                    self.print(),
+                );
+                CheckedType::Unknown
+            }
+            CheckedType::Unknown | CheckedType::Never => CheckedType::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JkString, ToObjectInstance};
+
+    /// Execute the input after declaring the array used by the tests
+    fn execute(input: &str) -> Result<InstrKind, JkError> {
+        crate::utils::execute(&format!("a = [\"jin\", \"ko\"]; {}", input))
+    }
+
+    #[test]
+    fn t_print() {
+        let index = ArrayIndex::new(Box::new(JkInt::from(12)), Box::new(JkInt::from(0)));
+
+        assert_eq!(index.print(), "12[0]");
+    }
+
+    #[test]
+    fn t_index() {
+        assert_eq!(
+            execute("a[1]").unwrap(),
+            InstrKind::Expression(Some(JkString::from("ko").to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_index_out_of_bounds() {
+        assert_eq!(
+            execute("a[2]").unwrap_err().msg(),
+            "index out of bounds: the length is 2 but the index is 2"
+        );
+        assert!(execute("a[0 - 1]").is_err());
+    }
+
+    #[test]
+    fn t_index_invalid() {
+        assert!(execute("a['c']").is_err());
+        assert!(execute("12[0]").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! ArrayLiterals are used to create a new array out of a list of elements: `[1, 2, 3]`.
+//! All the elements of an array must be of the same type. Since the type of an empty
+//! literal cannot be known, empty arrays are created using `array::new<T>()`.
+
+use crate::instruction::TypeId;
+use crate::{
+    CheckedType, InstrKind, Instruction, Interpreter, JkErrKind, JkErrSpaceLocation, JkError,
+    ObjectInstance, Rename, TypeCheck, TypeCtx,
+};
+
+#[derive(Clone)]
+pub struct ArrayLiteral {
+    elements: Vec<Box<dyn Instruction>>,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl ArrayLiteral {
+    /// Create a new array literal out of its elements
+    pub fn new(elements: Vec<Box<dyn Instruction>>) -> ArrayLiteral {
+        ArrayLiteral {
+            elements,
+            location: None,
+        }
+    }
+
+    /// Return a reference to the elements of the literal
+    #[cfg(test)]
+    pub fn elements(&self) -> &Vec<Box<dyn Instruction>> {
+        &self.elements
+    }
+
+    /// Error emitted when the literal does not contain any element
+    fn empty_error(&self, kind: JkErrKind) -> JkError {
+        JkError::new(
+            kind,
+            String::from("cannot infer the type of an empty array literal"),
+            self.location.clone(),
+            self.print(),
+        )
+        .with_help(String::from(
+            "create empty arrays using `array::new<T>()` instead",
+        ))
+    }
+
+    /// Error emitted when one of the elements is not of the same type as the first one
+    fn mismatch_error(&self, idx: usize, expected: &str, got: &str) -> String {
+        format!(
+            "elements of an array must all be of the same type: expected `{}`, \
+            got `{}` for element {}",
+            expected, got, idx
+        )
+    }
+}
+
+impl Instruction for ArrayLiteral {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Expression(None)
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!(
+            "[{}]",
+            self.elements
+                .iter()
+                .map(|element| element.print())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("ARRAY", &self.print());
+
+        let elements = self
+            .elements
+            .iter()
+            .map(|element| element.execute_expression(interpreter))
+            .collect::<Result<Vec<ObjectInstance>, JkError>>()?;
+
+        let element_ty = match elements.first() {
+            Some(first) => first.ty().map(|ty| ty.type_id()),
+            None => return Err(self.empty_error(JkErrKind::Interpreter)),
+        };
+
+        let element_name = element_ty.as_ref().map_or("", |ty| ty.id());
+        if let Some((idx, element)) = elements.iter().enumerate().find(|(_, element)| {
+            element.ty().map(|ty| ty.name()).unwrap_or_default() != element_name
+        }) {
+            return Err(JkError::new(
+                JkErrKind::Interpreter,
+                self.mismatch_error(
+                    idx,
+                    element_name,
+                    element.ty().map(|ty| ty.name()).unwrap_or_default(),
+                ),
+                self.location.clone(),
+                self.print(),
+            ));
+        }
+
+        let ty = element_ty
+            .and_then(|element_ty| interpreter.get_type(&TypeId::array(element_ty)))
+            .map(|ty| (*ty).clone());
+
+        Ok(InstrKind::Expression(Some(ObjectInstance::from_elements(
+            ty, elements,
+        ))))
+    }
+}
+
+impl Rename for ArrayLiteral {
+    fn prefix(&mut self, prefix: &str) {
+        self.elements
+            .iter_mut()
+            .for_each(|element| element.prefix(prefix));
+    }
+}
+
+impl TypeCheck for ArrayLiteral {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let elements_ty: Vec<CheckedType> = self
+            .elements
+            .iter()
+            .map(|element| element.resolve_type(ctx))
+            .collect();
+
+        let element_ty = match elements_ty.first() {
+            Some(element_ty) => element_ty.clone(),
+            None => {
+                ctx.report(self.empty_error(JkErrKind::TypeChecker));
+                return CheckedType::Unknown;
+            }
+        };
+
+        for (idx, (element, ty)) in self.elements.iter().zip(elements_ty.iter()).enumerate() {
+            if !ty.compatible(&element_ty) {
+                ctx.error(
+                    self.mismatch_error(idx, &element_ty.to_string(), &ty.to_string()),
+                    element.location(),
+                    element.print(),
+                );
+            }
+        }
+
+        match element_ty {
+            CheckedType::Resolved(ty) => CheckedType::Resolved(TypeId::array(ty)),
+            CheckedType::Void => {
+                ctx.error(
+                    String::from("arrays cannot contain statements"),
+                    self.location(),
+                    self.print(),
+                );
+                CheckedType::Unknown
+            }
+            CheckedType::Unknown | CheckedType::Never => CheckedType::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::execute;
+    use crate::{JkInt, ToObjectInstance};
+
+    #[test]
+    fn t_print() {
+        let array = ArrayLiteral::new(vec![Box::new(JkInt::from(1)), Box::new(JkInt::from(2))]);
+
+        assert_eq!(array.print(), "[1, 2]");
+    }
+
+    #[test]
+    fn t_array_literal() {
+        let array = match execute("[1, 2, 3]").unwrap() {
+            InstrKind::Expression(Some(array)) => array,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(array.ty().unwrap().name(), "[int]");
+        assert_eq!(array.len(), 3);
+        assert_eq!(
+            array.element(2, None).unwrap().data(),
+            JkInt::from(3).to_instance().data()
+        );
+    }
+
+    #[test]
+    fn t_array_literal_mixed_types() {
+        assert_eq!(
+            execute("[1, 2.5]").unwrap_err().msg(),
+            "elements of an array must all be of the same type: expected `int`, got `float` for element 1"
+        );
+    }
+
+    #[test]
+    fn t_array_literal_empty() {
+        assert!(execute("[]").is_err());
+    }
+}
@@ -1,17 +1,24 @@
 //! Audit blocks are more permissive than normal blocks. They allow ignoring a return
 //! value, for example.
 
-use crate::{instruction::Block, InstrKind, Instruction, Interpreter, JkError, Rename};
+use crate::{
+    instruction::Block, CheckedType, InstrKind, Instruction, Interpreter, JkErrSpaceLocation,
+    JkError, Rename, TypeCheck, TypeCtx,
+};
 
 #[derive(Clone)]
 pub struct Audit {
     block: Block,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl Audit {
     /// Create a new assign block
     pub fn new(block: Block) -> Audit {
-        Audit { block }
+        Audit {
+            block,
+            location: None,
+        }
     }
 }
 
@@ -20,6 +27,14 @@ impl Instruction for Audit {
         self.block.kind()
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         format!("audit {}", self.block.print())
     }
@@ -42,3 +57,9 @@ impl Rename for Audit {
         self.block.prefix(prefix)
     }
 }
+
+impl TypeCheck for Audit {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        self.block.resolve_type(ctx)
+    }
+}
@@ -2,14 +2,34 @@
 //! 1 + 2, a BinaryOp will be created containing "1" as a left hand side operand, "2" as
 //! a right hand side operand and "+" as the operator.
 //!
-//! The available operators are `+`, `-`, `*` and `/`.
-//! That is `Add`, `Substract`, `Multiply` and `Divide`.
+//! The available arithmetic operators are `+`, `-`, `*`, `/`, `%` and `**`.
+//! That is `Add`, `Substract`, `Multiply`, `Divide`, `Modulo` and `Power`. Integers
+//! can also be combined bitwise using `&`, `|`, `^`, `<<` and `>>`. When an integer
+//! and a floating point number are used together, the integer is promoted to a
+//! floating point number: `1 + 2.5` is `3.5`.
+//!
+//! The available comparison operators are `==`, `!=`, `<`, `<=`, `>` and `>=`, which
+//! produce a boolean. The logical operators `&&` and `||` combine two booleans, and
+//! only evaluate their right hand side operand if needed.
 
 use crate::{
-    instruction::Operator, FromObjectInstance, InstrKind, Instruction, Interpreter, JkErrKind,
-    JkError, JkFloat, JkInt, ObjectInstance, Rename, Value,
+    instruction::Operator, CheckedType, FromObjectInstance, InstrKind, Instruction, Interpreter,
+    JkBool, JkChar, JkErrKind, JkErrSpaceLocation, JkError, JkFloat, JkInt, JkString,
+    ObjectInstance, Rename, ToObjectInstance, TypeCheck, TypeCtx, Value,
 };
 
+/// Types which can be compared for equality
+const EQUALITY_TYPES: [&str; 5] = ["int", "float", "char", "string", "bool"];
+
+/// Types which can be ordered
+const ORDERED_TYPES: [&str; 4] = ["int", "float", "char", "string"];
+
+/// Types on which arithmetic operations are possible
+const ARITHMETIC_TYPES: [&str; 2] = ["int", "float"];
+
+/// Types on which bitwise operations are possible
+const BITWISE_TYPES: [&str; 1] = ["int"];
+
 /// The `BinaryOp` struct contains two expressions and an operator, which can be an arithmetic
 /// or a comparison one
 #[derive(Clone)]
@@ -19,6 +39,7 @@ pub struct BinaryOp {
     op: Operator,
 
     value: Option<Box<dyn Instruction>>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl BinaryOp {
@@ -29,6 +50,7 @@ impl BinaryOp {
             rhs,
             op,
             value: None,
+            location: None,
         }
     }
 
@@ -64,12 +86,85 @@ impl BinaryOp {
                     "Invalid use of statement in binary operation: {}",
                     self.lhs.print()
                 ),
-                None,
+                self.location.clone(),
                 self.print(),
             )),
             InstrKind::Expression(Some(v)) => Ok(v),
         }
     }
+
+    /// Execute a logical operation. The right hand side operand is only executed if
+    /// the left hand side one does not already decide the result
+    fn execute_logical(&self, interpreter: &mut Interpreter) -> Result<ObjectInstance, JkError> {
+        let value = match (self.op, self.lhs.as_bool(interpreter)?) {
+            (Operator::And, false) => false,
+            (Operator::Or, true) => true,
+            _ => self.rhs.as_bool(interpreter)?,
+        };
+
+        Ok(JkBool::from(value).to_instance())
+    }
+
+    /// Promote the operands of a mixed integer and floating point operation: The
+    /// integer is converted to a floating point number. Operands of any other
+    /// differing types cannot be used together
+    fn promote(
+        &self,
+        l_value: ObjectInstance,
+        r_value: ObjectInstance,
+    ) -> Result<(ObjectInstance, ObjectInstance), JkError> {
+        let to_float = |value: &ObjectInstance| {
+            JkFloat::from(JkInt::from_instance(value).0 as f64).to_instance()
+        };
+
+        let l_name = l_value.ty().map(|ty| ty.name()).unwrap_or("void");
+        let r_name = r_value.ty().map(|ty| ty.name()).unwrap_or("void");
+
+        match (l_name, r_name) {
+            (l_name, r_name) if l_name == r_name => Ok((l_value, r_value)),
+            ("int", "float") => Ok((to_float(&l_value), r_value)),
+            ("float", "int") => Ok((l_value, to_float(&r_value))),
+            (l_name, r_name) => Err(JkError::new(
+                JkErrKind::Interpreter, // FIXME: Should be a type error
+                format!(
+                    "trying to do binary operation on invalid types: `{}` {} `{}`",
+                    l_name,
+                    self.op.to_str(),
+                    r_name
+                ),
+                self.location.clone(),
+                self.print(),
+            )),
+        }
+    }
+
+    /// Promote the types of the operands of a mixed integer and floating point
+    /// operation, the same way their values are promoted during execution
+    fn promote_types(l_ty: CheckedType, r_ty: CheckedType) -> (CheckedType, CheckedType) {
+        let int = CheckedType::resolved("int");
+        let float = CheckedType::resolved("float");
+
+        if (l_ty == int && r_ty == float) || (l_ty == float && r_ty == int) {
+            (float.clone(), float)
+        } else {
+            (l_ty, r_ty)
+        }
+    }
+
+    /// Types on which the operator can be used
+    fn valid_types(&self) -> &'static [&'static str] {
+        match self.op {
+            Operator::And | Operator::Or => &["bool"],
+            Operator::Equals | Operator::NotEquals => &EQUALITY_TYPES,
+            op if op.is_comparison() => &ORDERED_TYPES,
+            Operator::BitAnd
+            | Operator::BitOr
+            | Operator::BitXor
+            | Operator::LeftShift
+            | Operator::RightShift => &BITWISE_TYPES,
+            _ => &ARITHMETIC_TYPES,
+        }
+    }
 }
 
 impl Instruction for BinaryOp {
@@ -77,6 +172,14 @@ impl Instruction for BinaryOp {
         InstrKind::Expression(None)
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         format!(
             "{} {} {}",
@@ -91,47 +194,53 @@ impl Instruction for BinaryOp {
 
         interpreter.debug("OP", self.op.to_str());
 
+        if self.op.is_logical() {
+            let value = self.execute_logical(interpreter)?;
+
+            interpreter.debug_step("BINOP EXIT");
+
+            return Ok(InstrKind::Expression(Some(value)));
+        }
+
         let l_value = self.execute_node(&self.lhs, interpreter)?;
         let r_value = self.execute_node(&self.rhs, interpreter)?;
 
-        if l_value.ty() != r_value.ty() {
-            return Err(JkError::new(
-                JkErrKind::Interpreter, // FIXME: Should be a type error
+        let (l_value, r_value) = self.promote(l_value, r_value)?;
+
+        // FIXME: Do not unwrap
+        let ty_name = l_value.ty().unwrap().name();
+        let value = match ty_name {
+            "int" => JkInt::from_instance(&l_value).do_op(&JkInt::from_instance(&r_value), self.op),
+            "float" => {
+                JkFloat::from_instance(&l_value).do_op(&JkFloat::from_instance(&r_value), self.op)
+            }
+            "char" => {
+                JkChar::from_instance(&l_value).do_op(&JkChar::from_instance(&r_value), self.op)
+            }
+            "string" => {
+                JkString::from_instance(&l_value).do_op(&JkString::from_instance(&r_value), self.op)
+            }
+            "bool" => {
+                JkBool::from_instance(&l_value).do_op(&JkBool::from_instance(&r_value), self.op)
+            }
+            _ => Err(JkError::new(
+                JkErrKind::Interpreter,
                 format!(
-                    "Trying to do binary operation on invalid types: {:#?} {} {:#?}",
-                    l_value.ty(),
+                    "operator `{}` is not implemented for type `{}`",
                     self.op.to_str(),
-                    r_value.ty() // FIXME: Display correctly
+                    ty_name
                 ),
-                None, // FIXME: Fix Location
+                self.location.clone(),
                 self.print(),
-            ));
-        }
-
-        let return_value;
-
-        // FIXME: DISGUSTING and do not unwap
-        match l_value.ty().unwrap().name() {
-            // FIXME: Absolutely DISGUSTING
-            "int" => {
-                return_value = InstrKind::Expression(Some(
-                    JkInt::from_instance(&l_value)
-                        .do_op(&JkInt::from_instance(&r_value), self.op)?,
-                ));
-            }
+            )),
+        };
 
-            "float" => {
-                return_value = InstrKind::Expression(Some(
-                    JkFloat::from_instance(&l_value)
-                        .do_op(&JkFloat::from_instance(&r_value), self.op)?,
-                ));
-            }
-            _ => todo!("Implement empty types?"),
-        }
+        // Values are not always located, so locate the error on the whole operation
+        let value = value.map_err(|e| e.locate(self.location()))?;
 
         interpreter.debug_step("BINOP EXIT");
 
-        Ok(return_value)
+        Ok(InstrKind::Expression(Some(value)))
     }
 }
 
@@ -142,6 +251,67 @@ impl Rename for BinaryOp {
     }
 }
 
+impl TypeCheck for BinaryOp {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let (l_ty, r_ty) =
+            BinaryOp::promote_types(self.lhs.resolve_type(ctx), self.rhs.resolve_type(ctx));
+
+        if !l_ty.compatible(&r_ty) {
+            ctx.error(
+                format!(
+                    "trying to do binary operation on invalid types: `{}` {} `{}`",
+                    l_ty,
+                    self.op.to_str(),
+                    r_ty
+                ),
+                self.location(),
+                self.print(),
+            );
+            return CheckedType::Unknown;
+        }
+
+        // If one of the operands is unknown, use the other one
+        let ty = match l_ty {
+            CheckedType::Unknown => r_ty,
+            _ => l_ty,
+        };
+
+        match &ty {
+            CheckedType::Resolved(id) if self.valid_types().contains(&id.id()) => {}
+            CheckedType::Unknown | CheckedType::Never => {}
+            CheckedType::Void => {
+                ctx.error(
+                    format!(
+                        "invalid use of statement in binary operation: {}",
+                        self.print()
+                    ),
+                    self.location(),
+                    self.print(),
+                );
+                return CheckedType::Unknown;
+            }
+            CheckedType::Resolved(id) => {
+                ctx.error(
+                    format!(
+                        "operator `{}` is not implemented for type `{}`",
+                        self.op.to_str(),
+                        id.id()
+                    ),
+                    self.location(),
+                    self.print(),
+                );
+                return CheckedType::Unknown;
+            }
+        }
+
+        // Comparisons and logical operations always produce a boolean
+        match self.op.is_comparison() || self.op.is_logical() {
+            true => CheckedType::resolved("bool"),
+            false => ty,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,6 +349,101 @@ mod tests {
         binop_assert(2, 99, "+", 101);
     }
 
+    #[test]
+    fn t_binop_mod_pow() {
+        binop_assert(17, 5, "%", 2);
+        binop_assert(-7, 2, "%", -1);
+        binop_assert(3, 4, "**", 81);
+    }
+
+    #[test]
+    fn t_binop_bitwise() {
+        binop_assert(6, 3, "&", 2);
+        binop_assert(6, 3, "|", 7);
+        binop_assert(6, 3, "^", 5);
+        binop_assert(1, 4, "<<", 16);
+        binop_assert(-16, 2, ">>", -4);
+    }
+
+    #[test]
+    fn t_binop_negative_int_power() {
+        let binop = BinaryOp::new(
+            Box::new(JkInt::from(2)),
+            Box::new(JkInt::from(-1)),
+            Operator::Pow,
+        );
+
+        let mut i = Interpreter::new();
+
+        assert!(binop.execute(&mut i).is_err());
+    }
+
+    fn binop_error(l_num: i64, r_num: i64, op: Operator) -> JkError {
+        let binop = BinaryOp::new(
+            Box::new(JkInt::from(l_num)),
+            Box::new(JkInt::from(r_num)),
+            op,
+        );
+
+        binop.execute(&mut Interpreter::new()).unwrap_err()
+    }
+
+    #[test]
+    fn t_binop_division_by_zero() {
+        assert_eq!(
+            binop_error(1, 0, Operator::Div).msg(),
+            "division by zero: 1 / 0"
+        );
+        assert_eq!(
+            binop_error(1, 0, Operator::Mod).msg(),
+            "division by zero: 1 % 0"
+        );
+    }
+
+    #[test]
+    fn t_binop_overflow() {
+        assert!(binop_error(i64::MAX, 1, Operator::Add)
+            .msg()
+            .starts_with("integer overflow"));
+        assert!(binop_error(i64::MIN, 1, Operator::Sub)
+            .msg()
+            .starts_with("integer overflow"));
+        assert!(binop_error(i64::MIN, -1, Operator::Div)
+            .msg()
+            .starts_with("integer overflow"));
+        assert!(binop_error(3, 40, Operator::Pow)
+            .msg()
+            .starts_with("integer overflow"));
+        assert!(binop_error(1, 64, Operator::LeftShift)
+            .msg()
+            .starts_with("invalid shift amount"));
+    }
+
+    #[test]
+    fn t_binop_float_mod_pow() {
+        let mut i = Interpreter::new();
+
+        let modulo = BinaryOp::new(
+            Box::new(JkFloat::from(7.5)),
+            Box::new(JkFloat::from(2.0)),
+            Operator::Mod,
+        );
+        let pow = BinaryOp::new(
+            Box::new(JkFloat::from(2.0)),
+            Box::new(JkFloat::from(0.5)),
+            Operator::Pow,
+        );
+
+        assert_eq!(
+            modulo.execute(&mut i).unwrap(),
+            InstrKind::Expression(Some(JkFloat::from(1.5).to_instance()))
+        );
+        assert_eq!(
+            pow.execute(&mut i).unwrap(),
+            InstrKind::Expression(Some(JkFloat::from(2.0f64.sqrt()).to_instance()))
+        );
+    }
+
     #[test]
     fn t_binop_mul_same() {
         binop_assert(12, 12, "*", 144);
@@ -237,4 +502,79 @@ mod tests {
             InstrKind::Expression(Some(JkInt::from(36).to_instance()))
         );
     }
+
+    fn binop_assert_bool(l: Box<dyn Instruction>, r: Box<dyn Instruction>, op: &str, res: bool) {
+        let binop = BinaryOp::new(l, r, Operator::new(op));
+
+        let mut i = Interpreter::new();
+
+        assert_eq!(
+            binop.execute(&mut i).unwrap(),
+            InstrKind::Expression(Some(JkBool::from(res).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_binop_compare_primitives() {
+        binop_assert_bool(
+            Box::new(JkInt::from(1)),
+            Box::new(JkInt::from(2)),
+            "<",
+            true,
+        );
+        binop_assert_bool(
+            Box::new(JkFloat::from(1.5)),
+            Box::new(JkFloat::from(1.5)),
+            ">=",
+            true,
+        );
+        binop_assert_bool(
+            Box::new(JkChar::from('a')),
+            Box::new(JkChar::from('b')),
+            "==",
+            false,
+        );
+        binop_assert_bool(
+            Box::new(JkString::from("jinko")),
+            Box::new(JkString::from("jinko")),
+            "==",
+            true,
+        );
+        binop_assert_bool(
+            Box::new(JkBool::from(true)),
+            Box::new(JkBool::from(false)),
+            "!=",
+            true,
+        );
+    }
+
+    #[test]
+    fn t_binop_short_circuit() {
+        // The right hand side is not a boolean, and would error if executed
+        binop_assert_bool(
+            Box::new(JkBool::from(false)),
+            Box::new(JkInt::from(12)),
+            "&&",
+            false,
+        );
+        binop_assert_bool(
+            Box::new(JkBool::from(true)),
+            Box::new(JkInt::from(12)),
+            "||",
+            true,
+        );
+    }
+
+    #[test]
+    fn t_binop_not_implemented() {
+        let binop = BinaryOp::new(
+            Box::new(JkBool::from(true)),
+            Box::new(JkBool::from(false)),
+            Operator::Add,
+        );
+
+        let mut i = Interpreter::new();
+
+        assert!(binop.execute(&mut i).is_err());
+    }
 }
@@ -17,21 +17,37 @@
 //!
 //! The return value of the function is the last instruction if it is an expression.
 //! Otherwise, it's `void`
+//!
+//! Functions and types declared in a block are declared before any other instruction
+//! of the block is executed, so that they can be used before their declaration.
 
-use crate::{InstrKind, Instruction, Interpreter, JkError, Rename};
+use crate::instruction::{FunctionDec, TypeDec};
+use crate::{
+    CheckedType, InstrKind, Instruction, Interpreter, JkErrSpaceLocation, JkError, Rename,
+    TypeCheck, TypeCtx,
+};
 
 #[derive(Clone)]
 pub struct Block {
     instructions: Vec<Box<dyn Instruction>>,
     last: Option<Box<dyn Instruction>>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl Block {
+    /// Check if an instruction declares a function or a type. Such instructions are
+    /// hoisted, and handled before the other instructions of a block
+    pub fn is_hoisted(instruction: &dyn Instruction) -> bool {
+        instruction.downcast_ref::<FunctionDec>().is_some()
+            || instruction.downcast_ref::<TypeDec>().is_some()
+    }
+
     /// Create a new function
     pub fn new() -> Block {
         Block {
             instructions: Vec::new(),
             last: None,
+            location: None,
         }
     }
 
@@ -79,6 +95,14 @@ impl Instruction for Block {
         }
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         let mut base = String::from("{\n");
 
@@ -104,15 +128,30 @@ impl Instruction for Block {
         interpreter.scope_enter();
         interpreter.debug_step("BLOCK ENTER");
 
-        self.instructions()
+        // Errors which do not know where they come from are located on the
+        // instruction which emitted them. The scope is exited even when an error, or a
+        // control flow signal, interrupts the block
+        let ret_val = self
+            .instructions()
             .iter()
-            .map(|inst| inst.execute(interpreter))
-            .collect::<Result<Vec<InstrKind>, JkError>>()?;
-
-        let ret_val = match &self.last {
-            Some(e) => e.execute(interpreter),
-            None => Ok(InstrKind::Statement),
-        };
+            .chain(self.last.iter())
+            .filter(|inst| Block::is_hoisted(inst.as_ref()))
+            .chain(
+                self.instructions()
+                    .iter()
+                    .filter(|inst| !Block::is_hoisted(inst.as_ref())),
+            )
+            .try_for_each(|inst| {
+                inst.execute(interpreter)
+                    .map(|_| ())
+                    .map_err(|e| e.locate(inst.location()))
+            })
+            .and_then(|_| match &self.last {
+                Some(e) if !Block::is_hoisted(e.as_ref()) => e
+                    .execute(interpreter)
+                    .map_err(|err| err.locate(e.location())),
+                _ => Ok(InstrKind::Statement),
+            });
 
         interpreter.scope_exit();
         interpreter.debug_step("BLOCK EXIT");
@@ -134,6 +173,36 @@ impl Rename for Block {
     }
 }
 
+impl TypeCheck for Block {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        ctx.scope_enter();
+
+        self.instructions
+            .iter()
+            .chain(self.last.iter())
+            .for_each(|instr| instr.hoist(ctx));
+
+        // A block containing an instruction which never completes never completes
+        // either, unless it has a last expression
+        let mut diverges = false;
+        for instr in self.instructions.iter() {
+            if instr.resolve_type(ctx) == CheckedType::Never {
+                diverges = true;
+            }
+        }
+
+        let ty = match &self.last {
+            Some(last) => last.resolve_type(ctx),
+            None if diverges => CheckedType::Never,
+            None => CheckedType::Void,
+        };
+
+        ctx.scope_exit();
+
+        ty
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
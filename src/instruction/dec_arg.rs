@@ -22,6 +22,12 @@ impl DecArg {
     pub fn get_type(&self) -> &TypeId {
         &self.ty
     }
+
+    /// Rename the argument's type but not the argument itself. This is used for the
+    /// fields of a type, whose names are not renamed
+    pub fn prefix_type(&mut self, prefix: &str) {
+        self.ty.prefix(prefix);
+    }
 }
 
 impl Rename for DecArg {
@@ -0,0 +1,165 @@
+//! Destructurings bind the fields of a sum type's instance to new variables, in the
+//! current scope: `Circle(r) = shape;`. The variant of the instance is checked when
+//! destructuring it, and destructuring the wrong variant is an error. Use a `match`
+//! to handle every variant instead.
+
+use crate::instruction::{InstrKind, Instruction, Pattern};
+use crate::{
+    CheckedType, Interpreter, JkErrKind, JkErrSpaceLocation, JkError, Rename, TypeCheck, TypeCtx,
+};
+
+#[derive(Clone)]
+pub struct Destructure {
+    pattern: Pattern,
+    value: Box<dyn Instruction>,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl Destructure {
+    /// Create a new destructuring of a value, using a pattern
+    pub fn new(pattern: Pattern, value: Box<dyn Instruction>) -> Destructure {
+        Destructure {
+            pattern,
+            value,
+            location: None,
+        }
+    }
+
+    /// Return a reference to the pattern used to destructure the value
+    #[cfg(test)]
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    fn error(&self, msg: String) -> JkError {
+        JkError::new(
+            JkErrKind::Interpreter,
+            msg,
+            self.location.clone(),
+            self.print(),
+        )
+    }
+}
+
+impl Instruction for Destructure {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Statement
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!("{} = {}", self.pattern.print(), self.value.print())
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("DESTRUCTURE", &self.print());
+
+        let instance = self.value.execute_expression(interpreter)?;
+
+        let variant = match instance.variant() {
+            Some(variant) => variant.name(),
+            None => {
+                return Err(self.error(format!(
+                    "cannot destructure a value of type `{}`",
+                    instance.ty().map_or("{unknown}", |ty| ty.name())
+                )))
+            }
+        };
+
+        if !self.pattern.matches(variant) {
+            return Err(self.error(format!(
+                "cannot destructure variant `{}` using pattern `{}`",
+                variant,
+                self.pattern.print()
+            )));
+        }
+
+        self.pattern
+            .bind(&instance, interpreter, self.location.as_ref())?;
+
+        Ok(InstrKind::Statement)
+    }
+}
+
+impl Rename for Destructure {
+    fn prefix(&mut self, prefix: &str) {
+        self.pattern.prefix(prefix);
+        self.value.prefix(prefix);
+    }
+}
+
+impl TypeCheck for Destructure {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let value_ty = self.value.resolve_type(ctx);
+
+        let type_dec = match &value_ty {
+            CheckedType::Resolved(ty) => ctx.get_type(ty).filter(|type_dec| type_dec.is_sum()),
+            _ => None,
+        };
+
+        match (&value_ty, &type_dec) {
+            (_, Some(_)) | (CheckedType::Unknown, _) | (CheckedType::Never, _) => {}
+            (ty, None) => ctx.error(
+                format!(
+                    "cannot destructure a value of type `{}`: only sum types can be destructured",
+                    ty
+                ),
+                self.value.location(),
+                self.print(),
+            ),
+        }
+
+        self.pattern
+            .declare_bindings(type_dec.as_deref(), ctx, self.location());
+
+        CheckedType::Void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JkInt, ToObjectInstance};
+
+    /// Execute the input after declaring the type used by the tests
+    fn execute(input: &str) -> Result<InstrKind, JkError> {
+        crate::utils::execute(&format!(
+            "type Shape = Circle(r: int) | Rect(w: int, h: int); {}",
+            input
+        ))
+    }
+
+    #[test]
+    fn t_print() {
+        let destructure = Destructure::new(
+            Pattern::Variant("Circle".to_owned(), vec!["r".to_owned()]),
+            Box::new(JkInt::from(1)),
+        );
+
+        assert_eq!(destructure.print(), "Circle(r) = 1");
+    }
+
+    #[test]
+    fn t_destructure() {
+        assert_eq!(
+            execute("Rect(w, _) = Rect(3, 4); w").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_destructure_wrong_variant() {
+        assert_eq!(
+            execute("Circle(r) = Rect(3, 4)").unwrap_err().msg(),
+            "cannot destructure variant `Rect` using pattern `Circle(r)`"
+        );
+        assert!(execute("Circle(r) = 4").is_err());
+    }
+}
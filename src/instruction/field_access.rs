@@ -0,0 +1,206 @@
+//! FieldAccesses are used to get the value of one of the fields of a custom type's
+//! instance. Accesses can be chained in order to reach the fields of nested instances:
+//! `t.value.value`
+
+use crate::instruction::{TypeDec, TypeId};
+use crate::{
+    CheckedType, InstrKind, Instruction, Interpreter, JkErrKind, JkErrSpaceLocation, JkError,
+    ObjectInstance, Rename, TypeCheck, TypeCtx,
+};
+
+#[derive(Clone)]
+pub struct FieldAccess {
+    instance: Box<dyn Instruction>,
+    field_name: String,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl FieldAccess {
+    /// Create a new field access on an instance
+    pub fn new(instance: Box<dyn Instruction>, field_name: String) -> FieldAccess {
+        FieldAccess {
+            instance,
+            field_name,
+            location: None,
+        }
+    }
+
+    /// Return the name of the accessed field
+    #[cfg(test)]
+    pub fn field_name(&self) -> &str {
+        &self.field_name
+    }
+
+    /// Error emitted when the accessed field does not exist on the given type
+    fn missing_field(&self, ty_name: &str) -> String {
+        format!("no field `{}` on type `{}`", self.field_name, ty_name)
+    }
+
+    /// Get the declared type of the accessed field on a given type declaration
+    fn field_type<'dec>(&self, type_dec: &'dec TypeDec) -> Option<&'dec TypeId> {
+        type_dec
+            .fields()
+            .iter()
+            .find(|field| field.name() == self.field_name)
+            .map(|field| field.get_type())
+    }
+
+    /// Get the accessed field out of an instance
+    fn access(
+        &self,
+        instance: &ObjectInstance,
+        interpreter: &mut Interpreter,
+    ) -> Result<ObjectInstance, JkError> {
+        let error = |msg| {
+            JkError::new(
+                JkErrKind::Interpreter,
+                msg,
+                self.location.clone(),
+                self.print(),
+            )
+        };
+
+        let type_dec = match instance.ty() {
+            Some(type_dec) => type_dec,
+            None => {
+                return Err(error(format!(
+                    "cannot access field `{}` of a value without a type",
+                    self.field_name
+                )))
+            }
+        };
+
+        let field_ty = match self.field_type(type_dec) {
+            Some(field_ty) => interpreter.get_type(field_ty).map(|ty| (*ty).clone()),
+            None => return Err(error(self.missing_field(type_dec.name()))),
+        };
+
+        instance
+            .field_instance(&self.field_name, field_ty)
+            .ok_or_else(|| error(self.missing_field(type_dec.name())))
+    }
+}
+
+impl Instruction for FieldAccess {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Expression(None)
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!("{}.{}", self.instance.print(), self.field_name)
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("FIELD ACCESS", &self.print());
+
+        let instance = self.instance.execute_expression(interpreter)?;
+        let field = self.access(&instance, interpreter)?;
+
+        Ok(InstrKind::Expression(Some(field)))
+    }
+}
+
+impl Rename for FieldAccess {
+    fn prefix(&mut self, prefix: &str) {
+        // Field names are part of their type, and are never renamed
+        self.instance.prefix(prefix);
+    }
+}
+
+impl TypeCheck for FieldAccess {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let ty_id = match self.instance.resolve_type(ctx) {
+            CheckedType::Resolved(ty_id) => ty_id,
+            CheckedType::Void => {
+                ctx.error(
+                    format!("cannot access field `{}` of a statement", self.field_name),
+                    self.location(),
+                    self.print(),
+                );
+                return CheckedType::Unknown;
+            }
+            CheckedType::Unknown | CheckedType::Never => return CheckedType::Unknown,
+        };
+
+        let field_ty = ctx
+            .get_type(&ty_id)
+            .and_then(|type_dec| self.field_type(&type_dec).cloned());
+
+        match field_ty {
+            Some(field_ty) => CheckedType::Resolved(field_ty),
+            None => {
+                ctx.error(
+                    self.missing_field(ty_id.id()),
+                    self.location(),
+                    self.print(),
+                );
+                CheckedType::Unknown
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JkInt, JkString, ToObjectInstance};
+
+    /// Declarations of the object used by the tests
+    const OBJECT: &str = "type Inner(value: string, id: int);
+        type Outer(id: int, inner: Inner);
+        o = Outer { 1, Inner { \"jinko\", 2 } };";
+
+    /// Execute the input after declaring the object used by the tests
+    fn execute(input: &str) -> Result<InstrKind, JkError> {
+        crate::utils::execute(&format!("{} {}", OBJECT, input))
+    }
+
+    #[test]
+    fn t_print() {
+        let access = FieldAccess::new(
+            Box::new(FieldAccess::new(
+                Box::new(JkInt::from(12)),
+                String::from("inner"),
+            )),
+            String::from("value"),
+        );
+
+        assert_eq!(access.print(), "12.inner.value");
+    }
+
+    #[test]
+    fn t_access_field() {
+        assert_eq!(
+            execute("o.id").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(1).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_access_nested_field() {
+        assert_eq!(
+            execute("o.inner.value").unwrap(),
+            InstrKind::Expression(Some(JkString::from("jinko").to_instance()))
+        );
+        assert_eq!(
+            execute("o.inner.id").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(2).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_access_missing_field() {
+        assert_eq!(
+            execute("o.inner.nope").unwrap_err().msg(),
+            "no field `nope` on type `Inner`"
+        );
+    }
+}
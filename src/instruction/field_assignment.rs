@@ -0,0 +1,308 @@
+//! FieldAssigns are used when assigning a new value to one of the fields of a custom
+//! type's instance, such as `p.x = 3` or `segment.start.x = 3`. The variable holding
+//! the instance needs to be mutable, and the value needs to be of the same type as
+//! the one declared for the field.
+
+use crate::instruction::{TypeDec, TypeId};
+use crate::{
+    CheckedType, InstrKind, Instruction, Interpreter, JkErrKind, JkErrSpaceLocation, JkError,
+    ObjectInstance, Rename, TypeCheck, TypeCtx,
+};
+
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct FieldAssign {
+    /// The "name" of the variable containing the instance
+    symbol: String,
+
+    /// The path to the assigned field, from the variable's instance
+    fields: Vec<String>,
+
+    value: Box<dyn Instruction>,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl FieldAssign {
+    /// Create a new field assignment. `fields` cannot be empty
+    pub fn new(symbol: String, fields: Vec<String>, value: Box<dyn Instruction>) -> FieldAssign {
+        FieldAssign {
+            symbol,
+            fields,
+            value,
+            location: None,
+        }
+    }
+
+    /// Get a reference to the symbol of the variable containing the instance
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Return the full path of the assigned field, without the variable: `start.x`
+    fn path(&self) -> String {
+        self.fields.join(".")
+    }
+
+    /// Follow the path of the assigned field from the type of the variable, and return
+    /// the type declared for the field. `get_type` is used to get the declaration of
+    /// nested types
+    fn field_type<F: Fn(&TypeId) -> Option<Rc<TypeDec>>>(
+        &self,
+        type_dec: Rc<TypeDec>,
+        get_type: F,
+    ) -> Result<TypeId, String> {
+        let mut type_dec = type_dec;
+        let mut field_ty = None;
+
+        for field_name in self.fields.iter() {
+            if let Some(ty) = &field_ty {
+                type_dec = match get_type(ty) {
+                    Some(type_dec) => type_dec,
+                    None => return Err(format!("cannot find type `{}`", ty.id())),
+                }
+            }
+
+            field_ty = match type_dec.fields().iter().find(|f| f.name() == field_name) {
+                Some(field) => Some(field.get_type().clone()),
+                None => {
+                    return Err(format!(
+                        "no field `{}` on type `{}`",
+                        field_name,
+                        type_dec.name()
+                    ))
+                }
+            }
+        }
+
+        // We can unwrap since a field assignment always contains at least one field
+        Ok(field_ty.unwrap())
+    }
+
+    fn error(&self, msg: String) -> JkError {
+        JkError::new(
+            JkErrKind::Interpreter,
+            msg,
+            self.location.clone(),
+            self.print(),
+        )
+    }
+}
+
+impl Instruction for FieldAssign {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Statement
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!("{}.{} = {}", self.symbol, self.path(), self.value.print())
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("ASSIGN FIELD", &self.print());
+
+        let mut var = match interpreter.get_variable(&self.symbol) {
+            Some(var) => var.clone(),
+            None => {
+                return Err(self.error(format!(
+                    "trying to assign field of undefined variable `{}`",
+                    self.symbol
+                )))
+            }
+        };
+
+        if !var.mutable() {
+            return Err(self
+                .error(format!(
+                    "trying to assign field of non mutable variable `{}`: `{}`",
+                    var.name(),
+                    self.value.print()
+                ))
+                .with_help(format!(
+                    "declare the variable as mutable: `mut {} = ...`",
+                    var.name()
+                )));
+        }
+
+        let mut instance = var.instance();
+        let type_dec = match instance.ty() {
+            Some(type_dec) => Rc::new(type_dec.clone()),
+            None => {
+                return Err(self.error(format!(
+                    "cannot assign field of a value without a type: `{}`",
+                    var.name()
+                )))
+            }
+        };
+
+        let field_ty = self
+            .field_type(type_dec, |ty| interpreter.get_type(ty))
+            .map_err(|msg| self.error(msg))?;
+
+        let value: ObjectInstance = self.value.execute_expression(interpreter)?;
+        let value_ty = value.ty().map(|ty| ty.name()).unwrap_or_default();
+
+        if value_ty != field_ty.id() {
+            return Err(self.error(format!(
+                "trying to assign value of type `{}` to field `{}` of type `{}`",
+                value_ty,
+                self.path(),
+                field_ty.id()
+            )));
+        }
+
+        // We can unwrap since we checked that the field exists
+        instance.set_field(&self.path(), &value).unwrap();
+        var.set_instance(instance);
+
+        // We can unwrap safely since we checked that the variable exists
+        interpreter.replace_variable(var).unwrap();
+
+        Ok(InstrKind::Statement)
+    }
+}
+
+impl Rename for FieldAssign {
+    fn prefix(&mut self, prefix: &str) {
+        // Field names are part of their type, and are never renamed
+        self.value.prefix(prefix);
+        self.symbol = format!("{}{}", prefix, self.symbol)
+    }
+}
+
+impl TypeCheck for FieldAssign {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let value_ty = self.value.resolve_type(ctx);
+
+        let var_ty = match ctx.get_var(self.symbol()).cloned() {
+            Some(CheckedType::Resolved(ty)) => ty,
+            Some(_) => return CheckedType::Void,
+            None => {
+                ctx.error(
+                    format!(
+                        "trying to assign field of undefined variable `{}`",
+                        self.symbol
+                    ),
+                    self.location(),
+                    self.print(),
+                );
+                return CheckedType::Void;
+            }
+        };
+
+        let field_ty = match ctx.get_type(&var_ty) {
+            Some(type_dec) => self.field_type(type_dec, |ty| ctx.get_type(ty)),
+            None => return CheckedType::Void,
+        };
+
+        match field_ty {
+            Ok(field_ty) => {
+                let field_ty = CheckedType::Resolved(field_ty);
+                if !field_ty.compatible(&value_ty) {
+                    ctx.error(
+                        format!(
+                            "trying to assign value of type `{}` to field `{}` of type `{}`",
+                            value_ty,
+                            self.path(),
+                            field_ty
+                        ),
+                        self.location(),
+                        self.print(),
+                    );
+                }
+            }
+            Err(msg) => ctx.error(msg, self.location(), self.print()),
+        }
+
+        // A field assignment is always a statement
+        CheckedType::Void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::execute_in;
+    use crate::{JkInt, JkString, ToObjectInstance};
+
+    /// Create an interpreter containing the object used by the tests
+    fn interpreter(var_assign: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        let input = format!(
+            "type Inner(value: string, id: int); type Outer(inner: Inner, id: int); {}",
+            var_assign
+        );
+
+        execute_in(&input, &mut interpreter).unwrap();
+
+        interpreter
+    }
+
+    #[test]
+    fn t_print() {
+        let assign = FieldAssign::new(
+            "o".to_owned(),
+            vec!["inner".to_owned(), "id".to_owned()],
+            Box::new(JkInt::from(12)),
+        );
+
+        assert_eq!(assign.print(), "o.inner.id = 12");
+    }
+
+    #[test]
+    fn t_assign_nested_field() {
+        let mut i = interpreter("mut o = Outer { Inner { \"jinko\", 2 }, 1 };");
+
+        execute_in("o.inner.value = \"jinko is cool\"", &mut i).unwrap();
+        execute_in("o.inner.id = 3", &mut i).unwrap();
+
+        assert_eq!(
+            execute_in("o.inner.value", &mut i).unwrap(),
+            InstrKind::Expression(Some(JkString::from("jinko is cool").to_instance()))
+        );
+        assert_eq!(
+            execute_in("o.inner.id", &mut i).unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+        // The fields following the resized string must still be valid
+        assert_eq!(
+            execute_in("o.id", &mut i).unwrap(),
+            InstrKind::Expression(Some(JkInt::from(1).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_assign_immutable() {
+        let mut i = interpreter("o = Outer { Inner { \"jinko\", 2 }, 1 };");
+
+        assert!(execute_in("o.id = 3", &mut i).is_err());
+    }
+
+    #[test]
+    fn t_assign_wrong_type() {
+        let mut i = interpreter("mut o = Outer { Inner { \"jinko\", 2 }, 1 };");
+
+        assert_eq!(
+            execute_in("o.inner.id = \"3\"", &mut i).unwrap_err().msg(),
+            "trying to assign value of type `string` to field `inner.id` of type `int`"
+        );
+    }
+
+    #[test]
+    fn t_assign_missing_field() {
+        let mut i = interpreter("mut o = Outer { Inner { \"jinko\", 2 }, 1 };");
+
+        assert_eq!(
+            execute_in("o.inner.nope = 3", &mut i).unwrap_err().msg(),
+            "no field `nope` on type `Inner`"
+        );
+    }
+}
@@ -1,8 +1,12 @@
 //! FunctionCalls are used when calling a function. The argument list is given to the
 //! function on execution.
 
-use crate::instruction::{FunctionDec, Var};
-use crate::{InstrKind, Instruction, Interpreter, JkErrKind, JkError, Rename};
+use crate::builtins::{BuiltinFn, Builtins, InPlaceBuiltinFn};
+use crate::instruction::{FunctionDec, FunctionKind, GenericMap, TypeDec, TypeId, Var};
+use crate::{
+    CheckedType, ControlFlow, InstrKind, Instruction, Interpreter, JkErrKind, JkErrSpaceLocation,
+    JkError, ObjectInstance, Rename, TypeCheck, TypeCtx,
+};
 use std::rc::Rc;
 
 #[derive(Clone)]
@@ -10,8 +14,12 @@ pub struct FunctionCall {
     /// Name of the function to call
     fn_name: String,
 
+    /// Types given to the generic parameters of the function
+    generics: Vec<TypeId>,
+
     /// Arguments to give to the function
     args: Vec<Box<dyn Instruction>>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl FunctionCall {
@@ -19,7 +27,9 @@ impl FunctionCall {
     pub fn new(fn_name: String) -> FunctionCall {
         FunctionCall {
             fn_name,
+            generics: Vec::new(),
             args: Vec::new(),
+            location: None,
         }
     }
 
@@ -44,24 +54,54 @@ impl FunctionCall {
         &self.args
     }
 
-    /// Get the corresponding declaration from an interpreter
+    /// Return a reference to the generic arguments of the call
+    #[cfg(test)]
+    pub fn generics(&self) -> &Vec<TypeId> {
+        &self.generics
+    }
+
+    /// Set the generic arguments given to the called function
+    pub fn set_generics(&mut self, generics: Vec<TypeId>) {
+        self.generics = generics
+    }
+
+    /// Get the corresponding declaration from an interpreter. While a test runs, a
+    /// mocked function is replaced by its mock
     fn get_declaration(&self, interpreter: &mut Interpreter) -> Result<Rc<FunctionDec>, JkError> {
-        match interpreter.get_function(self.name()) {
-            // get_function() return a Rc, so this clones the Rc, not the FunctionDec
-            Some(f) => Ok(f.clone()),
-            // FIXME: Fix Location and input
-            None => Err(JkError::new(
+        // get_function() return a Rc, so this clones the Rc, not the FunctionDec
+        let function = match interpreter.get_function(self.name()) {
+            Some(f) => f.clone(),
+            None => {
+                return Err(JkError::new(
+                    JkErrKind::Interpreter,
+                    format!("cannot find function {}", self.name()),
+                    self.location.clone(),
+                    self.name().to_owned(),
+                ))
+            }
+        };
+
+        // The typechecker already checks the signature of mocks: This only catches
+        // programs which were not typechecked
+        match interpreter.get_mock(self.name()) {
+            Some(mock) if !mock.same_signature(&function) => Err(JkError::new(
                 JkErrKind::Interpreter,
-                format!("cannot find function {}", self.name()),
-                None,
-                self.name().to_owned(),
-            )),
+                format!(
+                    "mock `{}` does not match the signature of the function it replaces",
+                    mock.name()
+                ),
+                mock.location().cloned(),
+                mock.signature(),
+            )
+            .with_note(format!("expected `{}`", function.signature()))),
+            Some(mock) => Ok(mock.clone()),
+            None => Ok(function),
         }
     }
 
-    /// Check if the arguments received and the arguments expected match
-    fn check_args_count(&self, function: &FunctionDec) -> Result<(), JkError> {
-        match self.args().len() == function.args().len() {
+    /// Check if the amount of arguments received and the arguments expected match
+    fn check_args_count(&self, function: &FunctionDec, count: usize) -> Result<(), JkError> {
+        match count == function.args().len() {
             true => Ok(()),
             false => Err(JkError::new(
                 JkErrKind::Interpreter,
@@ -70,41 +110,168 @@ impl FunctionCall {
                     for call to function `{}`: expected {}, got {}",
                     self.name(),
                     function.args().len(),
-                    self.args().len()
+                    count
                 ),
-                None,
+                self.location.clone(),
                 "".to_owned(),
                 // FIXME: Add input and location
             )),
         }
     }
 
-    /// Map each argument to its corresponding instruction
-    fn map_args(
+    /// Check if the generic arguments received and the generic parameters expected
+    /// match
+    fn check_generics(&self, function: &FunctionDec) -> Result<(), String> {
+        match self.generics.len() == function.generics().len() {
+            true => Ok(()),
+            false => Err(format!(
+                "wrong number of generic arguments \
+                for call to function `{}`: expected {}, got {}",
+                self.name(),
+                function.generics().len(),
+                self.generics.len()
+            )),
+        }
+    }
+
+    /// Associate each generic parameter of the function to the type it stands for. If
+    /// the call does not specify any generic argument, they are inferred from the types
+    /// of the arguments given to the call
+    fn generic_map(
         &self,
         function: &FunctionDec,
+        args_ty: &[Option<TypeId>],
+    ) -> Result<GenericMap, String> {
+        if !self.generics.is_empty() || function.generics().is_empty() {
+            self.check_generics(function)?;
+
+            return Ok(function
+                .generics()
+                .iter()
+                .map(|generic| generic.id().to_owned())
+                .zip(self.generics.iter().cloned())
+                .collect());
+        }
+
+        let mut generics = GenericMap::new();
+        function
+            .args()
+            .iter()
+            .zip(args_ty)
+            .for_each(|(func_arg, arg_ty)| {
+                if let Some(arg_ty) = arg_ty {
+                    func_arg
+                        .get_type()
+                        .infer(arg_ty, function.generics(), &mut generics)
+                }
+            });
+
+        match function
+            .generics()
+            .iter()
+            .find(|generic| !generics.contains_key(generic.id()))
+        {
+            Some(generic) => Err(format!(
+                "cannot infer generic argument `{}` for call to function `{}`",
+                generic.id(),
+                self.name()
+            )),
+            None => Ok(generics),
+        }
+    }
+
+    /// Make each generic parameter of the function stand for the type given to the call,
+    /// in the function's scope. The given types are looked up beforehand, in the scope
+    /// of the caller
+    fn map_generics(
+        &self,
+        generics: &[(String, Rc<TypeDec>)],
         interpreter: &mut Interpreter,
     ) -> Result<(), JkError> {
-        for (call_arg, func_arg) in self.args.iter().zip(function.args()) {
+        for (generic, type_dec) in generics {
             interpreter.debug(
-                "VAR MAP",
-                format!("Mapping `{}` to `{}`", func_arg.name(), call_arg.print()).as_ref(),
+                "GENERIC MAP",
+                format!("Mapping `{}` to `{}`", generic, type_dec.name()).as_ref(),
             );
 
-            // Create a new variable, and execute the content of the function argument
+            interpreter.add_type_alias(generic, type_dec)?;
+        }
+
+        Ok(())
+    }
+
+    /// Look up the types given as generic arguments to the call, or inferred from the
+    /// arguments of the call
+    fn lookup_generics(
+        &self,
+        function: &FunctionDec,
+        args: &[ObjectInstance],
+        interpreter: &mut Interpreter,
+    ) -> Result<Vec<(String, Rc<TypeDec>)>, JkError> {
+        let error = |msg| {
+            JkError::new(
+                JkErrKind::Interpreter,
+                msg,
+                self.location.clone(),
+                self.print(),
+            )
+        };
+
+        let args_ty: Vec<Option<TypeId>> = args
+            .iter()
+            .map(|arg| arg.ty().map(|ty| ty.type_id()))
+            .collect();
+        let generics = self.generic_map(function, &args_ty).map_err(error)?;
+
+        function
+            .generics()
+            .iter()
+            .map(|generic| {
+                // We can unwrap since every generic parameter is mapped
+                let ty = generics.get(generic.id()).unwrap();
+                match interpreter.get_type(ty) {
+                    Some(type_dec) => Ok((generic.id().to_owned(), type_dec)),
+                    None => Err(error(format!("type not found: {}", ty.id()))),
+                }
+            })
+            .collect()
+    }
+
+    /// Execute the arguments given to the call, in order, in the scope of the caller
+    fn execute_args(&self, interpreter: &mut Interpreter) -> Result<Vec<ObjectInstance>, JkError> {
+        self.args
+            .iter()
+            .map(|arg| arg.execute_expression(interpreter))
+            .collect()
+    }
+
+    /// Map each argument to its corresponding instruction
+    fn map_args(
+        &self,
+        function: &FunctionDec,
+        args: Vec<ObjectInstance>,
+        interpreter: &mut Interpreter,
+    ) -> Result<(), JkError> {
+        for (mut instance, func_arg) in args.into_iter().zip(function.args()) {
+            if interpreter.debug_mode {
+                interpreter.debug(
+                    "VAR MAP",
+                    format!("Mapping `{}` to `{}`", func_arg.name(), instance).as_ref(),
+                );
+            }
+
+            // Create a new variable, containing the value of the function argument
             // passed to the call
             let mut new_var = Var::new(func_arg.name().to_owned());
-            let mut instance = call_arg.execute_expression(interpreter)?;
 
             let ty = match interpreter.get_type(func_arg.get_type()) {
-                // Double dereferencing: Some(t) gives us a &Rc<TypeDec>. We dereference
-                // it to access the Rc, and dereference it again to access the TypeDec.
-                Some(t) => (**t).clone(),
+                // Dereference the Rc<TypeDec> to access the TypeDec
+                Some(t) => (*t).clone(),
                 None => {
                     return Err(JkError::new(
                         JkErrKind::Interpreter,
                         format!("type not found: {}", func_arg.get_type().id()),
-                        None,
+                        self.location.clone(),
                         self.print(),
                     ))
                 }
@@ -119,6 +286,201 @@ impl FunctionCall {
 
         Ok(())
     }
+
+    /// Call a builtin function, implemented by the interpreter, with the already
+    /// executed arguments
+    fn call_builtin(
+        &self,
+        builtin: BuiltinFn,
+        args: Vec<ObjectInstance>,
+        interpreter: &mut Interpreter,
+    ) -> Result<InstrKind, JkError> {
+        interpreter.debug("BUILTIN", self.name());
+
+        builtin(interpreter, args).map_err(|e| e.locate(self.location()))
+    }
+
+    /// Take the value given as the first argument out of its variable, for a builtin
+    /// modifying that value in place. The builtin is then the only owner of the value,
+    /// which does not need to be copied. Returns `None` if the first argument is not a
+    /// variable
+    fn take_receiver(&self, interpreter: &mut Interpreter) -> Result<Option<Var>, JkError> {
+        let name = match self.args.first().and_then(|arg| arg.downcast_ref::<Var>()) {
+            Some(var) => var.name(),
+            None => return Ok(None),
+        };
+
+        let mut var = match interpreter.get_variable(name) {
+            Some(var) => var.clone(),
+            None => return Ok(None),
+        };
+
+        if !var.mutable() {
+            return Err(JkError::new(
+                JkErrKind::Interpreter,
+                format!(
+                    "trying to modify non mutable variable `{}` with `{}`",
+                    name,
+                    self.name()
+                ),
+                self.location.clone(),
+                self.print(),
+            )
+            .with_help(format!(
+                "declare the variable as mutable: `mut {} = ...`",
+                name
+            )));
+        }
+
+        var.set_instance(ObjectInstance::empty());
+        interpreter.replace_variable(var.clone())?;
+
+        Ok(Some(var))
+    }
+
+    /// Catch the value returned early by the function, if any. That value must be of
+    /// the type the function is declared to return. This needs to happen in the scope
+    /// of the function, where its generic parameters are known
+    fn unwind_return(
+        &self,
+        function: &FunctionDec,
+        ret_val: Result<InstrKind, JkError>,
+        interpreter: &Interpreter,
+    ) -> Result<InstrKind, JkError> {
+        let error = match ret_val {
+            Err(e) if matches!(e.signal(), Some(ControlFlow::Return(_))) => e,
+            ret_val => return ret_val,
+        };
+
+        let value = match error.into_signal() {
+            Some(ControlFlow::Return(value)) => value,
+            _ => None,
+        };
+
+        // Generic return types are resolved to the type they stand for
+        let return_ty = function.ty().map(|ty| match interpreter.get_type(ty) {
+            Some(type_dec) => type_dec.name().to_owned(),
+            None => ty.id().to_owned(),
+        });
+
+        let valid = match (&return_ty, &value) {
+            (None, None) => true,
+            // Values without a type cannot be checked
+            (Some(ty), Some(value)) => match value.ty() {
+                Some(value_ty) => value_ty.name() == ty,
+                None => true,
+            },
+            _ => false,
+        };
+
+        match (valid, value) {
+            (true, Some(value)) => Ok(InstrKind::Expression(Some(value))),
+            (true, None) => Ok(InstrKind::Statement),
+            (false, value) => Err(JkError::new(
+                JkErrKind::Interpreter,
+                format!(
+                    "function `{}` should return `{}` but returned `{}`",
+                    self.name(),
+                    return_ty.as_deref().unwrap_or("void"),
+                    value
+                        .as_ref()
+                        .map_or("void", |v| v.ty().map_or("{unknown}", |ty| ty.name())),
+                ),
+                self.location.clone(),
+                self.print(),
+            )),
+        }
+    }
+
+    /// Describe the declaration of the called function, to help users figure out how
+    /// to call it
+    fn declared_as(function: &FunctionDec) -> String {
+        match function.location() {
+            Some(loc) => format!("function declared as `{}` at {}", function.signature(), loc),
+            None => format!("function declared as `{}`", function.signature()),
+        }
+    }
+
+    /// Call the function with arguments which were already executed, instead of the
+    /// ones given to the call. This allows the interpreter to call jinko functions on
+    /// its own values
+    pub fn call_with(
+        &self,
+        args: Vec<ObjectInstance>,
+        interpreter: &mut Interpreter,
+    ) -> Result<InstrKind, JkError> {
+        let function = self.get_declaration(interpreter)?;
+
+        self.check_args_count(&function, args.len())?;
+
+        self.run(&function, args, interpreter)
+    }
+
+    /// Run the function in its own scope, with the given arguments
+    fn run(
+        &self,
+        function: &FunctionDec,
+        args: Vec<ObjectInstance>,
+        interpreter: &mut Interpreter,
+    ) -> Result<InstrKind, JkError> {
+        let generics = self.lookup_generics(function, &args, interpreter)?;
+
+        let builtin = match function.fn_kind() {
+            FunctionKind::Ext => interpreter.get_builtin(self.name()),
+            _ => None,
+        };
+
+        interpreter.function_scope_enter();
+
+        interpreter.debug("CALL", self.name());
+
+        let ret_val = self
+            .map_generics(&generics, interpreter)
+            .and_then(|_| match builtin {
+                Some(builtin) => self.call_builtin(builtin, args, interpreter),
+                None => self.map_args(function, args, interpreter).and_then(|_| {
+                    let ret_val = function.run(interpreter);
+                    self.unwind_return(function, ret_val, interpreter)
+                }),
+            });
+
+        interpreter.scope_exit();
+
+        ret_val
+    }
+
+    /// Run a builtin modifying its first argument in place. If that argument is a
+    /// variable, the variable is updated with the modified value. Otherwise, the
+    /// modified value is discarded
+    fn run_in_place(
+        &self,
+        builtin: InPlaceBuiltinFn,
+        function: &FunctionDec,
+        mut args: Vec<ObjectInstance>,
+        interpreter: &mut Interpreter,
+    ) -> Result<InstrKind, JkError> {
+        let generics = self.lookup_generics(function, &args, interpreter)?;
+        let receiver = self.take_receiver(interpreter)?;
+        let mut value = args.remove(0);
+
+        interpreter.function_scope_enter();
+
+        interpreter.debug("CALL", self.name());
+
+        let ret_val = self.map_generics(&generics, interpreter).and_then(|_| {
+            interpreter.debug("BUILTIN", self.name());
+            builtin(interpreter, &mut value, args).map_err(|e| e.locate(self.location()))
+        });
+
+        interpreter.scope_exit();
+
+        if let Some(mut var) = receiver {
+            var.set_instance(value);
+            interpreter.replace_variable(var)?;
+        }
+
+        ret_val
+    }
 }
 
 impl Instruction for FunctionCall {
@@ -127,8 +489,27 @@ impl Instruction for FunctionCall {
         InstrKind::Expression(None)
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
-        let mut base = format!("{}(", self.fn_name);
+        let mut base = match self.generics.is_empty() {
+            true => format!("{}(", self.fn_name),
+            false => format!(
+                "{}<{}>(",
+                self.fn_name,
+                self.generics
+                    .iter()
+                    .map(|generic| generic.id())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+        };
 
         let mut first_arg = true;
         for arg in &self.args {
@@ -147,36 +528,124 @@ impl Instruction for FunctionCall {
     fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
         let function = self.get_declaration(interpreter)?;
 
-        self.check_args_count(&function)?;
-
-        interpreter.scope_enter();
-
-        interpreter.debug("CALL", self.name());
+        self.check_args_count(&function, self.args.len())?;
+        let args = self.execute_args(interpreter)?;
 
-        match self.map_args(&function, interpreter) {
-            Ok(_) => {}
-            Err(e) => {
-                interpreter.scope_exit();
-                return Err(e);
-            }
+        let in_place = match function.fn_kind() {
+            FunctionKind::Ext => interpreter.get_in_place_builtin(self.name()),
+            _ => None,
         };
 
-        let ret_val = function.run(interpreter);
-
-        interpreter.scope_exit();
-
-        ret_val
+        match in_place {
+            Some(builtin) => self.run_in_place(builtin, &function, args, interpreter),
+            None => self.run(&function, args, interpreter),
+        }
     }
 }
 
 impl Rename for FunctionCall {
     fn prefix(&mut self, prefix: &str) {
-        self.fn_name = format!("{}{}", prefix, self.fn_name);
+        // Builtins are available from everywhere and must not be renamed
+        if !Builtins::is_builtin(&self.fn_name) {
+            self.fn_name = format!("{}{}", prefix, self.fn_name);
+        }
+
+        self.generics
+            .iter_mut()
+            .for_each(|generic| generic.prefix(prefix));
 
         self.args.iter_mut().for_each(|arg| arg.prefix(prefix));
     }
 }
 
+impl TypeCheck for FunctionCall {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let args_ty: Vec<CheckedType> = self.args.iter().map(|arg| arg.resolve_type(ctx)).collect();
+
+        let function = match ctx.get_function(self.name()) {
+            Some(f) => f,
+            None => {
+                ctx.error(
+                    format!("cannot find function {}", self.name()),
+                    self.location(),
+                    self.print(),
+                );
+                return CheckedType::Unknown;
+            }
+        };
+
+        if self.args.len() != function.args().len() {
+            ctx.report(
+                JkError::new(
+                    JkErrKind::TypeChecker,
+                    format!(
+                        "wrong number of arguments \
+                        for call to function `{}`: expected {}, got {}",
+                        self.name(),
+                        function.args().len(),
+                        self.args().len()
+                    ),
+                    self.location().cloned(),
+                    self.print(),
+                )
+                .with_note(FunctionCall::declared_as(&function)),
+            );
+        }
+
+        self.generics.iter().for_each(|generic| {
+            ctx.resolve(Some(generic), self.location());
+        });
+
+        let known_args_ty: Vec<Option<TypeId>> = args_ty
+            .iter()
+            .map(|arg_ty| match arg_ty {
+                CheckedType::Resolved(ty) => Some(ty.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let generics = match self.generic_map(&function, &known_args_ty) {
+            Ok(generics) => generics,
+            Err(msg) => {
+                ctx.report(
+                    JkError::new(
+                        JkErrKind::TypeChecker,
+                        msg,
+                        self.location().cloned(),
+                        self.print(),
+                    )
+                    .with_note(FunctionCall::declared_as(&function)),
+                );
+                GenericMap::new()
+            }
+        };
+
+        for ((call_arg, arg_ty), func_arg) in self.args.iter().zip(args_ty).zip(function.args()) {
+            let expected_ty = ctx.lookup(Some(&func_arg.get_type().substitute(&generics)));
+
+            if !arg_ty.compatible(&expected_ty) {
+                ctx.report(
+                    JkError::new(
+                        JkErrKind::TypeChecker,
+                        format!(
+                            "wrong type for argument `{}` of function `{}`: expected `{}`, got `{}`",
+                            func_arg.name(),
+                            self.name(),
+                            expected_ty,
+                            arg_ty,
+                        ),
+                        call_arg.location().cloned(),
+                        call_arg.print(),
+                    )
+                    .with_note(FunctionCall::declared_as(&function)),
+                );
+            }
+        }
+
+        ctx.lookup(function.ty().map(|ty| ty.substitute(&generics)).as_ref())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,4 +753,93 @@ mod tests {
             InstrKind::Expression(Some(JkInt::from(1).to_instance()))
         );
     }
+
+    fn call(declaration: &str, call: &str) -> Result<InstrKind, JkError> {
+        use crate::parser::Construct;
+
+        let mut i = Interpreter::new();
+        let func_dec = Construct::instruction(declaration).unwrap().1;
+        let func_call = Construct::instruction(call).unwrap().1;
+
+        func_dec.execute(&mut i).unwrap();
+        func_call.execute(&mut i)
+    }
+
+    #[test]
+    fn t_func_call_early_return() {
+        use crate::value::JkInt;
+        use crate::ToObjectInstance;
+
+        assert_eq!(
+            call("func f(x: int) -> int { if true { return x; }; 0 }", "f(4)").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(4).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_func_call_return_from_loop() {
+        use crate::value::JkInt;
+        use crate::ToObjectInstance;
+
+        assert_eq!(
+            call(
+                "func f() -> int { for i in range(2, 10) { loop { return i * 3; }; }; 0 }",
+                "f()"
+            )
+            .unwrap(),
+            InstrKind::Expression(Some(JkInt::from(6).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_func_call_return_void() {
+        assert_eq!(
+            call("func f() { return; }", "f()").unwrap(),
+            InstrKind::Statement
+        );
+    }
+
+    #[test]
+    fn t_func_call_return_wrong_type() {
+        assert!(call("func f() -> int { return 1.5; }", "f()").is_err());
+        assert!(call("func f() -> int { return; }", "f()").is_err());
+        assert!(call("func f() { return 1; }", "f()").is_err());
+    }
+
+    #[test]
+    fn t_func_call_generic() {
+        use crate::parser::Construct;
+        use crate::value::JkString;
+        use crate::ToObjectInstance;
+
+        let mut i = Interpreter::new();
+        let func_dec = Construct::instruction("func id<T>(value: T) -> T { value }")
+            .unwrap()
+            .1;
+        func_dec.execute(&mut i).unwrap();
+
+        let func_call = Construct::instruction("id<string>(\"jinko\")").unwrap().1;
+        assert_eq!(
+            func_call.execute(&mut i).unwrap(),
+            InstrKind::Expression(Some(JkString::from("jinko").to_instance()))
+        );
+
+        // Generic arguments are inferred from the arguments of the call
+        let func_call = Construct::instruction("id(\"jinko\")").unwrap().1;
+        assert_eq!(
+            func_call.execute(&mut i).unwrap(),
+            InstrKind::Expression(Some(JkString::from("jinko").to_instance()))
+        );
+
+        let func_dec = Construct::instruction("func none<T>() -> int { 0 }")
+            .unwrap()
+            .1;
+        func_dec.execute(&mut i).unwrap();
+
+        let func_call = Construct::instruction("none()").unwrap().1;
+        assert_eq!(
+            func_call.execute(&mut i).unwrap_err().msg(),
+            "cannot infer generic argument `T` for call to function `none`"
+        );
+    }
 }
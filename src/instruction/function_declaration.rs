@@ -1,8 +1,13 @@
 //! Function Declarations are used when adding a new function to the source. They contain
-//! a name, a list of required arguments as well as an associated code block
+//! a name, a list of required arguments as well as an associated code block. Functions
+//! can be generic over types: `func id<T>(value: T) -> T`
 
-use crate::instruction::{Block, DecArg, InstrKind, Instruction, TypeId};
-use crate::{Interpreter, JkErrKind, JkError, Rename};
+use crate::instruction::{
+    Block, DecArg, InstrKind, Instruction, TypeDec, TypeId, VariantInstantiation,
+};
+use crate::{
+    CheckedType, Interpreter, JkErrKind, JkErrSpaceLocation, JkError, Rename, TypeCheck, TypeCtx,
+};
 
 /// What "kind" of function is defined. There are four types of functions in jinko,
 /// the normal ones, the external ones, the unit tests and the mocks
@@ -18,10 +23,12 @@ pub enum FunctionKind {
 #[derive(Clone)]
 pub struct FunctionDec {
     name: String,
+    generics: Vec<TypeId>,
     ty: Option<TypeId>,
     kind: FunctionKind,
     args: Vec<DecArg>,
     block: Option<Block>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl FunctionDec {
@@ -29,10 +36,12 @@ impl FunctionDec {
     pub fn new(name: String, ty: Option<TypeId>) -> FunctionDec {
         FunctionDec {
             name,
+            generics: Vec::new(),
             ty,
             kind: FunctionKind::Unknown,
             args: Vec::new(),
             block: None,
+            location: None,
         }
     }
 
@@ -54,7 +63,7 @@ impl FunctionDec {
                     "function {} has no instruction block. It might be an extern function or an error",
                     self.name
                 ),
-                None,
+                self.location.clone(),
                 self.name.clone(),
             )),
         }
@@ -65,6 +74,26 @@ impl FunctionDec {
         &self.name
     }
 
+    /// Is the function the constructor of one of the variants of a sum type. Such
+    /// constructors are generated from the type's declaration
+    pub fn is_constructor(&self) -> bool {
+        self.block
+            .as_ref()
+            .and_then(|block| block.last())
+            .and_then(|last| last.downcast_ref::<VariantInstantiation>())
+            .is_some()
+    }
+
+    /// Return a reference to the function's generic parameters
+    pub fn generics(&self) -> &Vec<TypeId> {
+        &self.generics
+    }
+
+    /// Set the generic parameters of the function
+    pub fn set_generics(&mut self, generics: Vec<TypeId>) {
+        self.generics = generics
+    }
+
     /// Return a reference to the function's return type
     pub fn ty(&self) -> Option<&TypeId> {
         self.ty.as_ref()
@@ -111,7 +140,6 @@ impl FunctionDec {
     pub fn run(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
         let block = match self.block() {
             Some(b) => b,
-            // FIXME: Fix Location and input
             None => {
                 return Err(JkError::new(
                     JkErrKind::Interpreter,
@@ -119,7 +147,7 @@ impl FunctionDec {
                         "cannot execute function {} as it is marked `ext`",
                         self.name()
                     ),
-                    None,
+                    self.location.clone(),
                     self.name().to_owned(),
                 ))
             }
@@ -127,6 +155,60 @@ impl FunctionDec {
 
         block.execute(interpreter)
     }
+
+    /// Check if a function can stand for another one, such as a mock standing for the
+    /// function it replaces. The name of the arguments does not matter, only their type
+    pub fn same_signature(&self, other: &FunctionDec) -> bool {
+        self.generics == other.generics
+            && self.ty == other.ty
+            && self.args.len() == other.args.len()
+            && self
+                .args
+                .iter()
+                .zip(other.args.iter())
+                .all(|(lhs, rhs)| lhs.get_type() == rhs.get_type())
+    }
+
+    /// Pretty-print the signature of the function, without its block
+    pub fn signature(&self) -> String {
+        let mut base = String::from(match self.kind {
+            FunctionKind::Func => "func",
+            FunctionKind::Ext => "ext func",
+            FunctionKind::Test => "test",
+            FunctionKind::Mock => "mock",
+            FunctionKind::Unknown => "UNKNOWN",
+        });
+
+        base = match self.generics.is_empty() {
+            true => format!("{} {}(", base, self.name),
+            false => format!(
+                "{} {}<{}>(",
+                base,
+                self.name,
+                self.generics
+                    .iter()
+                    .map(|generic| generic.id())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+        };
+
+        let mut first_arg = true;
+        for arg in &self.args {
+            if !first_arg {
+                base.push_str(", ");
+            }
+
+            base.push_str(&format!("{}: {}", arg.name(), arg.get_type().id()));
+
+            first_arg = false;
+        }
+
+        match &self.ty {
+            Some(ty) => format!("{}) -> {}", base, ty.id()),
+            None => format!("{})", base),
+        }
+    }
 }
 
 impl Instruction for FunctionDec {
@@ -140,11 +222,12 @@ impl Instruction for FunctionDec {
         match self.fn_kind() {
             FunctionKind::Func | FunctionKind::Ext => interpreter.add_function(self.clone())?,
             FunctionKind::Test => interpreter.add_test(self.clone())?,
-            FunctionKind::Mock | FunctionKind::Unknown => {
+            FunctionKind::Mock => interpreter.add_mock(self.clone())?,
+            FunctionKind::Unknown => {
                 return Err(JkError::new(
                     JkErrKind::Interpreter,
                     format!("unknown type for function {}", self.name()),
-                    None,
+                    self.location.clone(),
                     self.name().to_owned(),
                 ))
             }
@@ -155,32 +238,16 @@ impl Instruction for FunctionDec {
         Ok(InstrKind::Statement)
     }
 
-    fn print(&self) -> String {
-        let mut base = String::from(match self.kind {
-            FunctionKind::Func => "func",
-            FunctionKind::Ext => "ext func",
-            FunctionKind::Test => "test",
-            FunctionKind::Mock => "mock",
-            FunctionKind::Unknown => "UNKNOWN",
-        });
-
-        base = format!("{} {}(", base, self.name);
-
-        let mut first_arg = true;
-        for arg in &self.args {
-            if !first_arg {
-                base.push_str(", ");
-            }
-
-            base.push_str(&format!("{}: {}", arg.name(), arg.get_type().id()));
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
 
-            first_arg = false;
-        }
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
 
-        base = match &self.ty {
-            Some(ty) => format!("{}) -> {}", base, ty.id()),
-            None => format!("{})", base),
-        };
+    fn print(&self) -> String {
+        let base = self.signature();
 
         match &self.block {
             Some(block) => format!("{} {}", base, block.print()),
@@ -192,9 +259,13 @@ impl Instruction for FunctionDec {
 impl Rename for FunctionDec {
     fn prefix(&mut self, prefix: &str) {
         self.name = format!("{}{}", prefix, self.name);
-        self.set_ty(self.ty().map_or(None, |ty| {
-            Some(TypeId::from(format!("{}{}", prefix, ty.id()).as_str()))
-        }));
+        if let Some(ty) = &mut self.ty {
+            ty.prefix(prefix);
+        }
+
+        self.generics
+            .iter_mut()
+            .for_each(|generic| generic.prefix(prefix));
 
         match &mut self.block {
             Some(b) => b.prefix(prefix),
@@ -205,6 +276,84 @@ impl Rename for FunctionDec {
     }
 }
 
+impl FunctionDec {
+    /// A mock must replace a function declared before it, and have the same signature
+    fn check_mock(&self, ctx: &mut TypeCtx) {
+        match ctx.get_function(self.name()) {
+            None => ctx.error(
+                format!("cannot mock undeclared function `{}`", self.name()),
+                self.location(),
+                self.signature(),
+            ),
+            Some(original) if !self.same_signature(&original) => ctx.report(
+                JkError::new(
+                    JkErrKind::TypeChecker,
+                    format!(
+                        "mock `{}` does not match the signature of the function it replaces",
+                        self.name()
+                    ),
+                    self.location.clone(),
+                    self.signature(),
+                )
+                .with_note(format!("expected `{}`", original.signature())),
+            ),
+            Some(_) => {}
+        }
+    }
+}
+
+impl TypeCheck for FunctionDec {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        // The function itself was declared when hoisting it
+        if self.fn_kind() == FunctionKind::Mock {
+            self.check_mock(ctx);
+        }
+
+        ctx.function_scope_enter();
+
+        // Generic parameters are only known inside the function. They are opaque types
+        // which can only be passed around
+        self.generics
+            .iter()
+            .for_each(|generic| ctx.declare_type(TypeDec::from(generic.id())));
+
+        let return_ty = ctx.resolve(self.ty(), self.location());
+        let enclosing = ctx.function_enter(return_ty.clone());
+
+        self.args.iter().for_each(|arg| {
+            let arg_ty = ctx.resolve(Some(arg.get_type()), self.location());
+            ctx.declare_var(arg.name(), arg_ty);
+        });
+
+        if let Some(block) = self.block() {
+            let block_ty = block.resolve_type(ctx);
+
+            if !block_ty.compatible(&return_ty) {
+                ctx.error(
+                    format!(
+                        "function `{}` should return `{}` but its block returns `{}`",
+                        self.name, return_ty, block_ty
+                    ),
+                    self.location(),
+                    self.name().to_owned(),
+                );
+            }
+        }
+
+        ctx.function_exit(enclosing);
+        ctx.scope_exit();
+
+        // Declaring a function is always a statement
+        CheckedType::Void
+    }
+
+    fn hoist(&self, ctx: &mut TypeCtx) {
+        if let FunctionKind::Func | FunctionKind::Ext = self.fn_kind() {
+            ctx.declare_function(self.clone());
+        }
+    }
+}
+
 impl Default for FunctionDec {
     fn default() -> Self {
         FunctionDec::new(String::new(), None)
@@ -243,4 +392,18 @@ mod tests {
 
         assert_eq!(function.print(), "func fn(arg0: int, arg1: int) -> int {}");
     }
+
+    #[test]
+    fn same_signature() {
+        let mut function = FunctionDec::new("fn".to_owned(), Some(TypeId::from("int")));
+        function.set_args(vec![DecArg::new("x".to_owned(), TypeId::from("int"))]);
+
+        let mut mock = FunctionDec::new("fn".to_owned(), Some(TypeId::from("int")));
+        mock.set_kind(FunctionKind::Mock);
+        mock.set_args(vec![DecArg::new("y".to_owned(), TypeId::from("int"))]);
+        assert!(mock.same_signature(&function));
+
+        mock.set_ty(None);
+        assert!(!mock.same_signature(&function));
+    }
 }
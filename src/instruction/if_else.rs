@@ -16,13 +16,14 @@
 //! ```
 
 use crate::instruction::{Block, InstrKind, Instruction};
-use crate::{Interpreter, JkError, Rename};
+use crate::{CheckedType, Interpreter, JkErrSpaceLocation, JkError, Rename, TypeCheck, TypeCtx};
 
 #[derive(Clone)]
 pub struct IfElse {
     condition: Box<dyn Instruction>,
     if_body: Block,
     else_body: Option<Block>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl IfElse {
@@ -36,6 +37,7 @@ impl IfElse {
             condition,
             if_body,
             else_body,
+            location: None,
         }
     }
 }
@@ -47,6 +49,14 @@ impl Instruction for IfElse {
         self.if_body.kind()
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         let base = format!("if {} {}", self.condition.print(), self.if_body.print());
 
@@ -88,6 +98,53 @@ impl Rename for IfElse {
     }
 }
 
+impl TypeCheck for IfElse {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let cond_ty = self.condition.resolve_type(ctx);
+        if !cond_ty.compatible(&CheckedType::resolved("bool")) {
+            ctx.error(
+                format!(
+                    "condition is of type `{}` instead of `bool`: {}",
+                    cond_ty,
+                    self.condition.print()
+                ),
+                self.condition.location(),
+                self.print(),
+            );
+        }
+
+        let if_ty = self.if_body.resolve_type(ctx);
+
+        match &self.else_body {
+            Some(else_body) => {
+                let else_ty = else_body.resolve_type(ctx);
+
+                // If one of the branches never completes, the other one gives its type
+                // to the condition
+                if if_ty == CheckedType::Never {
+                    return else_ty;
+                }
+
+                if !if_ty.compatible(&else_ty) {
+                    ctx.error(
+                        format!(
+                            "incompatible types for if and else blocks: `{}` and `{}`",
+                            if_ty, else_ty
+                        ),
+                        self.location(),
+                        self.print(),
+                    );
+                    return CheckedType::Unknown;
+                }
+
+                if_ty
+            }
+            // Without an else block, nothing can be returned if the condition is false
+            None => CheckedType::Void,
+        }
+    }
+}
+
 // FIXME: Add printing tests for if else
 #[cfg(test)]
 mod tests {}
@@ -1,9 +1,18 @@
 //! This module is used to parse external code and make it available to other source
 //! files.
+//!
+//! Sources are looked for relatively to the includer first, and then in each of the
+//! library roots: the directories listed in the `JINKO_PATH` environment variable,
+//! jinko's installation directory, and finally the prelude. The prelude contains
+//! libraries written in jinko which are embedded in the interpreter, such as `std`.
 
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
 
-use crate::{parser::Construct, InstrKind, Instruction, Interpreter, JkErrKind, JkError, Rename};
+use crate::{
+    error::Source, parser::Parser, CheckedType, InstrKind, Instruction, Interpreter, JkErrKind,
+    JkErrSpaceLocation, JkError, Rename, TypeCheck, TypeCtx,
+};
 
 /// An `Incl` is constituted of a path, an optional alias and contains an interpreter.
 /// The interpreter is built from parsing the source file in the path.
@@ -12,14 +21,32 @@ use crate::{parser::Construct, InstrKind, Instruction, Interpreter, JkErrKind, J
 pub struct Incl {
     path: String,
     alias: Option<String>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 /// Default file that gets included when including a directory in jinko source code
 const DEFAULT_INCL: &str = "/lib.jk";
 
+/// Environment variable containing additional directories to include sources from
+const JINKO_PATH: &str = "JINKO_PATH";
+
+/// Directory in which jinko's libraries are installed, unless specified otherwise when
+/// building the interpreter
+const DEFAULT_INSTALL_DIR: &str = "/usr/local/lib/jinko";
+
+/// Root of the libraries embedded in the interpreter. It does not exist on disk
+const PRELUDE_ROOT: &str = "<prelude>";
+
+/// Sources of the libraries embedded in the interpreter, relative to the prelude's root
+const PRELUDE: [(&str, &str); 1] = [("std/lib.jk", include_str!("../../std/lib.jk"))];
+
 impl Incl {
     pub fn new(path: String, alias: Option<String>) -> Incl {
-        Incl { path, alias }
+        Incl {
+            path,
+            alias,
+            location: None,
+        }
     }
 
     fn format_candidates(&self, base: &Path) -> (PathBuf, PathBuf) {
@@ -38,10 +65,31 @@ impl Incl {
         (PathBuf::from(dir_fmt), PathBuf::from(file_fmt))
     }
 
-    fn find_include_path(&self, base: &Path) -> Result<PathBuf, JkError> {
+    /// Get the source of a library embedded in the interpreter, if the path designates
+    /// one
+    fn prelude_source(path: &Path) -> Option<&'static str> {
+        let path = path.strip_prefix(PRELUDE_ROOT).ok()?;
+
+        PRELUDE
+            .iter()
+            .find(|(prelude_path, _)| Path::new(prelude_path) == path)
+            .map(|(_, source)| *source)
+    }
+
+    /// Check if a candidate exists, either on disk or in the prelude
+    fn is_source(candidate: &Path) -> bool {
+        candidate.is_file() || Incl::prelude_source(candidate).is_some()
+    }
+
+    /// Find the source designated by self.path in a directory. Returns `None` if there
+    /// are no candidates in that directory
+    fn find_include_path(&self, base: &Path) -> Result<Option<PathBuf>, JkError> {
         let (dir_candidate, file_candidate) = self.format_candidates(base);
 
-        let (dir_valid, file_valid) = (dir_candidate.is_file(), file_candidate.is_file());
+        let (dir_valid, file_valid) = (
+            Incl::is_source(&dir_candidate),
+            Incl::is_source(&file_candidate),
+        );
 
         match (dir_valid, file_valid) {
             // We cannot have both <path>/lib.jk and <path>.jk be valid files
@@ -51,73 +99,95 @@ impl Incl {
                     "invalid include: {:?} and {:?} are both valid candidates",
                     dir_candidate, file_candidate
                 ),
-                None,
-                self.print(),
-            )),
-            (false, false) => Err(JkError::new(
-                JkErrKind::Interpreter,
-                format!(
-                    "no candidate for include: {:?} and {:?} do not exist",
-                    dir_candidate, file_candidate
-                ),
-                None,
+                self.location.clone(),
                 self.print(),
             )),
-            (false, true) => Ok(file_candidate),
-            (true, false) => Ok(dir_candidate),
+            (false, false) => Ok(None),
+            (false, true) => Ok(Some(file_candidate)),
+            (true, false) => Ok(Some(dir_candidate)),
         }
     }
 
-    /// Parse the code and load it in the Incl's interpreter
-    fn inner_load(
-        &self,
-        base: &Path,
-        i: &Interpreter,
-    ) -> Result<(PathBuf, Vec<Box<dyn Instruction>>), JkError> {
-        let formatted = self.find_include_path(base)?;
-
-        // If a source has already been included, skip it without returning
-        // an error
-        if i.is_included(&formatted) {
-            return Ok((formatted, vec![]));
-        }
-
-        i.debug("FINAL PATH", &format!("{:?}", formatted));
-
-        let input = std::fs::read_to_string(&formatted)?;
+    /// Parse the code located at the given path, returning all the errors it contains
+    fn parse_source(&self, path: &Path) -> Result<Vec<Box<dyn Instruction>>, Vec<JkError>> {
+        let input = match Incl::prelude_source(path) {
+            Some(source) => source.to_owned(),
+            None => std::fs::read_to_string(path).map_err(|e| {
+                vec![JkError::from(e)
+                    .locate(self.location())
+                    .with_note(format!("while reading {}", path.display()))]
+            })?,
+        };
+        let source = Source::new(input, Some(path.to_path_buf()));
 
         // We can't just parse the input, since it adds the instructions
         // to an entry block in order to execute them. What we can do, is
         // parse many instructions and add them to an empty interpreter
-        let (_, instructions) = Construct::many_instructions(input.as_str())?;
-
-        Ok((formatted, instructions))
+        Parser::with_source(source, Parser::instructions)
     }
 
-    /// Try to load code from the current path where the executable has been launched
-    fn load_relative(
-        &self,
-        base: &Path,
-        i: &Interpreter,
-    ) -> Result<(PathBuf, Vec<Box<dyn Instruction>>), JkError> {
-        self.inner_load(base, i)
+    /// Directory in which jinko's libraries are installed
+    fn install_dir() -> PathBuf {
+        PathBuf::from(option_env!("JINKO_INSTALL_DIR").unwrap_or(DEFAULT_INSTALL_DIR))
     }
 
-    /// Try to load code from jinko's installation path
-    fn _load_jinko_path(&self) -> Result<Vec<Box<dyn Instruction>>, JkError> {
-        todo!()
+    /// Directories to include sources from when they cannot be found relatively to the
+    /// includer, in order of priority
+    fn library_roots(jinko_path: Option<OsString>) -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = jinko_path
+            .map(|paths| std::env::split_paths(&paths).collect())
+            .unwrap_or_default();
+
+        roots.push(Incl::install_dir());
+        roots.push(PathBuf::from(PRELUDE_ROOT));
+
+        roots
     }
 
-    /// Load the source code located at self.path
+    /// Find the path of the source code designated by self.path
     ///
-    /// There are two ways to look for a source file: First in the includer's path, and
-    /// if not available in jinko's installation directory.
-    fn load(
-        &self,
-        base: &Path,
-        i: &Interpreter,
-    ) -> Result<(PathBuf, Vec<Box<dyn Instruction>>), JkError> {
-        self.load_relative(base, i)
+    /// Sources are first looked for in the includer's directory, and if not available
+    /// in each of the library roots.
+    fn find(&self, base: &Path) -> Result<PathBuf, JkError> {
+        let roots = Incl::library_roots(std::env::var_os(JINKO_PATH));
+
+        for dir in std::iter::once(base).chain(roots.iter().map(PathBuf::as_path)) {
+            if let Some(path) = self.find_include_path(dir)? {
+                return Ok(path);
+            }
+        }
+
+        Err(JkError::new(
+            JkErrKind::Interpreter,
+            format!(
+                "no candidate for include: `{}` not found in {}",
+                self.path,
+                std::iter::once(base)
+                    .chain(roots.iter().map(PathBuf::as_path))
+                    .map(|dir| format!("{:?}", dir))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            self.location.clone(),
+            self.print(),
+        )
+        .with_help(format!(
+            "add the directory containing it to the `{}` environment variable",
+            JINKO_PATH
+        )))
+    }
+
+    /// Get the directory to include sources from, based on the includer's path
+    fn base_dir(includer: Option<&PathBuf>) -> PathBuf {
+        match includer {
+            // Get the parent directory of the includer's source file. We can unwrap
+            // since there's always a base
+            Some(path) => path.parent().unwrap().to_path_buf(),
+            // There is no associated source file. Therefore, we load from where the
+            // interpreter was started. This is the case if we're in dynamic mode for
+            // example
+            None => PathBuf::new(),
+        }
     }
 
     /// Format the correct prefix to include content as. This depends on the presence
@@ -143,6 +213,14 @@ impl Instruction for Incl {
         InstrKind::Statement
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         use std::ffi::OsStr;
 
@@ -161,23 +239,29 @@ impl Instruction for Incl {
     fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
         interpreter.debug("INCL ENTER", &format!("{}", self.print()));
 
-        let base = match interpreter.path() {
-            // Get the parent directory of the interpreter's source file. We can unwrap
-            // since there's always a base
-            Some(path) => path.parent().unwrap(),
-            // The interpreter doesn't have an associated source file. Therefore, we
-            // load from where the interpreter was started. This is the case if we're
-            // in dynamic mode for example
-            None => Path::new(""),
-        };
+        let base = Incl::base_dir(interpreter.path());
 
         let prefix = self.format_prefix()?;
 
         interpreter.debug("BASE DIR", &format!("{:#?}", base));
 
-        let old_path = interpreter.path().cloned();
+        let new_path = self.find(&base)?;
+
+        // If a source has already been included, skip it without returning
+        // an error
+        if interpreter.is_included(&new_path) {
+            return Ok(InstrKind::Statement);
+        }
 
-        let (new_path, mut content) = self.load(base, interpreter)?;
+        interpreter.debug("FINAL PATH", &format!("{:?}", new_path));
+
+        // All the errors have already been reported by the typechecker, so only
+        // return the first one
+        let mut content = self
+            .parse_source(&new_path)
+            .map_err(|mut errors| errors.remove(0))?;
+
+        let old_path = interpreter.path().cloned();
 
         // Temporarily change the path of the interpreter
         interpreter.set_path(Some(new_path));
@@ -210,3 +294,82 @@ impl Rename for Incl {
         self.alias = Some(format!("{}{}", prefix, alias))
     }
 }
+
+impl TypeCheck for Incl {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let base = Incl::base_dir(ctx.path());
+
+        let (prefix, new_path) = match self
+            .format_prefix()
+            .and_then(|p| Ok((p, self.find(&base)?)))
+        {
+            Ok(res) => res,
+            Err(e) => {
+                ctx.report(e);
+                return CheckedType::Void;
+            }
+        };
+
+        if ctx.is_included(&new_path) {
+            return CheckedType::Void;
+        }
+
+        let mut content = match self.parse_source(&new_path) {
+            Ok(content) => content,
+            Err(errors) => {
+                errors.into_iter().for_each(|e| ctx.report(e));
+                return CheckedType::Void;
+            }
+        };
+
+        let old_path = ctx.path().cloned();
+        ctx.set_path(Some(new_path));
+
+        content.iter_mut().for_each(|instr| {
+            instr.prefix(&prefix);
+            instr.hoist(ctx);
+        });
+        content.iter().for_each(|instr| {
+            instr.resolve_type(ctx);
+        });
+
+        ctx.set_path(old_path);
+
+        CheckedType::Void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_prelude_source() {
+        assert!(Incl::prelude_source(Path::new("<prelude>/std/lib.jk")).is_some());
+        assert!(Incl::prelude_source(Path::new("std/lib.jk")).is_none());
+        assert!(Incl::prelude_source(Path::new("<prelude>/nothing.jk")).is_none());
+    }
+
+    #[test]
+    fn t_library_roots() {
+        let roots = Incl::library_roots(Some(OsString::from("/a:/b")));
+
+        assert_eq!(roots.len(), 4);
+        assert_eq!(roots[0], PathBuf::from("/a"));
+        assert_eq!(roots[1], PathBuf::from("/b"));
+        assert_eq!(roots[3], PathBuf::from(PRELUDE_ROOT));
+    }
+
+    #[test]
+    fn t_find_prelude() {
+        let incl = Incl::new("std".to_owned(), None);
+
+        assert_eq!(
+            incl.find(Path::new("tests")).unwrap(),
+            PathBuf::from("<prelude>/std/lib.jk")
+        );
+        assert!(Incl::new("nothing".to_owned(), None)
+            .find(Path::new("tests"))
+            .is_err());
+    }
+}
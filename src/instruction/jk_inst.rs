@@ -4,7 +4,9 @@
 //! module. They are executed at "compile" time, when running through the code first.
 
 use crate::instruction::{InstrKind, Instruction};
-use crate::{Interpreter, JkErrKind, JkError, Rename};
+use crate::{
+    CheckedType, Interpreter, JkErrKind, JkErrSpaceLocation, JkError, Rename, TypeCheck, TypeCtx,
+};
 
 /// The potential interpreter instructions
 #[derive(Clone, Debug, PartialEq)]
@@ -35,6 +37,14 @@ impl Instruction for JkInst {
         InstrKind::Statement
     }
 
+    // Interpreter directives are only used for debugging and cannot fail, so there is
+    // no need to keep track of their location
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        None
+    }
+
+    fn set_location(&mut self, _location: Option<JkErrSpaceLocation>) {}
+
     fn print(&self) -> String {
         match self {
             JkInst::Dump => "@dump",
@@ -60,3 +70,9 @@ impl Instruction for JkInst {
 impl Rename for JkInst {
     fn prefix(&mut self, _: &str) {}
 }
+
+impl TypeCheck for JkInst {
+    fn resolve_type(&self, _ctx: &mut TypeCtx) -> CheckedType {
+        CheckedType::Void
+    }
+}
@@ -1,15 +1,23 @@
 //! The Loop instruction is used for repeating instructions. They can be of three
-//! different kinds, `for`, `while` or `loop`.
+//! different kinds, `for`, `while` or `loop`. A loop declaring a return type is an
+//! expression: Its value is the one yielded by its last iteration, or the one given to
+//! `break`.
+//!
+//! `x = for i in range(0, n) -> int { i }`
 
-use crate::instruction::{Block, InstrKind, Instruction, Var};
-use crate::{Interpreter, JkErrKind, JkError, Rename};
+use crate::instance::InstanceIter;
+use crate::instruction::{Block, GenericMap, InstrKind, Instruction, TypeId, Var};
+use crate::{
+    CheckedType, ControlFlow, Interpreter, JkErrKind, JkErrSpaceLocation, JkError, ObjectInstance,
+    Rename, TypeCheck, TypeCtx,
+};
 
 /// What kind of loop the loop block represents: Either a for Loop, with a variable and
 /// a range expression, a while loop with just an upper bound, or a loop with no bound
 /// at all
 #[derive(Clone)]
 pub enum LoopKind {
-    For(Var, Box<dyn Instruction>),
+    For(Box<Var>, Box<dyn Instruction>),
     While(Box<dyn Instruction>),
     Loop,
 }
@@ -20,94 +28,270 @@ pub enum LoopKind {
 pub struct Loop {
     kind: LoopKind,
     block: Block,
+    ty: Option<TypeId>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl Loop {
     pub fn new(kind: LoopKind, block: Block) -> Loop {
-        Loop { kind, block }
+        Loop {
+            kind,
+            block,
+            ty: None,
+            location: None,
+        }
+    }
+
+    /// Type of the value yielded by the loop, if it yields one
+    pub fn ty(&self) -> Option<&TypeId> {
+        self.ty.as_ref()
+    }
+
+    /// Set the type of the value yielded by the loop
+    pub fn set_ty(&mut self, ty: Option<TypeId>) {
+        self.ty = ty
+    }
+
+    /// Execute the loop's block once. Returns `false` if the loop was broken out of.
+    /// The value of the block, or the one given to `break`, is kept as the last value
+    /// of the loop
+    fn iteration(
+        &self,
+        interpreter: &mut Interpreter,
+        last: &mut Option<ObjectInstance>,
+    ) -> Result<bool, JkError> {
+        let error = match self.block.execute(interpreter) {
+            Ok(InstrKind::Expression(Some(value))) => {
+                *last = Some(value);
+                return Ok(true);
+            }
+            Ok(_) => return Ok(true),
+            Err(e) => e,
+        };
+
+        // Returns are handled by the enclosing function call
+        match error.signal() {
+            Some(ControlFlow::Continue) | Some(ControlFlow::Break(_)) => {}
+            _ => return Err(error),
+        }
+
+        match error.into_signal() {
+            Some(ControlFlow::Break(value)) => {
+                if value.is_some() {
+                    *last = value;
+                }
+
+                Ok(false)
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// Typecheck the block of the loop. The values it yields must be of the type
+    /// declared by the loop, if any. Returns whether the block contains a `break`
+    /// leaving the loop
+    fn check_block(&self, ctx: &mut TypeCtx, loop_ty: &CheckedType) -> bool {
+        ctx.loop_enter(loop_ty.clone());
+        let block_ty = self.block.resolve_type(ctx);
+        let broken = ctx.loop_exit();
+
+        if *loop_ty != CheckedType::Void
+            && block_ty != CheckedType::Void
+            && !block_ty.compatible(loop_ty)
+        {
+            ctx.error(
+                format!(
+                    "loop should yield `{}` but its block yields `{}`",
+                    loop_ty, block_ty
+                ),
+                self.block.location(),
+                self.print(),
+            );
+        }
+
+        broken
+    }
+
+    /// Produce the value of the loop once it is done executing
+    fn result(&self, last: Option<ObjectInstance>) -> Result<InstrKind, JkError> {
+        match (&self.ty, last) {
+            (None, _) => Ok(InstrKind::Statement),
+            (Some(_), Some(value)) => Ok(InstrKind::Expression(Some(value))),
+            (Some(ty), None) => Err(JkError::new(
+                JkErrKind::Interpreter,
+                format!("loop did not yield any value of type `{}`", ty.id()),
+                self.location.clone(),
+                self.print(),
+            )),
+        }
+    }
+
+    /// Execute the range expression of a `for` loop and create an iterator over it
+    fn iterate(
+        &self,
+        range: &dyn Instruction,
+        interpreter: &mut Interpreter,
+    ) -> Result<InstanceIter, JkError> {
+        let instance = range.execute_expression(interpreter)?;
+
+        InstanceIter::new(&instance, interpreter).ok_or_else(|| {
+            let ty = instance.ty().map_or("{unknown}", |ty| ty.name());
+
+            JkError::new(
+                JkErrKind::Interpreter,
+                format!("cannot iterate over value of type `{}`", ty),
+                range.location().cloned(),
+                range.print(),
+            )
+        })
+    }
+
+    /// Get the type yielded when iterating over a custom type. Its `next` function must
+    /// return an optional item from a value of that type, and its `advance` function
+    /// the value following it
+    fn next_item_type(ty: &TypeId, ctx: &TypeCtx) -> Result<TypeId, String> {
+        let next = InstanceIter::next_function(ty);
+        let expected = format!("func {}(it: {}) -> Option<Item>", next, ty.id());
+
+        let item = match Loop::iter_function_type(ty, &next, &expected, ctx)? {
+            Some(option) if option.base() == "Option" && option.generics().len() == 1 => {
+                option.generics()[0].clone()
+            }
+            _ => return Err(Loop::invalid_iter_function(&next, &expected, ctx)),
+        };
+
+        let advance = InstanceIter::advance_function(ty);
+        let expected = format!("func {}(it: {}) -> {}", advance, ty.id(), ty.id());
+
+        match Loop::iter_function_type(ty, &advance, &expected, ctx)? {
+            Some(next_ty) if &next_ty == ty => Ok(item),
+            _ => Err(Loop::invalid_iter_function(&advance, &expected, ctx)),
+        }
+    }
+
+    /// Get the return type of a function used to iterate over a custom type, once its
+    /// generics are inferred from the type. The function must take a single value of
+    /// that type
+    fn iter_function_type(
+        ty: &TypeId,
+        name: &str,
+        expected: &str,
+        ctx: &TypeCtx,
+    ) -> Result<Option<TypeId>, String> {
+        let function = match ctx.get_function(name) {
+            Some(function) => function,
+            None => return Err(format!("declare `{}` to iterate over it", expected)),
+        };
+
+        let mut generics = GenericMap::new();
+        match function.args().as_slice() {
+            [arg] => {
+                arg.get_type().infer(ty, function.generics(), &mut generics);
+
+                match &arg.get_type().substitute(&generics) == ty {
+                    true => Ok(function.ty().map(|ty| ty.substitute(&generics))),
+                    false => Err(Loop::invalid_iter_function(name, expected, ctx)),
+                }
+            }
+            _ => Err(Loop::invalid_iter_function(name, expected, ctx)),
+        }
+    }
+
+    fn invalid_iter_function(name: &str, expected: &str, ctx: &TypeCtx) -> String {
+        let signature = ctx
+            .get_function(name)
+            .map_or_else(String::new, |function| function.signature());
+
+        format!(
+            "`{}` is declared as `{}` instead of `{}`",
+            name, signature, expected
+        )
     }
 }
 
 impl Instruction for Loop {
     fn kind(&self) -> InstrKind {
-        self.block.kind()
+        match self.ty {
+            Some(_) => InstrKind::Expression(None),
+            None => InstrKind::Statement,
+        }
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
     }
 
     fn print(&self) -> String {
+        let ty = match &self.ty {
+            Some(ty) => format!(" -> {}", ty.id()),
+            None => String::new(),
+        };
+
         match &self.kind {
             LoopKind::For(var, range) => format!(
-                "for {} in {} {}\n",
+                "for {} in {}{} {}\n",
                 var.name(),
                 range.print(),
+                ty,
                 self.block.print()
             ),
             LoopKind::While(condition) => {
-                format!("while {} {}\n", condition.print(), self.block.print())
+                format!("while {}{} {}\n", condition.print(), ty, self.block.print())
             }
-            LoopKind::Loop => format!("loop {}\n", self.block.print()),
+            LoopKind::Loop => format!("loop{} {}\n", ty, self.block.print()),
         }
     }
 
     fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        let mut last = None;
+
         match &self.kind {
-            LoopKind::Loop => loop {
+            LoopKind::Loop => {
                 interpreter.debug_step("LOOP ENTER");
-                self.block.execute(interpreter)?;
+                while self.iteration(interpreter, &mut last)? {}
                 interpreter.debug_step("LOOP EXIT");
-            },
+            }
             LoopKind::While(cond) => {
                 interpreter.debug_step("WHILE ENTER");
-                while cond.as_bool(interpreter)? {
-                    self.block.execute(interpreter)?;
-                }
+                while cond.as_bool(interpreter)? && self.iteration(interpreter, &mut last)? {}
                 interpreter.debug_step("WHILE EXIT");
             }
-            LoopKind::For(_var, _range) => {
-                // FIXME:
-                //
-                // In order to have a correct for implementation, jinko needs ranges,
-                // iterators, which implies the need for options, which implies the need
-                // for a standard library, which implies the need for code inclusion, etc
-                // etc etc... All of that is the goal of the 0.1.1 release.
-                //
-                // A possible implementation would be to execute the range, assign it
-                // to the var, and check that var is a valid iterator. For example,
-                // by calling `Iter::ok(var)` (whatever the API might be) and checking
-                // that that result, as a boolean, returns true. If it does, execute the
-                // body. If it does not, break from the for.
-
-                return Err(JkError::new(
-                    JkErrKind::Interpreter,
-                    format!("for loops are currently unimplemented"),
-                    None,
-                    self.print(),
-                ));
-
-                // FIXME: Rework that code
-                // interpreter.debug_step("FOR ENTER");
-                // let var_name = var.name().to_owned();
-                // interpreter.scope_enter();
-
-                // interpreter.add_variable(var.clone())?;
-
-                // loop {
-                //     range.execute(interpreter)?;
-
-                //     // We can unwrap since we added the variable right before
-                //     if !interpreter.get_variable(&var_name).unwrap().as_bool() {
-                //         break;
-                //     }
-
-                //     self.block.execute(interpreter)?;
-                // }
-
-                // interpreter.scope_exit();
-                // interpreter.debug_step("FOR EXIT");
+            LoopKind::For(var, range) => {
+                interpreter.debug_step("FOR ENTER");
+
+                let mut iter = self.iterate(range.as_ref(), interpreter)?;
+
+                while let Some(item) = iter
+                    .next(interpreter)
+                    .map_err(|e| e.locate(range.location()))?
+                {
+                    // The loop variable lives in its own scope, which is recreated on
+                    // each iteration
+                    interpreter.scope_enter();
+
+                    let mut item_var = Var::new(var.name().to_owned());
+                    item_var.set_instance(item);
+
+                    let result = interpreter
+                        .add_variable(item_var)
+                        .and_then(|_| self.iteration(interpreter, &mut last));
+
+                    interpreter.scope_exit();
+
+                    if !result? {
+                        break;
+                    }
+                }
+
+                interpreter.debug_step("FOR EXIT");
             }
         }
 
-        // FIXME: Add logic. Right now they only return on error, not the actual value
-        Ok(InstrKind::Statement)
+        self.result(last)
     }
 }
 
@@ -126,10 +310,82 @@ impl Rename for Loop {
     }
 }
 
+impl TypeCheck for Loop {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let loop_ty = ctx.resolve(self.ty(), self.location());
+
+        match &self.kind {
+            LoopKind::While(cond) => {
+                let cond_ty = cond.resolve_type(ctx);
+                if !cond_ty.compatible(&CheckedType::resolved("bool")) {
+                    ctx.error(
+                        format!(
+                            "condition is of type `{}` instead of `bool`: {}",
+                            cond_ty,
+                            cond.print()
+                        ),
+                        cond.location(),
+                        self.print(),
+                    );
+                }
+
+                self.check_block(ctx, &loop_ty);
+            }
+            LoopKind::For(var, range) => {
+                let item_ty = match range.resolve_type(ctx) {
+                    CheckedType::Unknown => CheckedType::Unknown,
+                    range_ty => {
+                        let item = match &range_ty {
+                            CheckedType::Resolved(ty) => match InstanceIter::item_type(ty) {
+                                Some(item) => Ok(item),
+                                None => Loop::next_item_type(ty, ctx).map_err(Some),
+                            },
+                            _ => Err(None),
+                        };
+
+                        match item {
+                            Ok(item) => CheckedType::Resolved(item),
+                            Err(help) => {
+                                let error = JkError::new(
+                                    JkErrKind::TypeChecker,
+                                    format!("cannot iterate over value of type `{}`", range_ty),
+                                    range.location().cloned(),
+                                    range.print(),
+                                );
+
+                                ctx.report(match help {
+                                    Some(help) => error.with_help(help),
+                                    None => error,
+                                });
+                                CheckedType::Unknown
+                            }
+                        }
+                    }
+                };
+
+                ctx.scope_enter();
+                ctx.declare_var(var.name(), item_ty);
+                self.check_block(ctx, &loop_ty);
+                ctx.scope_exit();
+            }
+            LoopKind::Loop => {
+                // A loop which cannot be left with `break` never terminates
+                if !self.check_block(ctx, &loop_ty) {
+                    return CheckedType::Never;
+                }
+            }
+        }
+
+        loop_ty
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::instruction::FunctionCall;
+    use crate::utils::execute;
+    use crate::{JkChar, JkInt, ToObjectInstance};
 
     #[test]
     fn pretty_print_loop() {
@@ -143,11 +399,19 @@ mod tests {
     fn pretty_print_for() {
         let r = Box::new(FunctionCall::new("iter".to_owned()));
         let b = Block::new();
-        let l = Loop::new(LoopKind::For(Var::new("i".to_owned()), r), b);
+        let l = Loop::new(LoopKind::For(Box::new(Var::new("i".to_owned())), r), b);
 
         assert_eq!(l.print().as_str(), "for i in iter() {\n}\n")
     }
 
+    #[test]
+    fn pretty_print_typed_loop() {
+        let mut l = Loop::new(LoopKind::Loop, Block::new());
+        l.set_ty(Some(TypeId::from("int")));
+
+        assert_eq!(l.print().as_str(), "loop -> int {\n}\n")
+    }
+
     #[test]
     fn pretty_print_while() {
         let r = Box::new(Block::new());
@@ -156,4 +420,86 @@ mod tests {
 
         assert_eq!(l.print().as_str(), "while {\n} {\n}\n")
     }
+
+    #[test]
+    fn t_for_range() {
+        assert_eq!(
+            execute("{ mut s = 0; for i in range(0, 5) { s = s + i; }; s }").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(10).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_for_string() {
+        assert_eq!(
+            execute("{ mut last = 'a'; for c in \"jinko\" { last = c; }; last }").unwrap(),
+            InstrKind::Expression(Some(JkChar::from('o').to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_for_variable_scope() {
+        assert!(execute("{ for i in range(0, 2) { x = i; }; i }").is_err());
+        assert!(execute("{ for i in range(0, 2) { x = i; }; x }").is_err());
+    }
+
+    #[test]
+    fn t_for_not_iterable() {
+        assert!(execute("{ for i in 12 { } }").is_err());
+    }
+
+    #[test]
+    fn t_untyped_loop_is_statement() {
+        assert_eq!(
+            execute("{ for i in range(0, 3) { i }; }").unwrap(),
+            InstrKind::Statement
+        );
+    }
+
+    #[test]
+    fn t_typed_loop_yields_last_value() {
+        assert_eq!(
+            execute("{ for i in range(0, 3) -> int { i } }").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(2).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_typed_loop_without_value() {
+        assert!(execute("{ for i in range(0, 0) -> int { i } }").is_err());
+    }
+
+    #[test]
+    fn t_break_with_value() {
+        assert_eq!(
+            execute("{ mut i = 0; loop -> int { i = i + 4; break i * 10; } }").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(40).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_break_without_value() {
+        assert_eq!(
+            execute("{ mut i = 0; while true { i = i + 1; break; i = 12; }; i }").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(1).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_continue() {
+        assert_eq!(
+            execute("{ mut s = 0; for i in range(0, 5) { s = s + i; continue; s = 0; }; s }")
+                .unwrap(),
+            InstrKind::Expression(Some(JkInt::from(10).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_break_inner_loop_only() {
+        assert_eq!(
+            execute("{ mut s = 0; for i in range(0, 3) { loop { break; }; s = s + 1; }; s }")
+                .unwrap(),
+            InstrKind::Expression(Some(JkInt::from(3).to_instance()))
+        );
+    }
 }
@@ -0,0 +1,164 @@
+//! Loop control instructions are used to leave the current iteration of a loop early.
+//! `continue` skips to the next iteration, while `break` exits the loop entirely. A
+//! `break` can carry a value, which then becomes the value of the loop.
+//!
+//! `x = loop -> int { break 12; }`
+
+use crate::{
+    CheckedType, ControlFlow, InstrKind, Instruction, Interpreter, JkErrSpaceLocation, JkError,
+    Rename, TypeCheck, TypeCtx,
+};
+
+/// Which loop control instruction is used
+#[derive(Clone)]
+pub enum LoopControlKind {
+    Break(Option<Box<dyn Instruction>>),
+    Continue,
+}
+
+#[derive(Clone)]
+pub struct LoopControl {
+    kind: LoopControlKind,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl LoopControl {
+    /// Create a new loop control instruction
+    pub fn new(kind: LoopControlKind) -> LoopControl {
+        LoopControl {
+            kind,
+            location: None,
+        }
+    }
+}
+
+impl Instruction for LoopControl {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Statement
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        match &self.kind {
+            LoopControlKind::Break(Some(value)) => format!("break {}", value.print()),
+            LoopControlKind::Break(None) => String::from("break"),
+            LoopControlKind::Continue => String::from("continue"),
+        }
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("LOOP CONTROL", &self.print());
+
+        let signal = match &self.kind {
+            LoopControlKind::Break(Some(value)) => {
+                ControlFlow::Break(Some(value.execute_expression(interpreter)?))
+            }
+            LoopControlKind::Break(None) => ControlFlow::Break(None),
+            LoopControlKind::Continue => ControlFlow::Continue,
+        };
+
+        Err(JkError::from_signal(
+            signal,
+            self.location.clone(),
+            self.print(),
+        ))
+    }
+}
+
+impl Rename for LoopControl {
+    fn prefix(&mut self, prefix: &str) {
+        if let LoopControlKind::Break(Some(value)) = &mut self.kind {
+            value.prefix(prefix)
+        }
+    }
+}
+
+impl TypeCheck for LoopControl {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let value_ty = match &self.kind {
+            LoopControlKind::Break(Some(value)) => Some(value.resolve_type(ctx)),
+            _ => None,
+        };
+
+        let loop_ty = match ctx.current_loop() {
+            Some(ty) => ty.clone(),
+            None => {
+                let keyword = match self.kind {
+                    LoopControlKind::Break(_) => "break",
+                    LoopControlKind::Continue => "continue",
+                };
+
+                ctx.error(
+                    format!("`{}` outside of a loop", keyword),
+                    self.location(),
+                    self.print(),
+                );
+                return CheckedType::Void;
+            }
+        };
+
+        match value_ty {
+            Some(_) if loop_ty == CheckedType::Void => ctx.error(
+                String::from("cannot break with a value out of a loop without a return type"),
+                self.location(),
+                self.print(),
+            ),
+            Some(value_ty) if !value_ty.compatible(&loop_ty) => ctx.error(
+                format!(
+                    "loop should yield `{}` but `break` yields `{}`",
+                    loop_ty, value_ty
+                ),
+                self.location(),
+                self.print(),
+            ),
+            _ => {}
+        }
+
+        if let LoopControlKind::Break(_) = self.kind {
+            ctx.loop_break();
+        }
+
+        // Code following a loop control instruction is never executed
+        CheckedType::Never
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JkInt;
+
+    #[test]
+    fn t_print() {
+        let with_value = LoopControl::new(LoopControlKind::Break(Some(Box::new(JkInt::from(12)))));
+
+        assert_eq!(with_value.print(), "break 12");
+        assert_eq!(
+            LoopControl::new(LoopControlKind::Break(None)).print(),
+            "break"
+        );
+        assert_eq!(
+            LoopControl::new(LoopControlKind::Continue).print(),
+            "continue"
+        );
+    }
+
+    #[test]
+    fn t_signal_outside_of_loop() {
+        let mut interpreter = Interpreter::new();
+        let err = LoopControl::new(LoopControlKind::Continue)
+            .execute(&mut interpreter)
+            .err()
+            .unwrap();
+
+        assert_eq!(err.signal(), Some(&ControlFlow::Continue));
+        assert_eq!(err.msg(), "`continue` outside of a loop");
+    }
+}
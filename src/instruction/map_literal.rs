@@ -0,0 +1,261 @@
+//! MapLiterals are used to create a new map out of a list of entries:
+//! `{ "one": 1, "two": 2 }`. All the keys of a map must be of the same type, and so
+//! must all of its values. Only some primitive types can be used as keys. Since the
+//! types of an empty literal cannot be known, empty maps are created using
+//! `map::new<K, V>()`.
+
+use crate::instruction::{TypeId, HASHABLE_TYPES, MAP_TYPE};
+use crate::{
+    CheckedType, InstrKind, Instruction, Interpreter, JkErrKind, JkErrSpaceLocation, JkError,
+    ObjectInstance, Rename, TypeCheck, TypeCtx,
+};
+
+/// An entry of a map literal: a key and its associated value
+pub type MapEntry = (Box<dyn Instruction>, Box<dyn Instruction>);
+
+#[derive(Clone)]
+pub struct MapLiteral {
+    entries: Vec<MapEntry>,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl MapLiteral {
+    /// Create a new map literal out of its entries. `entries` cannot be empty
+    pub fn new(entries: Vec<MapEntry>) -> MapLiteral {
+        MapLiteral {
+            entries,
+            location: None,
+        }
+    }
+
+    /// Return a reference to the entries of the literal
+    #[cfg(test)]
+    pub fn entries(&self) -> &Vec<MapEntry> {
+        &self.entries
+    }
+
+    /// Error emitted when the keys are of a type which cannot be used in a map
+    fn unhashable_error(&self, key_ty: &str) -> String {
+        format!(
+            "cannot use `{}` as the keys of a map: keys must be one of {}",
+            key_ty,
+            HASHABLE_TYPES.join(", ")
+        )
+    }
+
+    /// Error emitted when one of the keys or values is not of the same type as the
+    /// first one
+    fn mismatch_error(&self, what: &str, idx: usize, expected: &str, got: &str) -> String {
+        format!(
+            "{} of a map must all be of the same type: expected `{}`, got `{}` for entry {}",
+            what, expected, got, idx
+        )
+    }
+
+    /// Check that all the given instances are of the same type, and return that type
+    fn check_types(&self, what: &str, instances: &[ObjectInstance]) -> Result<TypeId, String> {
+        let ty = instances
+            .first()
+            .and_then(|first| first.ty())
+            .map(|ty| ty.type_id());
+        let ty_name = ty.as_ref().map_or("", |ty| ty.id());
+
+        if let Some((idx, instance)) = instances
+            .iter()
+            .enumerate()
+            .find(|(_, instance)| instance.ty().map(|ty| ty.name()).unwrap_or_default() != ty_name)
+        {
+            return Err(self.mismatch_error(
+                what,
+                idx,
+                ty_name,
+                instance.ty().map(|ty| ty.name()).unwrap_or_default(),
+            ));
+        }
+
+        ty.ok_or_else(|| format!("{} of a map must have a type", what))
+    }
+
+    /// Typecheck the keys or the values of the literal, and return their common type
+    fn resolve_types(
+        &self,
+        what: &str,
+        instructions: Vec<&dyn Instruction>,
+        ctx: &mut TypeCtx,
+    ) -> CheckedType {
+        let types: Vec<CheckedType> = instructions
+            .iter()
+            .map(|instruction| instruction.resolve_type(ctx))
+            .collect();
+
+        // We can index since a map literal always contains at least one entry
+        let expected = types[0].clone();
+
+        for (idx, (instruction, ty)) in instructions.iter().zip(types.iter()).enumerate() {
+            if !ty.compatible(&expected) {
+                ctx.error(
+                    self.mismatch_error(what, idx, &expected.to_string(), &ty.to_string()),
+                    instruction.location(),
+                    instruction.print(),
+                );
+            }
+        }
+
+        if matches!(expected, CheckedType::Void) {
+            ctx.error(
+                format!("{} of a map cannot be statements", what),
+                instructions[0].location(),
+                instructions[0].print(),
+            );
+            return CheckedType::Unknown;
+        }
+
+        expected
+    }
+}
+
+impl Instruction for MapLiteral {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Expression(None)
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!(
+            "{{ {} }}",
+            self.entries
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key.print(), value.print()))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("MAP", &self.print());
+
+        let error = |msg| {
+            JkError::new(
+                JkErrKind::Interpreter,
+                msg,
+                self.location.clone(),
+                self.print(),
+            )
+        };
+
+        let mut keys: Vec<ObjectInstance> = Vec::new();
+        let mut values: Vec<ObjectInstance> = Vec::new();
+
+        for (key, value) in self.entries.iter() {
+            keys.push(key.execute_expression(interpreter)?);
+            values.push(value.execute_expression(interpreter)?);
+        }
+
+        let key_ty = self.check_types("keys", &keys).map_err(error)?;
+        let value_ty = self.check_types("values", &values).map_err(error)?;
+
+        if !key_ty.is_hashable() {
+            return Err(error(self.unhashable_error(key_ty.id())));
+        }
+
+        let ty = interpreter
+            .get_type(&TypeId::generic(
+                MAP_TYPE.to_owned(),
+                vec![key_ty, value_ty],
+            ))
+            .map(|ty| (*ty).clone());
+
+        // A key given multiple times keeps its first position and its last value
+        Ok(InstrKind::Expression(Some(ObjectInstance::from_entries(
+            ty,
+            keys.into_iter().zip(values).collect(),
+        ))))
+    }
+}
+
+impl Rename for MapLiteral {
+    fn prefix(&mut self, prefix: &str) {
+        self.entries.iter_mut().for_each(|(key, value)| {
+            key.prefix(prefix);
+            value.prefix(prefix);
+        });
+    }
+}
+
+impl TypeCheck for MapLiteral {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let keys = self.entries.iter().map(|(key, _)| key.as_ref()).collect();
+        let key_ty = self.resolve_types("keys", keys, ctx);
+
+        let values = self
+            .entries
+            .iter()
+            .map(|(_, value)| value.as_ref())
+            .collect();
+        let value_ty = self.resolve_types("values", values, ctx);
+
+        match (key_ty, value_ty) {
+            (CheckedType::Resolved(key_ty), CheckedType::Resolved(value_ty)) => {
+                if !key_ty.is_hashable() {
+                    ctx.error(
+                        self.unhashable_error(key_ty.id()),
+                        self.location(),
+                        self.print(),
+                    );
+                    return CheckedType::Unknown;
+                }
+
+                CheckedType::Resolved(TypeId::generic(MAP_TYPE.to_owned(), vec![key_ty, value_ty]))
+            }
+            _ => CheckedType::Unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::execute;
+    use crate::{JkInt, JkString, ToObjectInstance};
+
+    #[test]
+    fn t_print() {
+        let map = MapLiteral::new(vec![(
+            Box::new(JkString::from("a")),
+            Box::new(JkInt::from(1)),
+        )]);
+
+        assert_eq!(map.print(), "{ \"a\": 1 }");
+    }
+
+    #[test]
+    fn t_map_literal() {
+        let map = match execute("{ 'a': 1, 'b': 2, 'a': 3 }").unwrap() {
+            InstrKind::Expression(Some(map)) => map,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(map.ty().unwrap().name(), "map<char, int>");
+        assert_eq!(map.keys(None).len(), 2);
+        assert_eq!(
+            map.values(None)[0].data(),
+            JkInt::from(3).to_instance().data()
+        );
+    }
+
+    #[test]
+    fn t_map_literal_invalid() {
+        assert_eq!(
+            execute("{ 1: 'a', 2: \"b\" }").unwrap_err().msg(),
+            "values of a map must all be of the same type: expected `char`, got `string` for entry 1"
+        );
+        assert!(execute("{ 1.5: 1 }").is_err());
+    }
+}
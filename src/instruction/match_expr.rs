@@ -0,0 +1,304 @@
+//! `Match`es are used to execute different instructions depending on the variant of a
+//! sum type's instance. Each arm of the match contains a pattern, naming one of the
+//! variants and binding its fields to new variables, or the wildcard `_` which matches
+//! every remaining variant.
+//!
+//! ```
+//! value = match lookup(key) {
+//!     Some(value) => value,
+//!     None => 0,
+//! };
+//! ```
+//!
+//! Matches must be exhaustive: every variant of the type needs to be covered by one
+//! of the arms.
+
+use crate::instruction::{InstrKind, Instruction, Pattern, TypeDec};
+use crate::{
+    CheckedType, Interpreter, JkErrKind, JkErrSpaceLocation, JkError, Rename, TypeCheck, TypeCtx,
+};
+
+/// A pattern and the instruction executed when it matches
+#[derive(Clone)]
+pub struct MatchArm {
+    pattern: Pattern,
+    body: Box<dyn Instruction>,
+}
+
+impl MatchArm {
+    /// Create a new arm, executing `body` when `pattern` matches
+    pub fn new(pattern: Pattern, body: Box<dyn Instruction>) -> MatchArm {
+        MatchArm { pattern, body }
+    }
+
+    fn print(&self) -> String {
+        format!("{} => {}", self.pattern.print(), self.body.print())
+    }
+}
+
+#[derive(Clone)]
+pub struct Match {
+    value: Box<dyn Instruction>,
+    arms: Vec<MatchArm>,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl Match {
+    /// Create a new match on a value
+    pub fn new(value: Box<dyn Instruction>, arms: Vec<MatchArm>) -> Match {
+        Match {
+            value,
+            arms,
+            location: None,
+        }
+    }
+
+    /// Return a reference to the arms of the match
+    #[cfg(test)]
+    pub fn arms(&self) -> &Vec<MatchArm> {
+        &self.arms
+    }
+
+    fn error(&self, msg: String) -> JkError {
+        JkError::new(
+            JkErrKind::Interpreter,
+            msg,
+            self.location.clone(),
+            self.print(),
+        )
+    }
+
+    /// Check that every variant is covered by exactly one arm, and that every arm can
+    /// be reached
+    fn check_exhaustive(&self, type_dec: &TypeDec, ctx: &mut TypeCtx) {
+        let mut covered: Vec<&str> = Vec::new();
+        let mut wildcard = false;
+
+        for arm in self.arms.iter() {
+            let unreachable = match &arm.pattern {
+                Pattern::Variant(name, _) => {
+                    let unreachable = wildcard || covered.contains(&name.as_str());
+                    covered.push(name);
+                    unreachable
+                }
+                Pattern::Wildcard => {
+                    let unreachable = wildcard
+                        || type_dec
+                            .variants()
+                            .iter()
+                            .all(|variant| covered.contains(&variant.name()));
+                    wildcard = true;
+                    unreachable
+                }
+            };
+
+            if unreachable {
+                ctx.error(
+                    format!("unreachable pattern `{}`", arm.pattern.print()),
+                    self.location(),
+                    arm.print(),
+                );
+            }
+        }
+
+        let missing: Vec<String> = type_dec
+            .variants()
+            .iter()
+            .filter(|variant| !wildcard && !covered.contains(&variant.name()))
+            .map(|variant| format!("`{}`", variant.name()))
+            .collect();
+
+        if !missing.is_empty() {
+            ctx.report(
+                JkError::new(
+                    JkErrKind::TypeChecker,
+                    format!(
+                        "non-exhaustive match on type `{}`: {} not covered",
+                        type_dec.name(),
+                        missing.join(", ")
+                    ),
+                    self.location.clone(),
+                    self.print(),
+                )
+                .with_help(String::from(
+                    "add an arm for each missing variant, or a wildcard arm `_`",
+                )),
+            );
+        }
+    }
+}
+
+impl Instruction for Match {
+    fn kind(&self) -> InstrKind {
+        // The typechecker makes sure that all the arms return the same thing
+        match self.arms.first() {
+            Some(arm) => arm.body.kind(),
+            None => InstrKind::Statement,
+        }
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!(
+            "match {} {{ {} }}",
+            self.value.print(),
+            self.arms
+                .iter()
+                .map(|arm| arm.print())
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug_step("MATCH ENTER");
+
+        let instance = self.value.execute_expression(interpreter)?;
+
+        let variant = match instance.variant() {
+            Some(variant) => variant.name(),
+            None => {
+                return Err(self.error(format!(
+                    "cannot match on a value of type `{}`",
+                    instance.ty().map_or("{unknown}", |ty| ty.name())
+                )))
+            }
+        };
+        interpreter.debug("VARIANT", variant);
+
+        let arm = match self.arms.iter().find(|arm| arm.pattern.matches(variant)) {
+            Some(arm) => arm,
+            None => return Err(self.error(format!("no arm matches variant `{}`", variant))),
+        };
+
+        // The variables bound by the arm only live as long as the arm
+        interpreter.scope_enter();
+        let result = arm
+            .pattern
+            .bind(&instance, interpreter, self.location())
+            .and_then(|_| arm.body.execute(interpreter));
+        interpreter.scope_exit();
+
+        interpreter.debug_step("MATCH EXIT");
+
+        result
+    }
+}
+
+impl Rename for Match {
+    fn prefix(&mut self, prefix: &str) {
+        self.value.prefix(prefix);
+        self.arms.iter_mut().for_each(|arm| {
+            arm.pattern.prefix(prefix);
+            arm.body.prefix(prefix);
+        });
+    }
+}
+
+impl TypeCheck for Match {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let value_ty = self.value.resolve_type(ctx);
+
+        let type_dec = match &value_ty {
+            CheckedType::Resolved(ty) => ctx.get_type(ty).filter(|type_dec| type_dec.is_sum()),
+            _ => None,
+        };
+
+        match (&value_ty, &type_dec) {
+            (_, Some(type_dec)) => self.check_exhaustive(type_dec, ctx),
+            (CheckedType::Unknown, _) | (CheckedType::Never, _) => {}
+            (ty, None) => ctx.error(
+                format!(
+                    "cannot match on a value of type `{}`: only sum types can be matched",
+                    ty
+                ),
+                self.value.location(),
+                self.print(),
+            ),
+        }
+
+        let mut match_ty: Option<CheckedType> = None;
+
+        for arm in self.arms.iter() {
+            ctx.scope_enter();
+            arm.pattern
+                .declare_bindings(type_dec.as_deref(), ctx, self.location());
+            let arm_ty = arm.body.resolve_type(ctx);
+            ctx.scope_exit();
+
+            // Arms which never complete do not give their type to the match
+            match &match_ty {
+                _ if arm_ty == CheckedType::Never => {}
+                None => match_ty = Some(arm_ty),
+                Some(expected) if !expected.compatible(&arm_ty) => ctx.error(
+                    format!(
+                        "incompatible types for match arms: `{}` and `{}`",
+                        expected, arm_ty
+                    ),
+                    arm.body.location(),
+                    arm.print(),
+                ),
+                Some(_) => {}
+            }
+        }
+
+        match_ty.unwrap_or(CheckedType::Never)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JkInt, ToObjectInstance};
+
+    /// Execute the input after declaring the type used by the tests
+    fn execute(input: &str) -> Result<InstrKind, JkError> {
+        crate::utils::execute(&format!(
+            "type Shape = Circle(r: int) | Rect(w: int, h: int) | Empty; {}",
+            input
+        ))
+    }
+
+    #[test]
+    fn t_print() {
+        let arms = vec![
+            MatchArm::new(
+                Pattern::Variant("Some".to_owned(), vec!["v".to_owned()]),
+                Box::new(JkInt::from(1)),
+            ),
+            MatchArm::new(Pattern::Wildcard, Box::new(JkInt::from(0))),
+        ];
+        let match_expr = Match::new(Box::new(JkInt::from(12)), arms);
+
+        assert_eq!(match_expr.print(), "match 12 { Some(v) => 1, _ => 0 }");
+    }
+
+    #[test]
+    fn t_match_variant() {
+        assert_eq!(
+            execute("match Rect(2, 3) { Circle(r) => r, Rect(w, h) => w * h, Empty => 0 }")
+                .unwrap(),
+            InstrKind::Expression(Some(JkInt::from(6).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_match_wildcard() {
+        assert_eq!(
+            execute("match Empty() { Circle(_) => 1, _ => 2 }").unwrap(),
+            InstrKind::Expression(Some(JkInt::from(2).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_match_non_sum_type() {
+        assert!(execute("match 12 { _ => 2 }").is_err());
+    }
+}
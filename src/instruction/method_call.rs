@@ -2,18 +2,26 @@
 //! they get desugared into a normal function call.
 
 use crate::instruction::FunctionCall;
-use crate::{InstrKind, Instruction, Interpreter, JkError, Rename};
+use crate::{
+    CheckedType, InstrKind, Instruction, Interpreter, JkErrSpaceLocation, JkError, Rename,
+    TypeCheck, TypeCtx,
+};
 
 #[derive(Clone)]
 pub struct MethodCall {
     var: Box<dyn Instruction>,
     method: FunctionCall,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl MethodCall {
     /// Create a new MethodCall from a variable and an associated function
     pub fn new(var: Box<dyn Instruction>, method: FunctionCall) -> MethodCall {
-        MethodCall { var, method }
+        MethodCall {
+            var,
+            method,
+            location: None,
+        }
     }
 }
 
@@ -23,6 +31,14 @@ impl Instruction for MethodCall {
         InstrKind::Expression(None)
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         format!("{}.{}", self.var.print(), self.method.print())
     }
@@ -50,6 +66,17 @@ impl Rename for MethodCall {
     }
 }
 
+impl TypeCheck for MethodCall {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        // Typecheck the desugared call, just like it would get executed
+        let mut call = self.method.clone();
+
+        call.add_arg_front(self.var.clone());
+
+        call.resolve_type(ctx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,48 +2,77 @@
 //! When using nested instructions, such as `foo = bar();`, you're actually using
 //! two instructions: A function call expression, and a variable assignment statement
 
-use crate::{Interpreter, JkErrKind, JkError, ObjectInstance};
+use crate::typechecker::TypeCheck;
+use crate::{
+    FromObjectInstance, Interpreter, JkBool, JkErrKind, JkErrSpaceLocation, JkError, ObjectInstance,
+};
 
 use colored::Colorize;
 use downcast_rs::{impl_downcast, Downcast};
 
+mod array_index;
+mod array_literal;
 mod audit;
 mod binary_op;
 mod block;
 mod dec_arg;
+mod destructure;
+mod field_access;
+mod field_assignment;
 mod function_call;
 mod function_declaration;
 mod if_else;
 mod incl;
 mod jk_inst;
 mod loop_block;
+mod loop_control;
+mod map_literal;
+mod match_expr;
 mod method_call;
 mod operator;
+mod pattern;
 mod rename;
+mod return_statement;
+mod string_interpolation;
 mod type_declaration;
 mod type_id;
 mod type_instantiation;
+mod unary_op;
 mod var;
 mod var_assignment;
+mod variant_instantiation;
 
+pub use array_index::ArrayIndex;
+pub use array_literal::ArrayLiteral;
 pub use audit::Audit;
 pub use binary_op::BinaryOp;
 pub use block::Block;
 pub use dec_arg::DecArg;
+pub use destructure::Destructure;
+pub use field_access::FieldAccess;
+pub use field_assignment::FieldAssign;
 pub use function_call::FunctionCall;
 pub use function_declaration::{FunctionDec, FunctionKind};
 pub use if_else::IfElse;
 pub use incl::Incl;
 pub use jk_inst::JkInst;
 pub use loop_block::{Loop, LoopKind};
+pub use loop_control::{LoopControl, LoopControlKind};
+pub use map_literal::{MapEntry, MapLiteral};
+pub use match_expr::{Match, MatchArm};
 pub use method_call::MethodCall;
 pub use operator::Operator;
+pub use pattern::Pattern;
 pub use rename::Rename;
-pub use type_declaration::TypeDec;
-pub use type_id::{TypeId, PRIMITIVE_TYPES};
+pub use return_statement::Return;
+pub use string_interpolation::{StringInterpolation, StringPart};
+pub use type_declaration::{TypeDec, Variant};
+pub use type_id::{GenericMap, TypeId, ARRAY_TYPE, HASHABLE_TYPES, MAP_TYPE, PRIMITIVE_TYPES};
 pub use type_instantiation::TypeInstantiation;
+pub use unary_op::UnaryOp;
 pub use var::Var;
 pub use var_assignment::VarAssign;
+pub use variant_instantiation::VariantInstantiation;
 
 /// The type of instructions available. An Instruction either is a statement, or an
 /// expression. An expression contains an instance of a result. For example,
@@ -56,8 +85,9 @@ pub enum InstrKind {
 }
 
 /// The `Instruction` trait is the basic trait for all of Jinko's execution nodes. Each
-/// node that can be executed needs to implement it
-pub trait Instruction: InstructionClone + Downcast + Rename {
+/// node that can be executed needs to implement it. Each node also needs to be
+/// typechecked before being executed
+pub trait Instruction: InstructionClone + Downcast + Rename + TypeCheck {
     /// Execute the instruction, altering the state of the interpreter. Executing
     /// this method returns an InstrKind, so either a statement or an expression
     /// containing a "return value".
@@ -80,7 +110,7 @@ pub trait Instruction: InstructionClone + Downcast + Rename {
                     "statement found when expression was expected: {}",
                     self.print()
                 ),
-                None,
+                self.location().cloned(),
                 self.print(),
             )),
         }
@@ -97,7 +127,7 @@ pub trait Instruction: InstructionClone + Downcast + Rename {
                     "expression found when statement was expected: {}",
                     self.print()
                 ),
-                None,
+                self.location().cloned(),
                 self.print(),
             )),
         }
@@ -106,13 +136,18 @@ pub trait Instruction: InstructionClone + Downcast + Rename {
     /// Maybe execute the instruction, transforming it in a Rust bool if possible. It's
     /// only possible to execute as_bool on boolean variables, boolean constants. blocks
     /// returning a boolean and functions returning a boolean.
-    fn as_bool(&self, _interpreter: &mut Interpreter) -> Result<bool, JkError> {
-        Err(JkError::new(
-            JkErrKind::Interpreter,
-            format!("cannot be used as a boolean: {}", self.print()),
-            None,
-            self.print(),
-        ))
+    fn as_bool(&self, interpreter: &mut Interpreter) -> Result<bool, JkError> {
+        let instance = self.execute_expression(interpreter)?;
+
+        match instance.ty() {
+            Some(ty) if ty.name() == "bool" => Ok(JkBool::from_instance(&instance).0),
+            _ => Err(JkError::new(
+                JkErrKind::Interpreter,
+                format!("cannot be used as a boolean: {}", self.print()),
+                self.location().cloned(),
+                self.print(),
+            )),
+        }
     }
 
     /// What is the type of the instruction: a Statement or an Expression.
@@ -121,6 +156,13 @@ pub trait Instruction: InstructionClone + Downcast + Rename {
     /// of the instruction, just the kind of it.
     fn kind(&self) -> InstrKind;
 
+    /// Location of the instruction in the source code it was parsed from, if any.
+    /// Instructions created by the interpreter itself are not located
+    fn location(&self) -> Option<&JkErrSpaceLocation>;
+
+    /// Set the location of the instruction in its source code
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>);
+
     /// Pretty-print the instruction to valid jinko code
     fn print(&self) -> String;
 }
@@ -1,7 +1,7 @@
-//! Operators used by jinko's BinaryOp struct. This module is not public, and is only
-//! used by the BinaryOp structure.
+//! Operators used by jinko's BinaryOp and UnaryOp structs. This module is not public,
+//! and is only used by these structures.
 
-/// All the binary operators available
+/// All the operators available
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Operator {
@@ -9,6 +9,25 @@ pub enum Operator {
     Sub,
     Mul,
     Div,
+    Mod,
+    Pow,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    UnaryMinus,
+    UnaryPlus,
+    LeftShift,
+    RightShift,
+    Equals,
+    NotEquals,
+    LowerThan,
+    LowerOrEqual,
+    GreaterThan,
+    GreaterOrEqual,
+    And,
+    Or,
+    Not,
     LeftParenthesis,
     RightParenthesis,
 }
@@ -21,6 +40,23 @@ impl Operator {
             "-" => Operator::Sub,
             "*" => Operator::Mul,
             "/" => Operator::Div,
+            "%" => Operator::Mod,
+            "**" => Operator::Pow,
+            "&" => Operator::BitAnd,
+            "|" => Operator::BitOr,
+            "^" => Operator::BitXor,
+            "~" => Operator::BitNot,
+            "<<" => Operator::LeftShift,
+            ">>" => Operator::RightShift,
+            "==" => Operator::Equals,
+            "!=" => Operator::NotEquals,
+            "<" => Operator::LowerThan,
+            "<=" => Operator::LowerOrEqual,
+            ">" => Operator::GreaterThan,
+            ">=" => Operator::GreaterOrEqual,
+            "&&" => Operator::And,
+            "||" => Operator::Or,
+            "!" => Operator::Not,
             "(" => Operator::LeftParenthesis,
             ")" => Operator::RightParenthesis,
             _ => unreachable!("Invalid operator: {}", op_str),
@@ -34,6 +70,25 @@ impl Operator {
             Operator::Sub => "-",
             Operator::Mul => "*",
             Operator::Div => "/",
+            Operator::Mod => "%",
+            Operator::Pow => "**",
+            Operator::BitAnd => "&",
+            Operator::BitOr => "|",
+            Operator::BitXor => "^",
+            Operator::BitNot => "~",
+            Operator::UnaryMinus => "-",
+            Operator::UnaryPlus => "+",
+            Operator::LeftShift => "<<",
+            Operator::RightShift => ">>",
+            Operator::Equals => "==",
+            Operator::NotEquals => "!=",
+            Operator::LowerThan => "<",
+            Operator::LowerOrEqual => "<=",
+            Operator::GreaterThan => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::And => "&&",
+            Operator::Or => "||",
+            Operator::Not => "!",
             Operator::LeftParenthesis => "(",
             Operator::RightParenthesis => ")",
         }
@@ -42,9 +97,31 @@ impl Operator {
     /// Return the operator's precedence according to the Shunting Yard algorithm
     pub fn precedence(&self) -> u8 {
         match self {
+            // Unary operators bind tighter than any binary one, except the power
+            // operator: `-2 ** 2` is `-(2 ** 2)`
+            Operator::Pow => 11,
+            Operator::Not | Operator::BitNot | Operator::UnaryMinus | Operator::UnaryPlus => 10,
+
             // Classic SY operator precedence
-            Operator::Mul | Operator::Div => 3,
-            Operator::Add | Operator::Sub => 2,
+            Operator::Mul | Operator::Div | Operator::Mod => 9,
+            Operator::Add | Operator::Sub => 8,
+
+            // Bitwise operators are applied after arithmetic ones, like in C
+            Operator::LeftShift | Operator::RightShift => 7,
+            Operator::BitAnd => 6,
+            Operator::BitXor => 5,
+            Operator::BitOr => 4,
+
+            // Comparisons are done on the result of arithmetic operations, and
+            // logical operators combine the results of comparisons
+            Operator::Equals
+            | Operator::NotEquals
+            | Operator::LowerThan
+            | Operator::LowerOrEqual
+            | Operator::GreaterThan
+            | Operator::GreaterOrEqual => 3,
+            Operator::And => 2,
+            Operator::Or => 1,
 
             // Special operators. They don't really have a precedence value, and it's
             // never used
@@ -54,10 +131,44 @@ impl Operator {
 
     /// Is the operator a left associative one
     pub fn is_left_associative(&self) -> bool {
-        // FIXME: Not entirely true
-        // - Changes once we add more operators such as the Power one
+        // `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+        !matches!(self, Operator::Pow)
+    }
+
+    /// Is the operator a unary one, applied to a single operand placed after it
+    pub fn is_unary(&self) -> bool {
+        matches!(
+            self,
+            Operator::Not | Operator::BitNot | Operator::UnaryMinus | Operator::UnaryPlus
+        )
+    }
+
+    /// Get the unary version of an operator, used when the operator is placed before
+    /// an operand. Returns `None` if the operator cannot be used as a unary one
+    pub fn as_unary(&self) -> Option<Operator> {
         match self {
-            _ => true,
+            Operator::Sub => Some(Operator::UnaryMinus),
+            Operator::Add => Some(Operator::UnaryPlus),
+            op if op.is_unary() => Some(*op),
+            _ => None,
         }
     }
+
+    /// Is the operator a comparison, which produces a boolean from two values
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Operator::Equals
+                | Operator::NotEquals
+                | Operator::LowerThan
+                | Operator::LowerOrEqual
+                | Operator::GreaterThan
+                | Operator::GreaterOrEqual
+        )
+    }
+
+    /// Is the operator a logical one, which combines two booleans
+    pub fn is_logical(&self) -> bool {
+        matches!(self, Operator::And | Operator::Or)
+    }
 }
@@ -0,0 +1,207 @@
+//! Patterns name one of the variants of a sum type, and bind each of its fields to a
+//! new variable: `Rect(w, h)`. Fields can be ignored using `_`: `Rect(w, _)`. They
+//! are used by matches as well as destructurings.
+
+use crate::builtins::Builtins;
+use crate::instruction::{TypeDec, Var};
+use crate::{
+    CheckedType, Interpreter, JkErrKind, JkErrSpaceLocation, JkError, ObjectInstance, Rename,
+    TypeCtx,
+};
+
+/// Name used to ignore one of the fields of a variant, or to match any variant
+const WILDCARD: &str = "_";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pattern {
+    /// A variant, and the names given to each of its fields
+    Variant(String, Vec<String>),
+    /// Any variant
+    Wildcard,
+}
+
+impl Pattern {
+    pub fn print(&self) -> String {
+        match self {
+            Pattern::Variant(name, bindings) if bindings.is_empty() => name.clone(),
+            Pattern::Variant(name, bindings) => format!("{}({})", name, bindings.join(", ")),
+            Pattern::Wildcard => WILDCARD.to_owned(),
+        }
+    }
+
+    /// Check if the pattern applies to a variant
+    pub fn matches(&self, variant: &str) -> bool {
+        match self {
+            Pattern::Variant(name, _) => name == variant,
+            Pattern::Wildcard => true,
+        }
+    }
+
+    /// Variables declared by the pattern, along with the index of the field they are
+    /// bound to. Ignored fields do not declare any variable
+    pub fn bindings(&self) -> Vec<(usize, &str)> {
+        match self {
+            Pattern::Variant(_, bindings) => bindings
+                .iter()
+                .enumerate()
+                .filter(|(_, binding)| *binding != WILDCARD)
+                .map(|(idx, binding)| (idx, binding.as_str()))
+                .collect(),
+            Pattern::Wildcard => vec![],
+        }
+    }
+
+    /// Declare the variables bound by the pattern, with the fields of an instance of
+    /// the variant as their values
+    pub fn bind(
+        &self,
+        instance: &ObjectInstance,
+        interpreter: &mut Interpreter,
+        location: Option<&JkErrSpaceLocation>,
+    ) -> Result<(), JkError> {
+        let fields = instance
+            .variant()
+            .map(|variant| variant.fields().clone())
+            .unwrap_or_default();
+
+        for (idx, binding) in self.bindings() {
+            let field = fields.get(idx).ok_or_else(|| {
+                JkError::new(
+                    JkErrKind::Interpreter,
+                    format!("no field to bind to `{}`", binding),
+                    location.cloned(),
+                    self.print(),
+                )
+            })?;
+
+            let ty = interpreter
+                .get_type(field.get_type())
+                .map(|ty| (*ty).clone());
+
+            let mut var = Var::new(binding.to_owned());
+            // The field exists, since the instance has been created out of its variant
+            var.set_instance(instance.field_instance(field.name(), ty).unwrap());
+            interpreter.add_variable(var)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check the pattern against the variants of a sum type, and declare the
+    /// variables it binds with the types of the corresponding fields
+    pub fn declare_bindings(
+        &self,
+        type_dec: Option<&TypeDec>,
+        ctx: &mut TypeCtx,
+        location: Option<&JkErrSpaceLocation>,
+    ) {
+        let bindings_ty = match type_dec {
+            Some(type_dec) => self.resolve_bindings(type_dec, ctx, location),
+            None => vec![],
+        };
+
+        self.bindings().into_iter().for_each(|(idx, binding)| {
+            let ty = bindings_ty
+                .get(idx)
+                .cloned()
+                .unwrap_or(CheckedType::Unknown);
+            ctx.declare_var(binding, ty);
+        });
+    }
+
+    /// Get the types of the fields bound by the pattern, reporting an error if the
+    /// variant does not exist or if the wrong number of bindings is given
+    fn resolve_bindings(
+        &self,
+        type_dec: &TypeDec,
+        ctx: &mut TypeCtx,
+        location: Option<&JkErrSpaceLocation>,
+    ) -> Vec<CheckedType> {
+        let (name, bindings) = match self {
+            Pattern::Variant(name, bindings) => (name, bindings),
+            Pattern::Wildcard => return vec![],
+        };
+
+        let variant = match type_dec.variant_tag(name) {
+            Some(tag) => &type_dec.variants()[tag],
+            None => {
+                ctx.error(
+                    format!("no variant `{}` on type `{}`", name, type_dec.name()),
+                    location,
+                    self.print(),
+                );
+                return vec![];
+            }
+        };
+
+        if variant.fields().len() != bindings.len() {
+            ctx.error(
+                format!(
+                    "wrong number of bindings for variant `{}`: expected {}, got {}",
+                    name,
+                    variant.fields().len(),
+                    bindings.len()
+                ),
+                location,
+                self.print(),
+            );
+            return vec![];
+        }
+
+        variant
+            .fields()
+            .iter()
+            .map(|field| ctx.lookup(Some(field.get_type())))
+            .collect()
+    }
+}
+
+impl Rename for Pattern {
+    fn prefix(&mut self, prefix: &str) {
+        if let Pattern::Variant(name, bindings) = self {
+            // Variants of builtin types keep their name
+            if !Builtins::is_builtin(name) {
+                *name = format!("{}{}", prefix, name);
+            }
+
+            bindings
+                .iter_mut()
+                .filter(|binding| *binding != WILDCARD)
+                .for_each(|binding| *binding = format!("{}{}", prefix, binding));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_print() {
+        let pattern = Pattern::Variant("Rect".to_owned(), vec!["w".to_owned(), "_".to_owned()]);
+
+        assert_eq!(pattern.print(), "Rect(w, _)");
+        assert_eq!(Pattern::Wildcard.print(), "_");
+    }
+
+    #[test]
+    fn t_bindings() {
+        let pattern = Pattern::Variant("Rect".to_owned(), vec!["_".to_owned(), "h".to_owned()]);
+
+        assert_eq!(pattern.bindings(), vec![(1, "h")]);
+        assert!(pattern.matches("Rect"));
+        assert!(!pattern.matches("Circle"));
+        assert!(Pattern::Wildcard.matches("Circle"));
+    }
+
+    #[test]
+    fn t_prefix() {
+        let mut pattern = Pattern::Variant("Circle".to_owned(), vec!["r".to_owned()]);
+        pattern.prefix("ns::");
+        assert_eq!(pattern.print(), "ns::Circle(ns::r)");
+
+        let mut pattern = Pattern::Variant("Some".to_owned(), vec!["_".to_owned()]);
+        pattern.prefix("ns::");
+        assert_eq!(pattern.print(), "Some(_)");
+    }
+}
@@ -0,0 +1,122 @@
+//! The Return instruction is used to leave a function early. It can carry a value, which
+//! is then the value returned by the function. Returning unwinds the execution of all
+//! the blocks, conditions and loops the instruction is contained in.
+//!
+//! `func f(x: int) -> int { if x { return 1; }; 0 }`
+
+use crate::{
+    CheckedType, ControlFlow, InstrKind, Instruction, Interpreter, JkErrSpaceLocation, JkError,
+    Rename, TypeCheck, TypeCtx,
+};
+
+#[derive(Clone)]
+pub struct Return {
+    value: Option<Box<dyn Instruction>>,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl Return {
+    /// Create a new return instruction, with an optional value to return
+    pub fn new(value: Option<Box<dyn Instruction>>) -> Return {
+        Return {
+            value,
+            location: None,
+        }
+    }
+}
+
+impl Instruction for Return {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Statement
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        match &self.value {
+            Some(value) => format!("return {}", value.print()),
+            None => String::from("return"),
+        }
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("RETURN", &self.print());
+
+        let value = match &self.value {
+            Some(value) => Some(value.execute_expression(interpreter)?),
+            None => None,
+        };
+
+        Err(JkError::from_signal(
+            ControlFlow::Return(value),
+            self.location.clone(),
+            self.print(),
+        ))
+    }
+}
+
+impl Rename for Return {
+    fn prefix(&mut self, prefix: &str) {
+        if let Some(value) = &mut self.value {
+            value.prefix(prefix)
+        }
+    }
+}
+
+impl TypeCheck for Return {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let value_ty = match &self.value {
+            Some(value) => value.resolve_type(ctx),
+            None => CheckedType::Void,
+        };
+
+        match ctx.return_ty().cloned() {
+            None => ctx.error(
+                String::from("`return` outside of a function"),
+                self.location(),
+                self.print(),
+            ),
+            Some(return_ty) if !value_ty.compatible(&return_ty) => ctx.error(
+                format!(
+                    "function should return `{}` but `return` yields `{}`",
+                    return_ty, value_ty
+                ),
+                self.location(),
+                self.print(),
+            ),
+            Some(_) => {}
+        }
+
+        // Code following a return is never executed
+        CheckedType::Never
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JkInt;
+
+    #[test]
+    fn t_print() {
+        assert_eq!(Return::new(None).print(), "return");
+        assert_eq!(
+            Return::new(Some(Box::new(JkInt::from(12)))).print(),
+            "return 12"
+        );
+    }
+
+    #[test]
+    fn t_return_is_a_signal() {
+        let mut interpreter = Interpreter::new();
+        let err = Return::new(None).execute(&mut interpreter).err().unwrap();
+
+        assert_eq!(err.signal(), Some(&ControlFlow::Return(None)));
+    }
+}
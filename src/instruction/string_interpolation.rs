@@ -0,0 +1,175 @@
+//! StringInterpolations are string literals containing expressions between curly
+//! brackets: `"{name} is {age + 1} next year"`. The expressions are evaluated in the
+//! current scope, and their results are concatenated with the rest of the string.
+//! Strings are inserted as is, while other values are displayed like in the REPL:
+//! Custom types are displayed along with their fields, such as `Point(x: 1, y: 2)`.
+//! Curly brackets can be inserted in a string by escaping them: `"\{not interpolated}"`.
+
+use crate::{
+    CheckedType, FromObjectInstance, InstrKind, Instruction, Interpreter, JkErrSpaceLocation,
+    JkError, JkString, ObjectInstance, Rename, ToObjectInstance, TypeCheck, TypeCtx,
+};
+
+/// A part of an interpolated string
+#[derive(Clone)]
+pub enum StringPart {
+    /// Characters inserted as is, once escaped
+    Literal(String),
+    /// An expression whose result is inserted in the string
+    Expression(Box<dyn Instruction>),
+}
+
+impl StringPart {
+    fn print(&self) -> String {
+        match self {
+            StringPart::Literal(literal) => literal.clone(),
+            StringPart::Expression(expression) => format!("{{{}}}", expression.print()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StringInterpolation {
+    parts: Vec<StringPart>,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl StringInterpolation {
+    /// Create a new interpolated string out of its parts
+    pub fn new(parts: Vec<StringPart>) -> StringInterpolation {
+        StringInterpolation {
+            parts,
+            location: None,
+        }
+    }
+
+    /// Return a reference to the parts of the string
+    #[cfg(test)]
+    pub fn parts(&self) -> &Vec<StringPart> {
+        &self.parts
+    }
+
+    /// Convert the result of an interpolated expression to a string
+    pub fn stringify(instance: &ObjectInstance, interpreter: &Interpreter) -> String {
+        match instance.ty().map(|ty| ty.name()) {
+            Some("string") => JkString::from_instance(instance).0,
+            _ => instance.display(interpreter),
+        }
+    }
+}
+
+impl Instruction for StringInterpolation {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Expression(None)
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!(
+            "\"{}\"",
+            self.parts
+                .iter()
+                .map(|part| part.print())
+                .collect::<String>()
+        )
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("INTERPOLATION", &self.print());
+
+        let mut result = String::new();
+
+        for part in self.parts.iter() {
+            match part {
+                StringPart::Literal(literal) => result.push_str(literal),
+                StringPart::Expression(expression) => {
+                    let instance = expression.execute_expression(interpreter)?;
+                    result.push_str(&StringInterpolation::stringify(&instance, interpreter));
+                }
+            }
+        }
+
+        Ok(InstrKind::Expression(Some(
+            JkString::from(result).to_instance(),
+        )))
+    }
+}
+
+impl Rename for StringInterpolation {
+    fn prefix(&mut self, prefix: &str) {
+        self.parts.iter_mut().for_each(|part| {
+            if let StringPart::Expression(expression) = part {
+                expression.prefix(prefix);
+            }
+        });
+    }
+}
+
+impl TypeCheck for StringInterpolation {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        for part in self.parts.iter() {
+            if let StringPart::Expression(expression) = part {
+                if expression.resolve_type(ctx) == CheckedType::Void {
+                    ctx.error(
+                        format!(
+                            "cannot interpolate statement `{}` in a string",
+                            expression.print()
+                        ),
+                        expression.location(),
+                        self.print(),
+                    );
+                }
+            }
+        }
+
+        CheckedType::resolved("string")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::execute;
+
+    #[test]
+    fn t_print() {
+        let interpolation = StringInterpolation::new(vec![
+            StringPart::Literal("a = ".to_owned()),
+            StringPart::Expression(Box::new(JkString::from("b"))),
+        ]);
+
+        assert_eq!(interpolation.print(), "\"a = {\"b\"}\"");
+    }
+
+    #[test]
+    fn t_interpolation() {
+        assert_eq!(
+            execute("name = \"jinko\"; age = 2; \"{name} is {age + 1}\\n\"").unwrap(),
+            InstrKind::Expression(Some(JkString::from("jinko is 3\n").to_instance()))
+        );
+        assert_eq!(
+            execute("c = 'c'; \"{c}{[1, 2]}{1.5}{true}\"").unwrap(),
+            InstrKind::Expression(Some(JkString::from("c[1, 2]1.5true").to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_interpolation_custom_type() {
+        assert_eq!(
+            execute("type P(x: int); p = P { 1 }; \"{p}\"").unwrap(),
+            InstrKind::Expression(Some(JkString::from("P(x: 1)").to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_interpolation_unknown_variable() {
+        assert!(execute("\"{unknown}\"").is_err());
+    }
+}
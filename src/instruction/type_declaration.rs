@@ -1,17 +1,88 @@
-use super::{DecArg, InstrKind, Instruction};
+//! TypeDecs are used when declaring custom types. Types can be generic over other
+//! types: `type Node<T>(value: T)`. Generic types are monomorphised when their generic
+//! arguments are supplied, which creates a new declaration such as `Node<int>`, with
+//! all occurences of `T` replaced by `int`.
+//! Sum types are declared as a list of variants, each carrying its own fields:
+//! `type Option<T> = Some(value: T) | None;`. Each variant gets a constructor function
+//! named after it, so that `Some(12)` creates a new `Option<int>`.
 
-use crate::{Interpreter, JkError, Rename};
+use super::{
+    Block, DecArg, FunctionDec, FunctionKind, GenericMap, InstrKind, Instruction, TypeId,
+    VariantInstantiation,
+};
 
+use crate::{CheckedType, Interpreter, JkErrSpaceLocation, JkError, Rename, TypeCheck, TypeCtx};
+
+/// One of the alternatives of a sum type, and the fields it carries
 #[derive(Clone, Debug, PartialEq)]
+pub struct Variant {
+    name: String,
+    fields: Vec<DecArg>,
+}
+
+impl Variant {
+    /// Create a new variant
+    pub fn new(name: String, fields: Vec<DecArg>) -> Variant {
+        Variant { name, fields }
+    }
+
+    /// Get a reference to the name of the variant
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get a reference to the variant's fields
+    pub fn fields(&self) -> &Vec<DecArg> {
+        &self.fields
+    }
+}
+
+/// Replace the generic parameters contained in the types of the given fields
+fn substitute_fields(fields: &[DecArg], generic_map: &GenericMap) -> Vec<DecArg> {
+    fields
+        .iter()
+        .map(|field| {
+            DecArg::new(
+                field.name().to_owned(),
+                field.get_type().substitute(generic_map),
+            )
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
 pub struct TypeDec {
     name: String,
+    generics: Vec<TypeId>,
     fields: Vec<DecArg>,
+    // Every instance holds its type declaration, which needs to stay small
+    variants: Box<[Variant]>,
+    location: Option<JkErrSpaceLocation>,
+
+    /// Identifier of the monomorphised type, if the declaration is the result of a
+    /// monomorphisation
+    generic_id: Option<Box<TypeId>>,
 }
 
 impl TypeDec {
     /// Create a new type
     pub fn new(name: String, fields: Vec<DecArg>) -> TypeDec {
-        TypeDec { name, fields }
+        TypeDec {
+            name,
+            generics: vec![],
+            fields,
+            variants: Box::new([]),
+            location: None,
+            generic_id: None,
+        }
+    }
+
+    /// Create a new sum type out of its variants
+    pub fn sum(name: String, variants: Vec<Variant>) -> TypeDec {
+        let mut type_dec = TypeDec::new(name, vec![]);
+        type_dec.variants = variants.into_boxed_slice();
+
+        type_dec
     }
 
     /// Get a reference to the name of the type
@@ -23,6 +94,135 @@ impl TypeDec {
     pub fn fields(&self) -> &Vec<DecArg> {
         &self.fields
     }
+
+    /// Get a reference to the type's variants. Only sum types have variants
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+
+    /// Is the type a sum type
+    pub fn is_sum(&self) -> bool {
+        !self.variants.is_empty()
+    }
+
+    /// Get the index of one of the type's variants, which is used as its tag
+    pub fn variant_tag(&self, name: &str) -> Option<usize> {
+        self.variants
+            .iter()
+            .position(|variant| variant.name() == name)
+    }
+
+    /// Get a reference to the type's generic parameters
+    pub fn generics(&self) -> &Vec<TypeId> {
+        &self.generics
+    }
+
+    /// Set the generic parameters of the type
+    pub fn set_generics(&mut self, generics: Vec<TypeId>) {
+        self.generics = generics
+    }
+
+    /// Get the identifier of the declared type
+    pub fn type_id(&self) -> TypeId {
+        match &self.generic_id {
+            Some(id) => (**id).clone(),
+            None => TypeId::new(self.name.clone()),
+        }
+    }
+
+    /// Check that the right amount of generic arguments are given to the type
+    pub fn check_generics(&self, type_id: &TypeId) -> Result<(), String> {
+        match self.generics.len() == type_id.generics().len() {
+            true => Ok(()),
+            false => Err(format!(
+                "wrong number of generic arguments for type `{}`: expected {}, got {}",
+                self.name,
+                self.generics.len(),
+                type_id.generics().len()
+            )),
+        }
+    }
+
+    /// Create a new declaration from a generic type, replacing its generic parameters
+    /// with the given types. The given types must already be monomorphised
+    pub fn monomorphise(&self, generics: &[TypeId]) -> TypeDec {
+        let generic_map: GenericMap = self
+            .generics
+            .iter()
+            .map(|generic| generic.id().to_owned())
+            .zip(generics.iter().cloned())
+            .collect();
+
+        let fields = substitute_fields(&self.fields, &generic_map);
+        let variants = self
+            .variants
+            .iter()
+            .map(|variant| {
+                Variant::new(
+                    variant.name.clone(),
+                    substitute_fields(&variant.fields, &generic_map),
+                )
+            })
+            .collect();
+
+        let generic_id = TypeId::generic(self.name.clone(), generics.to_vec());
+
+        TypeDec {
+            name: generic_id.id().to_owned(),
+            generics: vec![],
+            fields,
+            variants,
+            location: self.location.clone(),
+            generic_id: Some(Box::new(generic_id)),
+        }
+    }
+
+    /// Create the constructors of a sum type. Each variant gets a function named after
+    /// it, generic over the same parameters as the type, which takes the variant's
+    /// fields as arguments and returns a new instance of the type
+    pub fn constructors(&self) -> Vec<FunctionDec> {
+        let ty = TypeId::generic(self.name.clone(), self.generics.clone());
+
+        self.variants
+            .iter()
+            .enumerate()
+            .map(|(tag, variant)| {
+                let mut block = Block::new();
+                block.set_last(Some(Box::new(VariantInstantiation::new(
+                    ty.clone(),
+                    tag,
+                    variant.clone(),
+                ))));
+
+                let mut constructor = FunctionDec::new(variant.name.clone(), Some(ty.clone()));
+                constructor.set_kind(FunctionKind::Func);
+                constructor.set_generics(self.generics.clone());
+                constructor.set_args(variant.fields.clone());
+                constructor.set_block(block);
+                constructor.set_location(self.location.clone());
+
+                constructor
+            })
+            .collect()
+    }
+
+    /// Print the fields of a product type or the variants of a sum type
+    fn print_body(&self) -> String {
+        match self.is_sum() {
+            false => format!("( {:?} )", self.fields),
+            true => format!(
+                "= {}",
+                self.variants
+                    .iter()
+                    .map(|variant| match variant.fields.is_empty() {
+                        true => variant.name.clone(),
+                        false => format!("{}( {:?} )", variant.name, variant.fields),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" | ")
+            ),
+        }
+    }
 }
 
 impl Instruction for TypeDec {
@@ -34,6 +234,9 @@ impl Instruction for TypeDec {
         interpreter.debug_step(&format!("CUSTOM TYPE {} ENTER", self.name));
 
         interpreter.add_type(self.clone())?;
+        self.constructors()
+            .into_iter()
+            .try_for_each(|constructor| interpreter.add_function(constructor))?;
 
         interpreter.debug_step(&format!("CUSTOM TYPE {} EXIT", self.name));
 
@@ -41,17 +244,93 @@ impl Instruction for TypeDec {
         Ok(InstrKind::Statement)
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
-        format!("type {} ( {:?} )", self.name, self.fields)
+        match self.generics.is_empty() {
+            true => format!("type {} {}", self.name, self.print_body()),
+            false => format!(
+                "type {}<{}> {}",
+                self.name,
+                self.generics
+                    .iter()
+                    .map(|generic| generic.id())
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+                self.print_body()
+            ),
+        }
     }
 }
 
 impl Rename for TypeDec {
     fn prefix(&mut self, prefix: &str) {
         self.name = format!("{}{}", prefix, self.name);
+        self.generics
+            .iter_mut()
+            .for_each(|generic| generic.prefix(prefix));
+        // Field names are part of the type and are never renamed, unlike their types
         self.fields
             .iter_mut()
-            .for_each(|field| field.prefix(prefix));
+            .for_each(|field| field.prefix_type(prefix));
+        self.variants.iter_mut().for_each(|variant| {
+            variant.name = format!("{}{}", prefix, variant.name);
+            variant
+                .fields
+                .iter_mut()
+                .for_each(|field| field.prefix_type(prefix));
+        });
+    }
+}
+
+impl TypeCheck for TypeDec {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        // The type and its constructors were declared when hoisting it, so it can
+        // refer to itself as well as to types declared after it
+
+        // Generic parameters are only known inside the declaration
+        ctx.scope_enter();
+        self.generics
+            .iter()
+            .for_each(|generic| ctx.declare_type(TypeDec::from(generic.id())));
+
+        self.fields
+            .iter()
+            .chain(
+                self.variants
+                    .iter()
+                    .flat_map(|variant| variant.fields.iter()),
+            )
+            .for_each(|field| {
+                ctx.resolve(Some(field.get_type()), self.location());
+            });
+
+        ctx.scope_exit();
+
+        // Declaring a type is always a statement (for now)
+        CheckedType::Void
+    }
+
+    fn hoist(&self, ctx: &mut TypeCtx) {
+        ctx.declare_type(self.clone());
+        ctx.declare_constructors(self);
+    }
+}
+
+// Two type declarations are the same if they declare the same type, regardless of
+// where they are located
+impl PartialEq for TypeDec {
+    fn eq(&self, other: &TypeDec) -> bool {
+        self.name == other.name
+            && self.generics == other.generics
+            && self.fields == other.fields
+            && self.variants == other.variants
     }
 }
 
@@ -69,9 +348,6 @@ impl From<&String> for TypeDec {
 
 impl From<String> for TypeDec {
     fn from(type_name: String) -> TypeDec {
-        TypeDec {
-            name: type_name,
-            fields: vec![],
-        }
+        TypeDec::new(type_name, vec![])
     }
 }
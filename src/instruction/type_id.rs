@@ -1,36 +1,174 @@
 //! A TypeId refers to a type's identifier. For example, the TypeId of `int` is "int".
 //! The TypeId of `type Custom(a: int, b: OtherCustom)` is `Custom`.
+//! Generic types are identified by their name as well as their generic arguments: the
+//! TypeId of `Node<int>` is "Node<int>", and its generics are `[int]`. Arrays are
+//! generic types as well, with a dedicated syntax: `[int]`. So are builtin maps, such
+//! as `map<string, int>`.
 
+use std::collections::HashMap;
+
+use crate::builtins::Builtins;
 use crate::Rename;
 
 pub const PRIMITIVE_TYPES: [&str; 5] = ["bool", "int", "float", "char", "string"];
 
-#[derive(Clone, Debug, PartialEq)]
+/// Name of the builtin generic array type
+pub const ARRAY_TYPE: &str = "[]";
+
+/// Name of the builtin generic map type
+pub const MAP_TYPE: &str = "map";
+
+/// Types which can be used as the keys of a map
+pub const HASHABLE_TYPES: [&str; 4] = ["int", "char", "string", "bool"];
+
+/// Associate the name of generic parameters to the type they stand for
+pub type GenericMap = HashMap<String, TypeId>;
+
+#[derive(Clone, Debug)]
 pub struct TypeId {
     id: String,
+    generics: Vec<TypeId>,
 }
 
 impl TypeId {
     pub fn new(id: String) -> TypeId {
-        TypeId { id }
+        TypeId {
+            id,
+            generics: vec![],
+        }
+    }
+
+    /// Create a new TypeId for a generic type, such as `Node<int>`
+    pub fn generic(base: String, generics: Vec<TypeId>) -> TypeId {
+        let id = match generics.is_empty() {
+            true => base,
+            false if base == ARRAY_TYPE => format!("[{}]", generics[0].id()),
+            false => format!(
+                "{}<{}>",
+                base,
+                generics
+                    .iter()
+                    .map(|generic| generic.id())
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+        };
+
+        TypeId { id, generics }
+    }
+
+    /// Create a new TypeId for an array containing elements of type `element`
+    pub fn array(element: TypeId) -> TypeId {
+        TypeId::generic(ARRAY_TYPE.to_owned(), vec![element])
     }
 
     pub fn id(&self) -> &str {
         &self.id
     }
 
+    /// Name of the type, without its generic arguments: The base of `Node<int>` is
+    /// `Node`
+    pub fn base(&self) -> &str {
+        if self.is_array() {
+            return ARRAY_TYPE;
+        }
+
+        match self.id.find('<') {
+            Some(idx) => &self.id[..idx],
+            None => &self.id,
+        }
+    }
+
+    /// Generic arguments of the type, if any
+    pub fn generics(&self) -> &Vec<TypeId> {
+        &self.generics
+    }
+
     pub fn is_primitive(&self) -> bool {
         PRIMITIVE_TYPES.contains(&self.id.as_str())
     }
+
+    /// Can the type be used as the keys of a map
+    pub fn is_hashable(&self) -> bool {
+        HASHABLE_TYPES.contains(&self.id.as_str())
+    }
+
+    /// Types of the keys and values of a map type
+    pub fn entry(&self) -> Option<(&TypeId, &TypeId)> {
+        match (self.base() == MAP_TYPE, self.generics.as_slice()) {
+            (true, [key, value]) => Some((key, value)),
+            _ => None,
+        }
+    }
+
+    /// Is the type an array type, such as `[int]`
+    pub fn is_array(&self) -> bool {
+        self.id.starts_with('[') && self.generics.len() == 1
+    }
+
+    /// Type of the elements contained in an array type
+    pub fn element(&self) -> Option<&TypeId> {
+        match self.is_array() {
+            true => self.generics.first(),
+            false => None,
+        }
+    }
+
+    /// Replace the generic parameters contained in the TypeId with the types they
+    /// stand for: `Node<T>` becomes `Node<int>` if `T` stands for `int`
+    pub fn substitute(&self, generics: &GenericMap) -> TypeId {
+        match (self.generics.is_empty(), generics.get(self.id())) {
+            (true, Some(ty)) => ty.clone(),
+            (true, None) => self.clone(),
+            (false, _) => TypeId::generic(
+                self.base().to_owned(),
+                self.generics
+                    .iter()
+                    .map(|generic| generic.substitute(generics))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Find the types that the generic parameters `params` stand for, by matching the
+    /// TypeId against a concrete type: matching `[T]` against `[int]` means that `T`
+    /// stands for `int`. Parameters which are already known are not changed
+    pub fn infer(&self, concrete: &TypeId, params: &[TypeId], generics: &mut GenericMap) {
+        if self.generics.is_empty() {
+            if params.contains(self) && !generics.contains_key(self.id()) {
+                generics.insert(self.id().to_owned(), concrete.clone());
+            }
+        } else if self.base() == concrete.base() && self.generics.len() == concrete.generics.len() {
+            self.generics
+                .iter()
+                .zip(concrete.generics.iter())
+                .for_each(|(generic, concrete)| generic.infer(concrete, params, generics));
+        }
+    }
 }
 
 impl Rename for TypeId {
     fn prefix(&mut self, prefix: &str) {
-        match PRIMITIVE_TYPES.contains(&self.id.as_str()) {
-            // No need to rename primitive types
-            true => {}
-            false => self.id = format!("{}{}", prefix, self.id),
-        }
+        let base = self.base();
+        let base = match PRIMITIVE_TYPES.contains(&base) || Builtins::is_builtin_type(base) {
+            // No need to rename primitive or builtin types
+            true => base.to_owned(),
+            false => format!("{}{}", prefix, base),
+        };
+
+        self.generics
+            .iter_mut()
+            .for_each(|generic| generic.prefix(prefix));
+
+        *self = TypeId::generic(base, self.generics.clone());
+    }
+}
+
+// The identifier of a generic type contains its generic arguments, so comparing
+// identifiers is enough
+impl PartialEq for TypeId {
+    fn eq(&self, other: &TypeId) -> bool {
+        self.id == other.id
     }
 }
 
@@ -39,3 +177,99 @@ impl From<&str> for TypeId {
         TypeId::new(s.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_generic_id() {
+        let ty = TypeId::generic(
+            "Map".to_owned(),
+            vec![
+                TypeId::from("int"),
+                TypeId::generic("Node".to_owned(), vec![TypeId::from("T")]),
+            ],
+        );
+
+        assert_eq!(ty.id(), "Map<int, Node<T>>");
+        assert_eq!(ty.base(), "Map");
+        assert_eq!(ty.generics().len(), 2);
+    }
+
+    #[test]
+    fn t_substitute() {
+        let mut generics = GenericMap::new();
+        generics.insert("T".to_owned(), TypeId::from("int"));
+
+        let ty = TypeId::generic(
+            "Vec".to_owned(),
+            vec![TypeId::generic("Node".to_owned(), vec![TypeId::from("T")])],
+        );
+
+        assert_eq!(ty.substitute(&generics).id(), "Vec<Node<int>>");
+        assert_eq!(TypeId::from("T").substitute(&generics).id(), "int");
+        assert_eq!(TypeId::from("U").substitute(&generics).id(), "U");
+    }
+
+    #[test]
+    fn t_array() {
+        let ty = TypeId::array(TypeId::array(TypeId::from("int")));
+
+        assert_eq!(ty.id(), "[[int]]");
+        assert_eq!(ty.base(), ARRAY_TYPE);
+        assert!(ty.is_array());
+        assert_eq!(ty.element().unwrap().id(), "[int]");
+        assert!(TypeId::from("int").element().is_none());
+    }
+
+    #[test]
+    fn t_map() {
+        let ty = TypeId::generic(
+            MAP_TYPE.to_owned(),
+            vec![TypeId::from("string"), TypeId::array(TypeId::from("int"))],
+        );
+
+        assert_eq!(ty.id(), "map<string, [int]>");
+        assert_eq!(ty.entry().unwrap().0.id(), "string");
+        assert_eq!(ty.entry().unwrap().1.id(), "[int]");
+        assert!(ty.entry().unwrap().0.is_hashable());
+        assert!(!ty.entry().unwrap().1.is_hashable());
+        assert!(TypeId::from("map").entry().is_none());
+    }
+
+    #[test]
+    fn t_infer() {
+        let params = vec![TypeId::from("T"), TypeId::from("U")];
+        let mut generics = GenericMap::new();
+
+        let ty = TypeId::generic(
+            "Pair".to_owned(),
+            vec![TypeId::array(TypeId::from("T")), TypeId::from("U")],
+        );
+        let concrete = TypeId::generic(
+            "Pair".to_owned(),
+            vec![
+                TypeId::array(TypeId::from("char")),
+                TypeId::array(TypeId::from("int")),
+            ],
+        );
+
+        ty.infer(&concrete, &params, &mut generics);
+
+        assert_eq!(generics.get("T").unwrap().id(), "char");
+        assert_eq!(generics.get("U").unwrap().id(), "[int]");
+    }
+
+    #[test]
+    fn t_prefix_generic() {
+        let mut ty = TypeId::generic(
+            "Node".to_owned(),
+            vec![TypeId::from("int"), TypeId::from("T")],
+        );
+        ty.prefix("ns::");
+
+        assert_eq!(ty.id(), "ns::Node<int, ns::T>");
+        assert_eq!(ty.base(), "ns::Node");
+    }
+}
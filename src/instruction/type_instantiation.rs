@@ -1,11 +1,14 @@
 //! TypeInstantiations are used when instantiating a type. The argument list is given to the
-//! type on execution.
+//! type on execution. Sum types cannot be instantiated directly: one of their variants
+//! is instantiated instead, which is the same as calling the variant's constructor.
 
 use super::{
-    InstrKind, Instruction, Interpreter, JkErrKind, JkError, ObjectInstance, Rename, TypeDec,
-    TypeId,
+    FunctionCall, FunctionDec, InstrKind, Instruction, Interpreter, JkErrKind, JkError,
+    ObjectInstance, Rename, TypeDec, TypeId,
 };
+use crate::builtins::Builtins;
 use crate::instance::{Name, Size};
+use crate::{CheckedType, JkErrSpaceLocation, TypeCheck, TypeCtx};
 
 use std::rc::Rc;
 
@@ -13,6 +16,7 @@ use std::rc::Rc;
 pub struct TypeInstantiation {
     type_name: TypeId,
     fields: Vec<Box<dyn Instruction>>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl TypeInstantiation {
@@ -21,6 +25,7 @@ impl TypeInstantiation {
         TypeInstantiation {
             type_name,
             fields: Vec::new(),
+            location: None,
         }
     }
 
@@ -42,13 +47,12 @@ impl TypeInstantiation {
     /// Get the corresponding type declaration from an interpreter
     fn get_declaration(&self, interpreter: &mut Interpreter) -> Result<Rc<TypeDec>, JkError> {
         match interpreter.get_type(self.name()) {
-            // get_type() return a Rc, so this clones the Rc, not the TypeId
-            Some(t) => Ok(t.clone()),
-            // FIXME: Fix Location and input
+            // Generic types are monomorphised on the fly by get_type()
+            Some(t) => Ok(t),
             None => Err(JkError::new(
                 JkErrKind::Interpreter,
                 format!("Cannot find type {}", self.name().id()),
-                None,
+                self.location.clone(),
                 self.print(),
             )),
         }
@@ -67,13 +71,53 @@ impl TypeInstantiation {
                     type_dec.fields().len(),
                     self.fields().len()
                 ),
-                None,
+                self.location.clone(),
                 "".to_owned(),
                 // FIXME: Add input and location
             )),
         }
     }
 
+    /// Instantiating one of the variants of a sum type is the same as calling the
+    /// variant's constructor: `Circle { 1.5 }` is equivalent to `Circle(1.5)`. Returns
+    /// `None` if the given function is not the constructor of a variant
+    fn variant_call(&self, constructor: Option<&FunctionDec>) -> Option<FunctionCall> {
+        if !constructor?.is_constructor() {
+            return None;
+        }
+
+        let mut call = FunctionCall::new(self.type_name.base().to_owned());
+        call.set_generics(self.type_name.generics().clone());
+        self.fields
+            .iter()
+            .for_each(|field| call.add_arg(field.clone()));
+        call.set_location(self.location.clone());
+
+        Some(call)
+    }
+
+    /// Error emitted when instantiating a sum type instead of one of its variants
+    fn sum_error(&self, type_dec: &TypeDec, kind: JkErrKind) -> JkError {
+        JkError::new(
+            kind,
+            format!(
+                "cannot instantiate sum type `{}` directly",
+                self.type_name.id()
+            ),
+            self.location.clone(),
+            self.print(),
+        )
+        .with_help(format!(
+            "instantiate one of its variants instead: {}",
+            type_dec
+                .variants()
+                .iter()
+                .map(|variant| format!("`{}`", variant.name()))
+                .collect::<Vec<String>>()
+                .join(", ")
+        ))
+    }
+
     /// Check if the type we're currently instantiating is a primitive type or not
     fn check_primitive(&self) -> Result<(), JkError> {
         match self.type_name.is_primitive() {
@@ -83,7 +127,7 @@ impl TypeInstantiation {
                     "cannot instantiate primitive type `{}`",
                     self.type_name.id()
                 ),
-                None,
+                self.location.clone(),
                 self.print(),
             )),
             false => Ok(()),
@@ -96,6 +140,14 @@ impl Instruction for TypeInstantiation {
         InstrKind::Expression(None)
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         let mut base = format!("{}(", self.type_name.id());
         let mut first_arg = true;
@@ -115,13 +167,24 @@ impl Instruction for TypeInstantiation {
     fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
         self.check_primitive()?;
 
+        if interpreter.get_type(self.name()).is_none() {
+            let constructor = interpreter.get_function(self.name().base()).cloned();
+            if let Some(call) = self.variant_call(constructor.as_deref()) {
+                return call.execute(interpreter);
+            }
+        }
+
         let type_dec = self.get_declaration(interpreter)?;
+        if type_dec.is_sum() {
+            return Err(self.sum_error(&type_dec, JkErrKind::Interpreter));
+        }
 
         self.check_fields_count(&type_dec)?;
 
         let mut size: usize = 0;
         let mut data: Vec<u8> = Vec::new();
         let mut fields: Vec<(Name, Size)> = Vec::new();
+        let mut instances: Vec<ObjectInstance> = Vec::new();
         for (i, instr) in self.fields.iter().enumerate() {
             let dec_name = type_dec.fields()[i].name();
             let instance = match instr.execute(interpreter)? {
@@ -133,7 +196,7 @@ impl Instruction for TypeInstantiation {
                             "An Expression was excepted but a Statement was found: `{}`",
                             dec_name
                         ),
-                        None,
+                        self.location.clone(),
                         instr.print(),
                     ))
                 }
@@ -143,27 +206,129 @@ impl Instruction for TypeInstantiation {
             size += inst_size;
             fields.push((dec_name.to_string(), inst_size));
             data.append(&mut instance.data().to_vec());
+            instances.push(instance);
         }
 
-        Ok(InstrKind::Expression(Some(ObjectInstance::new(
+        let mut instance = ObjectInstance::new(
             // FIXME: Disgusting, maybe do not use Rc for TypeId?
             Some((*type_dec).clone()),
             size,
             data,
             Some(fields),
-        ))))
+        );
+
+        // Keep the layout of fields which are instances of custom types themselves
+        type_dec
+            .fields()
+            .iter()
+            .zip(instances.iter())
+            .for_each(|(dec, field)| instance.nest_fields(dec.name(), field));
+
+        Ok(InstrKind::Expression(Some(instance)))
     }
 }
 
 impl Rename for TypeInstantiation {
     fn prefix(&mut self, prefix: &str) {
-        self.type_name.prefix(prefix);
+        match Builtins::is_builtin(self.type_name.base()) {
+            // Variants of builtin types keep their name
+            true => {
+                let mut generics = self.type_name.generics().clone();
+                generics
+                    .iter_mut()
+                    .for_each(|generic| generic.prefix(prefix));
+                self.type_name = TypeId::generic(self.type_name.base().to_owned(), generics);
+            }
+            false => self.type_name.prefix(prefix),
+        }
         self.fields
             .iter_mut()
             .for_each(|field| field.prefix(prefix));
     }
 }
 
+impl TypeCheck for TypeInstantiation {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        if ctx.get_type(self.name()).is_none() {
+            let constructor = ctx.get_function(self.name().base());
+            if let Some(call) = self.variant_call(constructor.as_deref()) {
+                return call.resolve_type(ctx);
+            }
+        }
+
+        let fields_ty: Vec<CheckedType> = self
+            .fields
+            .iter()
+            .map(|field| field.resolve_type(ctx))
+            .collect();
+
+        if self.type_name.is_primitive() {
+            ctx.error(
+                format!(
+                    "cannot instantiate primitive type `{}`",
+                    self.type_name.id()
+                ),
+                self.location(),
+                self.print(),
+            );
+            return CheckedType::Unknown;
+        }
+
+        let type_dec = match ctx.get_type(self.name()) {
+            Some(t) => t,
+            None => {
+                let msg = ctx.generics_mismatch(self.name());
+
+                ctx.error(
+                    msg.unwrap_or_else(|| format!("cannot find type {}", self.name().id())),
+                    self.location(),
+                    self.print(),
+                );
+                return CheckedType::Unknown;
+            }
+        };
+
+        if type_dec.is_sum() {
+            ctx.report(self.sum_error(&type_dec, JkErrKind::TypeChecker));
+            return CheckedType::Unknown;
+        }
+
+        if self.fields.len() != type_dec.fields().len() {
+            ctx.error(
+                format!(
+                    "wrong number of arguments \
+                    for type instantiation `{}`: expected {}, got {}",
+                    self.name().id(),
+                    type_dec.fields().len(),
+                    self.fields().len()
+                ),
+                self.location(),
+                self.print(),
+            );
+        }
+
+        for ((field, field_ty), dec) in self.fields.iter().zip(fields_ty).zip(type_dec.fields()) {
+            let expected_ty = ctx.lookup(Some(dec.get_type()));
+
+            if !field_ty.compatible(&expected_ty) {
+                ctx.error(
+                    format!(
+                        "wrong type for field `{}` of type `{}`: expected `{}`, got `{}`",
+                        dec.name(),
+                        self.name().id(),
+                        expected_ty,
+                        field_ty,
+                    ),
+                    field.location(),
+                    field.print(),
+                );
+            }
+        }
+
+        CheckedType::Resolved(self.type_name.clone())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -262,6 +427,35 @@ mod test {
         );
     }
 
+    #[test]
+    fn t_instantiate_generic() {
+        use crate::parser::Construct;
+        use crate::value::JkInt;
+        use crate::ToObjectInstance;
+
+        let mut i = Interpreter::new();
+
+        Construct::instruction("type Node<T>(value: T, id: int);")
+            .unwrap()
+            .1
+            .execute(&mut i)
+            .unwrap();
+
+        let instance = Construct::instruction("Node<Node<int>> { Node<int> { 1, 2 }, 3 }")
+            .unwrap()
+            .1
+            .execute_expression(&mut i)
+            .unwrap();
+
+        let ty = instance.ty().unwrap();
+        assert_eq!(ty.name(), "Node<Node<int>>");
+        assert_eq!(ty.fields()[0].get_type().id(), "Node<int>");
+        assert_eq!(
+            instance.field("value.value"),
+            Some(JkInt::from(1).to_instance().data())
+        );
+    }
+
     #[test]
     fn t_instantiate_primitive() {
         use crate::parser::Construct;
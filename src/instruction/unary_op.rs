@@ -0,0 +1,161 @@
+//! Unary operations apply an operation on a single Instruction. When writing `!a`, a
+//! UnaryOp will be created containing "a" as its operand and "!" as the operator.
+//!
+//! The available unary operators are `!`, the logical `Not`, `~`, the bitwise `Not`,
+//! as well as `-` and `+` which respectively negate a number and leave it untouched.
+
+use crate::{
+    instruction::Operator, CheckedType, FromObjectInstance, InstrKind, Instruction, Interpreter,
+    JkBool, JkErrKind, JkErrSpaceLocation, JkError, JkFloat, JkInt, Rename, TypeCheck, TypeCtx,
+    Value,
+};
+
+/// The `UnaryOp` struct contains an expression and the operator applied to it
+#[derive(Clone)]
+pub struct UnaryOp {
+    operand: Box<dyn Instruction>,
+    op: Operator,
+
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl UnaryOp {
+    /// Create a new `UnaryOp` from an instruction and an operator
+    pub fn new(operand: Box<dyn Instruction>, op: Operator) -> UnaryOp {
+        UnaryOp {
+            operand,
+            op,
+            location: None,
+        }
+    }
+
+    /// Return the operator used by the UnaryOp
+    #[cfg(test)]
+    pub fn operator(&self) -> Operator {
+        self.op
+    }
+
+    /// Types on which the operator can be used
+    fn valid_types(&self) -> &'static [&'static str] {
+        match self.op {
+            Operator::Not => &["bool"],
+            Operator::BitNot => &["int"],
+            Operator::UnaryMinus | Operator::UnaryPlus => &["int", "float"],
+            _ => &[],
+        }
+    }
+}
+
+impl Instruction for UnaryOp {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Expression(None)
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!("{}{}", self.op.to_str(), self.operand.print())
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("UNOP", self.op.to_str());
+
+        let operand = self.operand.execute_expression(interpreter)?;
+
+        // FIXME: Do not unwrap
+        let ty_name = operand.ty().unwrap().name();
+        let value = match ty_name {
+            "int" => JkInt::from_instance(&operand).do_unary_op(self.op),
+            "float" => JkFloat::from_instance(&operand).do_unary_op(self.op),
+            "bool" => JkBool::from_instance(&operand).do_unary_op(self.op),
+            _ => Err(JkError::new(
+                JkErrKind::Interpreter,
+                format!(
+                    "operator `{}` is not implemented for type `{}`",
+                    self.op.to_str(),
+                    ty_name
+                ),
+                self.location.clone(),
+                self.print(),
+            )),
+        };
+
+        // Values are not always located, so locate the error on the whole operation
+        let value = value.map_err(|e| e.locate(self.location()))?;
+
+        Ok(InstrKind::Expression(Some(value)))
+    }
+}
+
+impl Rename for UnaryOp {
+    fn prefix(&mut self, prefix: &str) {
+        self.operand.prefix(prefix);
+    }
+}
+
+impl TypeCheck for UnaryOp {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let ty = self.operand.resolve_type(ctx);
+
+        match &ty {
+            CheckedType::Unknown | CheckedType::Never => ty,
+            CheckedType::Resolved(id) if self.valid_types().contains(&id.id()) => ty,
+            _ => {
+                ctx.error(
+                    format!(
+                        "operator `{}` is not implemented for type `{}`",
+                        self.op.to_str(),
+                        ty
+                    ),
+                    self.location(),
+                    self.print(),
+                );
+                CheckedType::Unknown
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToObjectInstance;
+
+    #[test]
+    fn t_unop_not() {
+        let mut i = Interpreter::new();
+        let unop = UnaryOp::new(Box::new(JkBool::from(true)), Operator::Not);
+
+        assert_eq!(unop.operator(), Operator::Not);
+        assert_eq!(unop.print(), "!true");
+        assert_eq!(
+            unop.execute(&mut i).unwrap(),
+            InstrKind::Expression(Some(JkBool::from(false).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_unop_not_on_int() {
+        let mut i = Interpreter::new();
+        let unop = UnaryOp::new(Box::new(JkInt::from(1)), Operator::Not);
+
+        assert!(unop.execute(&mut i).is_err());
+    }
+
+    #[test]
+    fn t_unop_bit_not() {
+        let mut i = Interpreter::new();
+        let unop = UnaryOp::new(Box::new(JkInt::from(5)), Operator::BitNot);
+
+        assert_eq!(
+            unop.execute(&mut i).unwrap(),
+            InstrKind::Expression(Some(JkInt::from(-6).to_instance()))
+        );
+    }
+}
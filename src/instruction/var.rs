@@ -5,7 +5,8 @@
 
 use crate::instruction::TypeDec;
 use crate::{
-    InstrKind, Instruction, Interpreter, JkBool, JkErrKind, JkError, ObjectInstance, Rename,
+    CheckedType, InstrKind, Instruction, Interpreter, JkBool, JkErrKind, JkErrSpaceLocation,
+    JkError, ObjectInstance, Rename, TypeCheck, TypeCtx,
 };
 
 #[derive(Clone)]
@@ -13,6 +14,7 @@ pub struct Var {
     name: String,
     mutable: bool,
     instance: ObjectInstance,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl Var {
@@ -22,6 +24,7 @@ impl Var {
             name,
             mutable: false,
             instance: ObjectInstance::empty(),
+            location: None,
         }
     }
 
@@ -56,6 +59,14 @@ impl Instruction for Var {
         InstrKind::Expression(None)
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         format!(
             "{} /* : {} = {} */",
@@ -79,7 +90,7 @@ impl Instruction for Var {
                     _ => Err(JkError::new(
                         JkErrKind::Interpreter,
                         format!("var {} cannot be interpreted as boolean", self.name),
-                        None,
+                        self.location.clone(),
                         self.print(),
                     )),
                 },
@@ -91,7 +102,7 @@ impl Instruction for Var {
             _ => Err(JkError::new(
                 JkErrKind::Interpreter,
                 format!("var {} cannot be interpreted as boolean", self.name),
-                None,
+                self.location.clone(),
                 self.print(),
             )),
         }
@@ -104,13 +115,17 @@ impl Instruction for Var {
                 return Err(JkError::new(
                     JkErrKind::Interpreter,
                     format!("variable has not been declared: {}", self.name),
-                    None,
+                    self.location.clone(),
                     self.name().to_owned(),
                 ))
             }
         };
 
-        interpreter.debug("VAR", var.print().as_ref());
+        // Printing the variable displays its whole value, which is only worth it when
+        // debugging
+        if interpreter.debug_mode {
+            interpreter.debug("VAR", var.print().as_ref());
+        }
 
         Ok(InstrKind::Expression(Some(var.instance())))
     }
@@ -122,6 +137,22 @@ impl Rename for Var {
     }
 }
 
+impl TypeCheck for Var {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        match ctx.get_var(self.name()) {
+            Some(ty) => ty.clone(),
+            None => {
+                ctx.error(
+                    format!("variable has not been declared: {}", self.name),
+                    self.location(),
+                    self.name().to_owned(),
+                );
+                CheckedType::Unknown
+            }
+        }
+    }
+}
+
 impl Default for Var {
     fn default() -> Self {
         Var::new(String::new())
@@ -1,7 +1,10 @@
 //! The VarAssign struct is used when assigning values to variables.
 
 use crate::instruction::{InstrKind, Var};
-use crate::{Instruction, Interpreter, JkErrKind, JkError, Rename};
+use crate::{
+    CheckedType, Instruction, Interpreter, JkErrKind, JkErrSpaceLocation, JkError, Rename,
+    TypeCheck, TypeCtx,
+};
 
 #[derive(Clone)]
 pub struct VarAssign {
@@ -12,6 +15,7 @@ pub struct VarAssign {
     symbol: String,
 
     value: Box<dyn Instruction>,
+    location: Option<JkErrSpaceLocation>,
 }
 
 impl VarAssign {
@@ -20,6 +24,7 @@ impl VarAssign {
             mutable,
             symbol,
             value,
+            location: None,
         }
     }
 
@@ -39,6 +44,14 @@ impl Instruction for VarAssign {
         InstrKind::Statement
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
     fn print(&self) -> String {
         let base = if self.mutable {
             String::from("mut ")
@@ -63,7 +76,7 @@ impl Instruction for VarAssign {
                     return Err(JkError::new(
                         JkErrKind::Interpreter,
                         format!("Trying to redefine already defined variable: {}", v.name()),
-                        None,
+                        self.location.clone(),
                         self.print(),
                     ));
                 }
@@ -96,9 +109,13 @@ impl Instruction for VarAssign {
                             var.name(),
                             self.value.print()
                         ),
-                        None,
+                        self.location.clone(),
                         self.print(),
-                    ));
+                    )
+                    .with_help(format!(
+                        "declare the variable as mutable: `mut {} = ...`",
+                        var.name()
+                    )));
                 }
                 true => var.set_instance(self.value.execute_expression(interpreter)?),
             },
@@ -120,6 +137,44 @@ impl Rename for VarAssign {
     }
 }
 
+impl TypeCheck for VarAssign {
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType {
+        let value_ty = self.value.resolve_type(ctx);
+
+        if value_ty == CheckedType::Void {
+            ctx.error(
+                format!(
+                    "cannot assign a statement to variable `{}`: {}",
+                    self.symbol,
+                    self.value.print()
+                ),
+                self.location(),
+                self.print(),
+            );
+        }
+
+        match ctx.get_assignable_var(self.symbol()).cloned() {
+            // Assigning to an already existing variable: The types must match
+            Some(var_ty) if !self.mutable() => {
+                if !var_ty.compatible(&value_ty) {
+                    ctx.error(
+                        format!(
+                            "trying to assign value of type `{}` to variable `{}` of type `{}`",
+                            value_ty, self.symbol, var_ty
+                        ),
+                        self.location(),
+                        self.print(),
+                    );
+                }
+            }
+            _ => ctx.declare_var(self.symbol(), value_ty),
+        }
+
+        // A variable assignment is always a statement
+        CheckedType::Void
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
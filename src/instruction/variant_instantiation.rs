@@ -0,0 +1,155 @@
+//! VariantInstantiations create a new instance of one of the variants of a sum type,
+//! out of the variables named after the variant's fields. They are only found in the
+//! body of the constructors generated for each variant, where the arguments given to
+//! the constructor are available as variables.
+
+use super::{InstrKind, Instruction, Interpreter, JkErrKind, JkError, Rename, TypeId, Variant};
+use crate::instance::Name;
+use crate::{CheckedType, JkErrSpaceLocation, ObjectInstance, TypeCheck, TypeCtx};
+
+#[derive(Clone)]
+pub struct VariantInstantiation {
+    ty: TypeId,
+    tag: usize,
+    variant: Variant,
+    location: Option<JkErrSpaceLocation>,
+}
+
+impl VariantInstantiation {
+    /// Create a new instantiation of the variant at index `tag` of the sum type `ty`
+    pub fn new(ty: TypeId, tag: usize, variant: Variant) -> VariantInstantiation {
+        VariantInstantiation {
+            ty,
+            tag,
+            variant,
+            location: None,
+        }
+    }
+
+    fn error(&self, msg: String) -> JkError {
+        JkError::new(
+            JkErrKind::Interpreter,
+            msg,
+            self.location.clone(),
+            self.print(),
+        )
+    }
+}
+
+impl Instruction for VariantInstantiation {
+    fn kind(&self) -> InstrKind {
+        InstrKind::Expression(None)
+    }
+
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.location.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.location = location;
+    }
+
+    fn print(&self) -> String {
+        format!(
+            "{}::{}({})",
+            self.ty.id(),
+            self.variant.name(),
+            self.variant
+                .fields()
+                .iter()
+                .map(|field| field.name())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )
+    }
+
+    fn execute(&self, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+        interpreter.debug("VARIANT", &self.print());
+
+        let fields = self
+            .variant
+            .fields()
+            .iter()
+            .map(|field| match interpreter.get_variable(field.name()) {
+                Some(var) => Ok((field.name().to_owned(), var.instance())),
+                None => Err(self.error(format!("missing field `{}`", field.name()))),
+            })
+            .collect::<Result<Vec<(Name, ObjectInstance)>, JkError>>()?;
+
+        // Generic parameters stand for the types they were given in the scope of the
+        // constructor
+        let ty = match interpreter.get_type(&self.ty) {
+            Some(ty) => (*ty).clone(),
+            None => return Err(self.error(format!("cannot find type `{}`", self.ty.id()))),
+        };
+
+        Ok(InstrKind::Expression(Some(ObjectInstance::from_variant(
+            Some(ty),
+            self.tag,
+            &fields,
+        ))))
+    }
+}
+
+impl Rename for VariantInstantiation {
+    fn prefix(&mut self, _: &str) {
+        // Variant instantiations are created out of an already renamed type
+        // declaration
+    }
+}
+
+impl TypeCheck for VariantInstantiation {
+    fn resolve_type(&self, _: &mut TypeCtx) -> CheckedType {
+        CheckedType::Resolved(self.ty.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instruction::DecArg;
+
+    /// Instantiate a variant, after declaring the type used by the tests
+    fn execute(input: &str) -> ObjectInstance {
+        let input = format!(
+            "type Shape = Circle(r: int) | Square(side: int) | Empty; {}",
+            input
+        );
+
+        match crate::utils::execute(&input).unwrap() {
+            InstrKind::Expression(Some(instance)) => instance,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn t_print() {
+        let variant = Variant::new(
+            "Some".to_owned(),
+            vec![DecArg::new("value".to_owned(), TypeId::from("T"))],
+        );
+        let instantiation = VariantInstantiation::new(
+            TypeId::generic("Option".to_owned(), vec![TypeId::from("T")]),
+            0,
+            variant,
+        );
+
+        assert_eq!(instantiation.print(), "Option<T>::Some(value)");
+    }
+
+    #[test]
+    fn t_tag() {
+        assert_eq!(execute("Circle(2)").tag(), Some(0));
+        assert_eq!(execute("Square(3)").tag(), Some(1));
+        assert_eq!(execute("Empty()").tag(), Some(2));
+        assert_eq!(execute("Empty()").ty().unwrap().name(), "Shape");
+    }
+
+    #[test]
+    fn t_generic_variant() {
+        let option = execute("Some(12)");
+
+        assert_eq!(option.ty().unwrap().name(), "Option<int>");
+        assert_eq!(option.tag(), Some(0));
+    }
+}
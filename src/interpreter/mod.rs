@@ -9,13 +9,16 @@ use std::path::PathBuf;
 use colored::Colorize;
 
 mod scope_map;
-use scope_map::ScopeMap;
+pub use scope_map::ScopeMap;
+
+mod test_runner;
 
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
+use crate::builtins::{BuiltinFn, Builtins, InPlaceBuiltinFn};
 use crate::instruction::{Block, FunctionDec, FunctionKind, Instruction, TypeDec, TypeId, Var};
-use crate::{JkErrKind, JkError};
+use crate::{JkErrKind, JkError, TypeCheck, TypeCtx};
 
 /// Type the interpreter uses for keys
 type IKey = String;
@@ -41,13 +44,22 @@ pub struct Interpreter {
     path: Option<PathBuf>,
 
     /// Contains the scopes of the interpreter, in which are variables and functions
-    scope_map: ScopeMap,
+    scope_map: ScopeMap<Var, FunctionDec, TypeDec>,
 
     /// Tests registered in the interpreter
     tests: HashMap<IKey, FunctionDec>,
 
+    /// Mocks registered in the interpreter, replacing functions while tests run
+    mocks: HashMap<IKey, Rc<FunctionDec>>,
+
+    /// Is the interpreter running a test or not
+    in_test: bool,
+
     /// Sources included by the interpreter
     included: HashSet<PathBuf>,
+
+    /// Implementation of the builtin functions
+    builtins: Builtins,
 }
 
 impl Interpreter {
@@ -69,7 +81,10 @@ impl Interpreter {
             path: None,
             scope_map: ScopeMap::new(),
             tests: HashMap::new(),
+            mocks: HashMap::new(),
+            in_test: false,
             included: HashSet::new(),
+            builtins: Builtins::new(),
         };
 
         i.scope_enter();
@@ -79,6 +94,15 @@ impl Interpreter {
             .iter()
             .for_each(|ty_name| i.add_type(TypeDec::from(*ty_name)).unwrap());
 
+        // Builtins are declared like regular types and functions, so that they can be
+        // used and typechecked the same way
+        Builtins::types()
+            .into_iter()
+            .for_each(|ty| i.add_type(ty).unwrap());
+        Builtins::declarations()
+            .into_iter()
+            .for_each(|function| i.add_function(function).unwrap());
+
         i
     }
 
@@ -107,32 +131,44 @@ impl Interpreter {
     /// Add a function to the interpreter. Returns `Ok` if the function was added, `Err`
     /// if it existed already and was not.
     pub fn add_function(&mut self, function: FunctionDec) -> Result<(), JkError> {
-        self.scope_map.add_function(function)
+        self.scope_map
+            .add_function(function.name().to_owned(), function)
     }
 
     /// Add a variable to the interpreter. Returns `Ok` if the variable was added, `Err`
     /// if it existed already and was not.
     pub fn add_variable(&mut self, var: Var) -> Result<(), JkError> {
-        self.scope_map.add_variable(var)
+        self.scope_map.add_variable(var.name().to_owned(), var)
     }
 
     /// Add a type to the interpreter. Returns `Ok` if the type was added, `Err`
     /// if it existed already and was not.
     pub fn add_type(&mut self, custom_type: TypeDec) -> Result<(), JkError> {
-        self.scope_map.add_type(custom_type)
+        self.scope_map
+            .add_type(custom_type.name().to_owned(), custom_type)
+    }
+
+    /// Make a generic parameter stand for an existing type in the current scope
+    pub fn add_type_alias(&mut self, alias: &str, type_dec: &TypeDec) -> Result<(), JkError> {
+        self.scope_map.add_type(alias.to_owned(), type_dec.clone())
     }
 
     /// Remove a variable from the interpreter
     pub fn remove_variable(&mut self, var: &Var) -> Result<(), JkError> {
-        self.scope_map.remove_variable(var)
+        self.scope_map.remove_variable(var.name())
     }
 
-    /// Replace a variable or create it if it does not exist
+    /// Replace a variable or create it if it does not exist. An existing variable is
+    /// replaced in the scope it was declared in, even if it is not the current one, as
+    /// long as it was declared in the current function call
     pub fn replace_variable(&mut self, var: Var) -> Result<(), JkError> {
-        // Remove the variable if it exists
-        let _ = self.remove_variable(&var);
-
-        self.add_variable(var)
+        match self.scope_map.get_variable_mut(var.name()) {
+            Some(existing) => {
+                *existing = var;
+                Ok(())
+            }
+            None => self.add_variable(var),
+        }
     }
 
     /// Get a mutable reference on an existing function
@@ -145,9 +181,20 @@ impl Interpreter {
         self.scope_map.get_variable(name)
     }
 
-    /// Get a reference on an existing type
-    pub fn get_type(&self, type_id: &TypeId) -> Option<&Rc<TypeDec>> {
-        self.scope_map.get_type(type_id.id())
+    /// Get an existing type. Generic types are monomorphised on the fly
+    pub fn get_type(&self, type_id: &TypeId) -> Option<Rc<TypeDec>> {
+        self.scope_map.get_type_id(type_id)
+    }
+
+    /// Get the implementation of a builtin function
+    pub fn get_builtin(&self, name: &str) -> Option<BuiltinFn> {
+        self.builtins.get(name)
+    }
+
+    /// Get the implementation of a builtin function modifying its first argument in
+    /// place
+    pub fn get_in_place_builtin(&self, name: &str) -> Option<InPlaceBuiltinFn> {
+        self.builtins.get_in_place(name)
     }
 
     /// Create a new empty scope
@@ -155,6 +202,12 @@ impl Interpreter {
         self.scope_map.scope_enter()
     }
 
+    /// Create a new empty scope for a function call. Assignments made inside of it
+    /// never modify the variables of the caller
+    pub fn function_scope_enter(&mut self) {
+        self.scope_map.function_scope_enter()
+    }
+
     /// Exit the latest created scope
     pub fn scope_exit(&mut self) {
         self.scope_map.scope_exit()
@@ -217,6 +270,46 @@ impl Interpreter {
         }
     }
 
+    /// Register a mock, which replaces the function of the same name while tests run
+    pub fn add_mock(&mut self, mock: FunctionDec) -> Result<(), JkError> {
+        match self.mocks.get(mock.name()) {
+            Some(mock) => Err(JkError::new(
+                JkErrKind::Interpreter,
+                format!("mock function already declared: {}", mock.name()),
+                mock.location().cloned(),
+                mock.name().to_owned(),
+            )),
+            None => {
+                self.mocks.insert(mock.name().to_owned(), Rc::new(mock));
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the mock replacing a function, if a test is running. Outside of tests,
+    /// functions are never mocked
+    pub fn get_mock(&self, name: &str) -> Option<&Rc<FunctionDec>> {
+        match self.in_test {
+            true => self.mocks.get(name),
+            false => None,
+        }
+    }
+
+    /// Typecheck the program contained in the interpreter, before executing it. All
+    /// the errors found are returned
+    pub fn typecheck(&self) -> Result<(), Vec<JkError>> {
+        let mut ctx = TypeCtx::new();
+        ctx.set_path(self.path.clone());
+
+        // The entry point is not a regular function: Its block is checked as is, since
+        // it can return any type
+        if let Some(block) = self.entry_point.block() {
+            block.resolve_type(&mut ctx);
+        }
+
+        ctx.finish()
+    }
+
     /// Check if a source is included or not
     pub fn is_included(&self, source: &PathBuf) -> bool {
         self.included.contains(source)
@@ -242,6 +335,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn t_mocks_only_in_tests() {
+        let mut i = Interpreter::new();
+
+        assert_eq!(i.add_mock(FunctionDec::new("f0".to_owned(), None)), Ok(()));
+        assert!(i.get_mock("f0").is_none());
+
+        i.in_test = true;
+        assert!(i.get_mock("f0").is_some());
+    }
+
+    #[test]
+    fn t_redefinition_of_mock() {
+        let mut i = Interpreter::new();
+
+        assert_eq!(i.add_mock(FunctionDec::new("f0".to_owned(), None)), Ok(()));
+        assert!(i.add_mock(FunctionDec::new("f0".to_owned(), None)).is_err());
+    }
+
     #[test]
     fn t_redefinition_of_variable() {
         let v0 = Var::new("v0".to_owned());
@@ -3,107 +3,122 @@
 //! In order to access variables and functions, the scope map first looks in the current
 //! scope. If the specified name cannot be found, it searches the other scopes, defined
 //! before the current one, until it finds the correct component.
+//!
+//! The scope map is generic over what it stores, since both the interpreter and the
+//! typechecker need to keep track of scopes. The interpreter stores variables, while
+//! the typechecker only cares about the types of said variables.
 
 use std::collections::{HashMap, LinkedList};
 use std::rc::Rc;
 
-use crate::instruction::{FunctionDec, TypeDec, Var};
+use crate::instruction::{TypeDec, TypeId};
 use crate::{Instruction, JkErrKind, JkError};
 
 /// A scope contains a set of available variables and functions
 #[derive(Clone)]
-struct Scope {
-    variables: HashMap<String, Var>,
-    functions: HashMap<String, Rc<FunctionDec>>,
-    types: HashMap<String, Rc<TypeDec>>,
+struct Scope<V, F, T> {
+    variables: HashMap<String, V>,
+    functions: HashMap<String, Rc<F>>,
+    types: HashMap<String, Rc<T>>,
+
+    /// Is the scope the outermost scope of a function call
+    function: bool,
 }
 
-impl Scope {
+impl<V, F, T> Scope<V, F, T> {
     /// Create a new empty Scope
-    pub fn new() -> Scope {
+    pub fn new() -> Scope<V, F, T> {
         Scope {
             variables: HashMap::new(),
             functions: HashMap::new(),
             types: HashMap::new(),
+            function: false,
         }
     }
 
     /// Get a reference on a variable from the scope map if is has been inserted already
-    pub fn get_variable(&self, name: &str) -> Option<&Var> {
+    pub fn get_variable(&self, name: &str) -> Option<&V> {
         self.variables.get(name)
     }
 
+    /// Get a mutable reference on a variable from the scope map if is has been inserted
+    /// already
+    pub fn get_variable_mut(&mut self, name: &str) -> Option<&mut V> {
+        self.variables.get_mut(name)
+    }
+
     /// Get a reference on a function from the scope map if is has been inserted already
-    pub fn get_function(&self, name: &str) -> Option<&Rc<FunctionDec>> {
+    pub fn get_function(&self, name: &str) -> Option<&Rc<F>> {
         self.functions.get(name)
     }
 
     /// Get a reference on a type from the scope map if is has been inserted already
-    pub fn get_type(&self, name: &str) -> Option<&Rc<TypeDec>> {
+    pub fn get_type(&self, name: &str) -> Option<&Rc<T>> {
         self.types.get(name)
     }
 
     /// Add a variable to the most recently created scope, if it doesn't already exist
-    pub fn add_variable(&mut self, var: Var) -> Result<(), JkError> {
-        match self.get_variable(var.name()) {
+    pub fn add_variable(&mut self, name: String, var: V) -> Result<(), JkError> {
+        match self.get_variable(&name) {
             Some(_) => Err(JkError::new(
                 JkErrKind::Interpreter,
-                format!("variable already declared: {}", var.name()),
+                format!("variable already declared: {}", name),
                 None,
-                var.name().to_owned(),
+                name,
             )),
             None => Ok({
-                self.variables.insert(var.name().to_owned(), var);
+                self.variables.insert(name, var);
             }),
         }
     }
 
     /// Remove a variable from the most recently created scope, if it exists
-    pub fn remove_variable(&mut self, var: &Var) -> Result<(), JkError> {
-        match self.get_variable(var.name()) {
+    pub fn remove_variable(&mut self, name: &str) -> Result<(), JkError> {
+        match self.get_variable(name) {
             Some(_) => Ok({
-                self.variables.remove(var.name()).unwrap();
+                self.variables.remove(name).unwrap();
             }),
             None => Err(JkError::new(
                 JkErrKind::Interpreter,
-                format!("variable does not exist: {}", var.name()),
+                format!("variable does not exist: {}", name),
                 None,
-                var.name().to_owned(),
+                name.to_owned(),
             )),
         }
     }
 
     /// Add a variable to the most recently created scope, if it doesn't already exist
-    pub fn add_function(&mut self, func: FunctionDec) -> Result<(), JkError> {
-        match self.get_function(func.name()) {
+    pub fn add_function(&mut self, name: String, func: F) -> Result<(), JkError> {
+        match self.get_function(&name) {
             Some(_) => Err(JkError::new(
                 JkErrKind::Interpreter,
-                format!("function already declared: {}", func.name()),
+                format!("function already declared: {}", name),
                 None,
-                func.name().to_owned(),
+                name,
             )),
             None => Ok({
-                self.functions.insert(func.name().to_owned(), Rc::new(func));
+                self.functions.insert(name, Rc::new(func));
             }),
         }
     }
 
     /// Add a type to the most recently created scope, if it doesn't already exist
-    pub fn add_type(&mut self, type_dec: TypeDec) -> Result<(), JkError> {
-        match self.get_type(type_dec.name()) {
+    pub fn add_type(&mut self, name: String, type_dec: T) -> Result<(), JkError> {
+        match self.get_type(&name) {
             Some(_) => Err(JkError::new(
                 JkErrKind::Interpreter,
-                format!("type already declared: {}", type_dec.name()),
+                format!("type already declared: {}", name),
                 None,
-                type_dec.name().to_owned(),
+                name,
             )),
             None => Ok({
-                self.types
-                    .insert(type_dec.name().to_owned(), Rc::new(type_dec));
+                self.types.insert(name, Rc::new(type_dec));
             }),
         }
     }
+}
 
+impl<V: Instruction, F: Instruction, T> Scope<V, F, T> {
     /// Display all contained information on stdout
     pub fn print(&self) {
         for (_, var) in &self.variables {
@@ -122,13 +137,13 @@ type ScopeStack<T> = LinkedList<T>;
 /// A scope map keeps track of the currently available scopes and the current depth
 /// level.
 #[derive(Clone)]
-pub struct ScopeMap {
-    scopes: ScopeStack<Scope>,
+pub struct ScopeMap<V, F, T> {
+    scopes: ScopeStack<Scope<V, F, T>>,
 }
 
-impl ScopeMap {
+impl<V, F, T> ScopeMap<V, F, T> {
     /// Create a new empty scope map, at depth 0
-    pub fn new() -> ScopeMap {
+    pub fn new() -> ScopeMap<V, F, T> {
         ScopeMap {
             scopes: ScopeStack::new(),
         }
@@ -139,6 +154,15 @@ impl ScopeMap {
         self.scopes.push_front(Scope::new());
     }
 
+    /// Enter into a new scope for a function call. Variables of the caller cannot be
+    /// modified from inside that scope
+    pub fn function_scope_enter(&mut self) {
+        let mut scope = Scope::new();
+        scope.function = true;
+
+        self.scopes.push_front(scope);
+    }
+
     /// Exit the last added scope
     pub fn scope_exit(&mut self) {
         // We unwrap since we want the interpreter to crash in case we pop an unexisting
@@ -147,7 +171,7 @@ impl ScopeMap {
     }
 
     /// Maybe get a variable in any available scopes
-    pub fn get_variable(&self, name: &str) -> Option<&Var> {
+    pub fn get_variable(&self, name: &str) -> Option<&V> {
         // FIXME: Use find for code quality?
         for scope in self.scopes.iter() {
             match scope.get_variable(name) {
@@ -159,8 +183,27 @@ impl ScopeMap {
         None
     }
 
+    /// Maybe get a mutable reference on a variable in any available scopes. The
+    /// variable is looked up from the innermost scope to the outermost one, stopping
+    /// at the scope of the current function call
+    pub fn get_variable_mut(&mut self, name: &str) -> Option<&mut V> {
+        for scope in self.scopes.iter_mut() {
+            let boundary = scope.function;
+
+            if let Some(var) = scope.get_variable_mut(name) {
+                return Some(var);
+            }
+
+            if boundary {
+                break;
+            }
+        }
+
+        None
+    }
+
     /// Maybe get a function in any available scopes
-    pub fn get_function(&self, name: &str) -> Option<&Rc<FunctionDec>> {
+    pub fn get_function(&self, name: &str) -> Option<&Rc<F>> {
         // FIXME: Use find for code quality?
         for scope in self.scopes.iter() {
             match scope.get_function(name) {
@@ -173,7 +216,7 @@ impl ScopeMap {
     }
 
     /// Maybe get a type in any available scopes
-    pub fn get_type(&self, name: &str) -> Option<&Rc<TypeDec>> {
+    pub fn get_type(&self, name: &str) -> Option<&Rc<T>> {
         // FIXME: Use find for code quality?
         for scope in self.scopes.iter() {
             match scope.get_type(name) {
@@ -186,57 +229,81 @@ impl ScopeMap {
     }
 
     /// Add a variable to the current scope if it hasn't been added before
-    pub fn add_variable(&mut self, var: Var) -> Result<(), JkError> {
+    pub fn add_variable(&mut self, name: String, var: V) -> Result<(), JkError> {
         match self.scopes.front_mut() {
-            Some(head) => head.add_variable(var),
+            Some(head) => head.add_variable(name, var),
             None => Err(JkError::new(
                 JkErrKind::Interpreter,
                 String::from("Adding variable to empty scopemap"),
                 None,
-                var.name().to_owned(),
+                name,
             )),
         }
     }
 
     /// Remove a variable from the current scope if it hasn't been added before
-    pub fn remove_variable(&mut self, var: &Var) -> Result<(), JkError> {
+    pub fn remove_variable(&mut self, name: &str) -> Result<(), JkError> {
         match self.scopes.front_mut() {
-            Some(head) => head.remove_variable(var),
+            Some(head) => head.remove_variable(name),
             None => Err(JkError::new(
                 JkErrKind::Interpreter,
                 String::from("Removing variable from empty scopemap"),
                 None,
-                var.name().to_owned(),
+                name.to_owned(),
             )),
         }
     }
 
     /// Add a function to the current scope if it hasn't been added before
-    pub fn add_function(&mut self, func: FunctionDec) -> Result<(), JkError> {
+    pub fn add_function(&mut self, name: String, func: F) -> Result<(), JkError> {
         match self.scopes.front_mut() {
-            Some(head) => head.add_function(func),
+            Some(head) => head.add_function(name, func),
             None => Err(JkError::new(
                 JkErrKind::Interpreter,
                 String::from("Adding function to empty scopemap"),
                 None,
-                func.name().to_owned(),
+                name,
             )),
         }
     }
 
     /// Add a type to the current scope if it hasn't been added before
-    pub fn add_type(&mut self, custom_type: TypeDec) -> Result<(), JkError> {
+    pub fn add_type(&mut self, name: String, custom_type: T) -> Result<(), JkError> {
         match self.scopes.front_mut() {
-            Some(head) => head.add_type(custom_type),
+            Some(head) => head.add_type(name, custom_type),
             None => Err(JkError::new(
                 JkErrKind::Interpreter,
                 String::from("Adding new custom type to empty scopemap"),
                 None,
-                custom_type.name().to_owned(),
+                name,
             )),
         }
     }
+}
+
+impl<V, F> ScopeMap<V, F, TypeDec> {
+    /// Maybe get a type in any available scopes. Generic types are monomorphised
+    /// using their generic arguments, which are looked up first. Generic types used
+    /// without the right amount of generic arguments cannot be found
+    pub fn get_type_id(&self, type_id: &TypeId) -> Option<Rc<TypeDec>> {
+        let type_dec = self.get_type(type_id.base())?;
+        type_dec.check_generics(type_id).ok()?;
 
+        if type_id.generics().is_empty() {
+            return Some(type_dec.clone());
+        }
+
+        let generics = type_id
+            .generics()
+            .iter()
+            .map(|generic| self.get_type_id(generic).map(|dec| dec.type_id()))
+            .collect::<Option<Vec<TypeId>>>()?;
+
+        Some(Rc::new(type_dec.monomorphise(&generics)))
+    }
+}
+
+impl<V: Instruction, F: Instruction, T> ScopeMap<V, F, T> {
     /// Display all contained information on stdout
     pub fn print(&self) {
         for stack in &self.scopes {
@@ -248,11 +315,14 @@ impl ScopeMap {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::instruction::{FunctionDec, TypeDec, Var};
+
+    type InterpreterScopeMap = ScopeMap<Var, FunctionDec, TypeDec>;
 
     #[test]
     #[should_panic]
     fn t_pop_non_existent_scope() {
-        let mut s = ScopeMap::new();
+        let mut s = InterpreterScopeMap::new();
 
         s.scope_enter();
         s.scope_exit();
@@ -262,34 +332,37 @@ mod tests {
     #[test]
     #[should_panic]
     fn t_add_var_non_existent_scope() {
-        let mut s = ScopeMap::new();
+        let mut s = InterpreterScopeMap::new();
 
-        s.add_variable(Var::new("Something".to_owned())).unwrap();
+        s.add_variable("Something".to_owned(), Var::new("Something".to_owned()))
+            .unwrap();
     }
 
     #[test]
     fn t_find_non_existent_var() {
-        let s = ScopeMap::new();
+        let s = InterpreterScopeMap::new();
 
         assert!(s.get_variable("a").is_none());
     }
 
     #[test]
     fn t_add_var_and_get_it() {
-        let mut s = ScopeMap::new();
+        let mut s = InterpreterScopeMap::new();
 
         s.scope_enter();
-        s.add_variable(Var::new("a".to_owned())).unwrap();
+        s.add_variable("a".to_owned(), Var::new("a".to_owned()))
+            .unwrap();
 
         assert!(s.get_variable("a").is_some());
     }
 
     #[test]
     fn t_add_var_and_get_it_from_inner_scope() {
-        let mut s = ScopeMap::new();
+        let mut s = InterpreterScopeMap::new();
 
         s.scope_enter();
-        s.add_variable(Var::new("a".to_owned())).unwrap();
+        s.add_variable("a".to_owned(), Var::new("a".to_owned()))
+            .unwrap();
 
         s.scope_enter();
         s.scope_enter();
@@ -302,14 +375,45 @@ mod tests {
 
     #[test]
     fn t_add_var_and_get_it_from_outer_scope() {
-        let mut s = ScopeMap::new();
+        let mut s = InterpreterScopeMap::new();
 
         s.scope_enter();
 
-        s.add_variable(Var::new("a".to_owned())).unwrap();
+        s.add_variable("a".to_owned(), Var::new("a".to_owned()))
+            .unwrap();
 
         s.scope_exit();
 
         assert!(s.get_variable("a").is_none());
     }
+
+    #[test]
+    fn t_get_var_mut_from_inner_scope() {
+        let mut s = InterpreterScopeMap::new();
+
+        s.scope_enter();
+        s.add_variable("a".to_owned(), Var::new("a".to_owned()))
+            .unwrap();
+
+        s.scope_enter();
+        s.get_variable_mut("a").unwrap().set_mutable(true);
+        s.scope_exit();
+
+        assert!(s.get_variable("a").unwrap().mutable());
+    }
+
+    #[test]
+    fn t_get_var_mut_stops_at_function_scope() {
+        let mut s = InterpreterScopeMap::new();
+
+        s.scope_enter();
+        s.add_variable("a".to_owned(), Var::new("a".to_owned()))
+            .unwrap();
+
+        s.function_scope_enter();
+        s.scope_enter();
+
+        assert!(s.get_variable("a").is_some());
+        assert!(s.get_variable_mut("a").is_none());
+    }
 }
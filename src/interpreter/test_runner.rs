@@ -0,0 +1,212 @@
+//! The test runner executes the `test` functions registered in an interpreter. Each
+//! test runs in a fresh scope, so that the variables it creates are not visible to
+//! the other tests. A test passes if its block executes without error. While a test
+//! runs, calls to mocked functions are resolved to their mock.
+
+use colored::Colorize;
+
+use super::Interpreter;
+use crate::error::ControlFlow;
+use crate::instruction::{Block, Destructure, FunctionDec, Incl, VarAssign};
+use crate::JkError;
+
+/// Outcome of running the tests of an interpreter
+pub struct TestReport {
+    /// Name of each test which was run, along with its error if it failed
+    results: Vec<(String, Option<JkError>)>,
+
+    /// Amount of tests which did not match the filter
+    filtered_out: usize,
+}
+
+impl TestReport {
+    /// Amount of tests which passed
+    pub fn passed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, error)| error.is_none())
+            .count()
+    }
+
+    /// Amount of tests which failed
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+
+    /// Did all the tests which were run pass
+    pub fn success(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// Display the result of each test, the errors of the failed ones on stderr, and a
+    /// summary of the run
+    pub fn print(&self) {
+        let plural = if self.results.len() == 1 { "" } else { "s" };
+        println!("running {} test{}", self.results.len(), plural);
+
+        for (name, error) in self.results.iter() {
+            match error {
+                None => println!("test {} ... {}", name, "ok".green()),
+                Some(e) => {
+                    println!("test {} ... {}", name, "FAILED".red());
+                    eprintln!("---- {} ----\n{}\n", name, e);
+                }
+            }
+        }
+
+        let status = match self.success() {
+            true => "ok".green(),
+            false => "FAILED".red(),
+        };
+
+        println!(
+            "\ntest result: {}. {} passed; {} failed; {} filtered out",
+            status,
+            self.passed(),
+            self.failed(),
+            self.filtered_out
+        );
+    }
+}
+
+impl Interpreter {
+    /// Execute the declarations of the entry point, without executing the rest of the
+    /// program. This registers the functions, types, variables and tests the tests may
+    /// use. Functions and types are hoisted like in any block, and variables are then
+    /// declared by evaluating their value, in order
+    pub fn declare(&mut self) -> Result<(), JkError> {
+        let ep = self.entry_point.block().unwrap().clone();
+        let instructions = || ep.instructions().iter().chain(ep.last());
+
+        for instruction in instructions().filter(|inst| Block::is_hoisted(inst.as_ref())) {
+            instruction.execute(self)?;
+        }
+
+        for instruction in instructions() {
+            let is_declaration = instruction.downcast_ref::<Incl>().is_some()
+                || instruction.downcast_ref::<VarAssign>().is_some()
+                || instruction.downcast_ref::<Destructure>().is_some();
+
+            if is_declaration {
+                instruction.execute(self)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Run a single test in a fresh scope. Returning early from a test is not a
+    /// failure
+    pub fn run_test(&mut self, test: &FunctionDec) -> Result<(), JkError> {
+        self.debug("TEST", test.name());
+
+        self.in_test = true;
+        self.function_scope_enter();
+        let result = test.run(self);
+        self.scope_exit();
+        self.in_test = false;
+
+        match result {
+            Err(e) if !matches!(e.signal(), Some(ControlFlow::Return(_))) => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Run the registered tests whose name contains `filter`, or all of them if no
+    /// filter is given. Tests are run in alphabetical order
+    pub fn run_tests(&mut self, filter: Option<&str>) -> TestReport {
+        let mut tests: Vec<FunctionDec> = self.tests.values().cloned().collect();
+        tests.sort_by(|lhs, rhs| lhs.name().cmp(rhs.name()));
+
+        let total = tests.len();
+        let tests: Vec<FunctionDec> = tests
+            .into_iter()
+            .filter(|test| match filter {
+                Some(filter) => test.name().contains(filter),
+                None => true,
+            })
+            .collect();
+
+        let filtered_out = total - tests.len();
+        let results = tests
+            .iter()
+            .map(|test| (test.name().to_owned(), self.run_test(test).err()))
+            .collect();
+
+        TestReport {
+            results,
+            filtered_out,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+
+    fn report(input: &str, filter: Option<&str>) -> (usize, usize) {
+        let mut interpreter = Parser::parse(input).unwrap();
+        interpreter.declare().unwrap();
+
+        let report = interpreter.run_tests(filter);
+
+        (report.passed(), report.failed())
+    }
+
+    #[test]
+    fn t_run_tests() {
+        let input = "func one() -> int { 1 }
+            test passes() { one() }
+            test fails() { [1][one()] }
+            test returns() { return; }";
+
+        assert_eq!(report(input, None), (2, 1));
+    }
+
+    #[test]
+    fn t_run_tests_filtered() {
+        let input = "test a_passes() { } test b_fails() { [1][1] }";
+
+        assert_eq!(report(input, Some("a_")), (1, 0));
+        assert_eq!(report(input, Some("c_")), (0, 0));
+    }
+
+    #[test]
+    fn t_tests_use_fresh_scope() {
+        let input = "test first() { x = 1 } test second() { x = 2 }";
+
+        assert_eq!(report(input, None), (2, 0));
+    }
+
+    #[test]
+    fn t_mocks_replace_functions() {
+        let input = "func zero() -> int { 0 }
+            mock zero() -> int { 1 }
+            test mocked() { [1][zero()] }";
+
+        assert_eq!(report(input, None), (0, 1));
+    }
+
+    #[test]
+    fn t_mock_wrong_signature() {
+        let input = "func zero() -> int { 0 }
+            mock zero(x: int) -> int { x }
+            test mocked() { zero() }";
+
+        assert_eq!(report(input, None), (0, 1));
+    }
+
+    #[test]
+    fn t_tests_use_global_variables() {
+        let input = "limit = 3; Some(value) = Some(2); test below() { [1, 2, 3][limit - value] }";
+
+        assert_eq!(report(input, None), (1, 0));
+    }
+
+    #[test]
+    fn t_program_not_executed() {
+        let input = "test passes() { } [1][1]";
+
+        assert_eq!(report(input, None), (1, 0));
+    }
+}
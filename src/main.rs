@@ -1,23 +1,25 @@
 #[warn(missing_docs)]
 mod args;
+mod builtins;
 mod error;
 mod instance;
 mod instruction;
 mod interpreter;
 mod parser;
 mod repl;
+mod typechecker;
 mod utils;
 mod value;
 
 use args::Args;
 use parser::Parser;
 use repl::Repl;
-use std::fs;
 
-pub use error::{JkErrKind, JkError};
+pub use error::{ControlFlow, JkErrKind, JkErrSpaceLocation, JkError};
 pub use instance::{FromObjectInstance, ObjectInstance, ToObjectInstance};
 pub use instruction::{InstrKind, Instruction, Rename};
 pub use interpreter::Interpreter;
+pub use typechecker::{CheckedType, TypeCheck, TypeCtx};
 pub use value::{JkBool, JkChar, JkConstant, JkFloat, JkInt, JkString, Value};
 
 fn handle_exit_code(result: InstrKind) {
@@ -59,14 +61,30 @@ fn main() {
     // We can unwrap since we checked for `None` in the if
     let path = args.input().unwrap();
 
-    let input = fs::read_to_string(&path).unwrap();
-
-    // FIXME: No unwrap()
-    let mut interpreter = Parser::parse(&input).unwrap();
+    let mut interpreter = match Parser::parse_file(path) {
+        Ok(interpreter) => interpreter,
+        Err(errors) => JkError::exit_many(&errors),
+    };
 
-    interpreter.set_path(Some(path.to_owned()));
     interpreter.set_debug(args.debug());
 
+    // A program containing type errors is never executed
+    if let Err(errors) = interpreter.typecheck() {
+        JkError::exit_many(&errors);
+    }
+
+    // In test mode, the program only declares what the tests need
+    if args.test() {
+        if let Err(e) = interpreter.declare() {
+            e.exit();
+        }
+
+        let report = interpreter.run_tests(args.filter());
+        report.print();
+
+        std::process::exit(if report.success() { 0 } else { 1 });
+    }
+
     // The entry point always has a block
     let ep = interpreter.entry_point.block().unwrap().clone();
     match ep.execute(&mut interpreter) {
@@ -12,7 +12,10 @@
 //! wrap the return value of `Construct::function_call` and `Construct::block` in a box,
 //! allowing to use them simultaneously when parsing multiple types of constructs.
 
-use crate::{parser::Construct, Instruction};
+use crate::{
+    parser::{Construct, Parser},
+    Instruction,
+};
 
 macro_rules! box_construct {
     ($func:ident) => {
@@ -25,12 +28,16 @@ macro_rules! box_construct {
 pub struct BoxConstruct;
 
 impl BoxConstruct {
-    /// Call a `Construct` and box the return value
+    /// Call a `Construct` and box the return value. The boxed instruction is located
+    /// on the input consumed by the construct
     fn new<T: 'static + Instruction>(
         input: &str,
         construct: Box<dyn FnOnce(&str) -> nom::IResult<&str, T>>,
     ) -> nom::IResult<&str, Box<dyn Instruction>> {
-        let (input, value) = construct(input)?;
+        let start = input;
+        let (input, mut value) = construct(input)?;
+
+        value.set_location(Parser::location(start, input));
 
         Ok((input, Box::new(value)))
     }
@@ -41,14 +48,21 @@ impl BoxConstruct {
     box_construct! {ext_declaration}
     box_construct! {variable}
     box_construct! {block}
+    box_construct! {match_expr}
+    box_construct! {string_interpolation}
+    box_construct! {destructure}
     box_construct! {jinko_inst}
     box_construct! {any_loop}
+    box_construct! {loop_control}
+    box_construct! {return_statement}
     box_construct! {var_assignment}
+    box_construct! {field_assignment}
     box_construct! {if_else}
     box_construct! {type_declaration}
     box_construct! {audit}
     box_construct! {test_declaration}
     box_construct! {mock_declaration}
     box_construct! {incl}
-    box_construct! {method_call}
+    box_construct! {array_literal}
+    box_construct! {map_literal}
 }
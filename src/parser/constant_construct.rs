@@ -1,7 +1,7 @@
 //! The ConstantConstruct module deals with parsing constant values in jinko source code.
-//! This is limited to booleans, integers, floats, characters and strings. Their
-//! only purpose is to wrap the lexing/parsing of tokens and box the result, for it
-//! to be used by high level Construct functions. It is very similar to BoxConstruct in
+//! This is limited to booleans, integers, floats, characters and strings without
+//! interpolated expressions. Their only purpose is to wrap the lexing/parsing of
+//! tokens and box the result, for it to be used by high level Construct functions. It is very similar to BoxConstruct in
 //! that sense.
 
 use crate::instruction::Instruction;
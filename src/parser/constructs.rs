@@ -13,13 +13,20 @@
 //!
 //! is the grammar for a variable assignment.
 
-use nom::{branch::alt, combinator::opt, multi::many0, IResult};
+use nom::{
+    branch::alt,
+    combinator::{cut, opt},
+    multi::{many0, many1},
+    IResult,
+};
 
 use crate::instruction::{
-    Audit, Block, DecArg, FunctionCall, FunctionDec, FunctionKind, IfElse, Incl, Instruction,
-    JkInst, Loop, LoopKind, MethodCall, TypeDec, TypeId, TypeInstantiation, Var, VarAssign,
+    ArrayIndex, ArrayLiteral, Audit, Block, DecArg, Destructure, FieldAccess, FieldAssign,
+    FunctionCall, FunctionDec, FunctionKind, IfElse, Incl, Instruction, JkInst, Loop, LoopControl,
+    LoopControlKind, LoopKind, MapEntry, MapLiteral, Match, MatchArm, MethodCall, Pattern, Return,
+    StringInterpolation, StringPart, TypeDec, TypeId, TypeInstantiation, Var, VarAssign, Variant,
 };
-use crate::parser::{BoxConstruct, ConstantConstruct, ShuntingYard, Token};
+use crate::parser::{BoxConstruct, ConstantConstruct, Parser, ShuntingYard, Token};
 
 type ParseResult<'i, T> = IResult<&'i str, T>;
 
@@ -35,20 +42,24 @@ impl Construct {
         // FIXME: We need to parse the remaining input after a correct instruction
         // has been parsed
         let (input, value) = alt((
-            Construct::binary_op,
-            BoxConstruct::method_call,
-            BoxConstruct::function_declaration,
-            BoxConstruct::type_declaration,
-            BoxConstruct::ext_declaration,
-            BoxConstruct::test_declaration,
-            BoxConstruct::mock_declaration,
+            Construct::assignment,
+            // Generic type instantiations must not be mistaken for comparisons
             BoxConstruct::type_instantiation,
+            Construct::binary_op,
+            Construct::member_access,
+            Construct::declaration,
+            BoxConstruct::array_literal,
             BoxConstruct::function_call,
             BoxConstruct::incl,
             BoxConstruct::if_else,
+            BoxConstruct::match_expr,
             BoxConstruct::any_loop,
+            BoxConstruct::loop_control,
+            BoxConstruct::return_statement,
             BoxConstruct::jinko_inst,
             BoxConstruct::audit,
+            // Map literals must not be mistaken for blocks
+            BoxConstruct::map_literal,
             BoxConstruct::block,
             BoxConstruct::var_assignment,
             BoxConstruct::variable,
@@ -60,6 +71,23 @@ impl Construct {
         Ok((input, value))
     }
 
+    /// Parse an assignment to something else than a variable: one of the fields of
+    /// a variable, or a pattern
+    fn assignment(input: &str) -> ParseResult<Box<dyn Instruction>> {
+        alt((BoxConstruct::field_assignment, BoxConstruct::destructure))(input)
+    }
+
+    /// Parse any declaration: functions, types, tests and mocks
+    fn declaration(input: &str) -> ParseResult<Box<dyn Instruction>> {
+        alt((
+            BoxConstruct::function_declaration,
+            BoxConstruct::type_declaration,
+            BoxConstruct::ext_declaration,
+            BoxConstruct::test_declaration,
+            BoxConstruct::mock_declaration,
+        ))(input)
+    }
+
     /// Parse an instruction and maybe the semicolon that follows.
     ///
     /// `<instruction> [ ; ]`
@@ -70,35 +98,139 @@ impl Construct {
         Ok((input, expr))
     }
 
-    /// Parse as many instructions as possible
-    pub fn many_instructions(input: &str) -> ParseResult<Vec<Box<dyn Instruction>>> {
-        many0(Construct::instruction_maybe_semicolon)(input)
-    }
-
     /// Constants are raw values in the source code. For example, `"string"`, `12` and
     /// `0.5`.
     ///
+    /// Strings containing interpolated expressions are parsed as well.
+    ///
     /// `'<any_char>' | "<any_char>*" | <num>? | <num>?.<num>?`
     pub(crate) fn constant(input: &str) -> ParseResult<Box<dyn Instruction>> {
-        alt((
+        let start = input;
+        let (input, mut constant) = alt((
             ConstantConstruct::char_constant,
+            // Strings without any interpolation are constants
+            BoxConstruct::string_interpolation,
             ConstantConstruct::string_constant,
             ConstantConstruct::float_constant,
             ConstantConstruct::int_constant,
             ConstantConstruct::bool_constant,
-        ))(input)
+        ))(input)?;
+
+        constant.set_location(Parser::location(start, input));
+
+        Ok((input, constant))
+    }
+
+    /// Parse an expression interpolated in a string
+    ///
+    /// `{ <instruction> }`
+    fn interpolated(input: &str) -> ParseResult<StringPart> {
+        let (input, _) = Token::left_curly_bracket(input)?;
+        let (input, expression) = Construct::instruction(input)?;
+        let (input, _) = Token::right_curly_bracket(input)?;
+
+        Ok((input, StringPart::Expression(expression)))
+    }
+
+    /// Parse a part of an interpolated string: Either characters or an interpolated
+    /// expression
+    fn string_part(input: &str) -> ParseResult<StringPart> {
+        if let Ok((input, literal)) = Token::string_chars(input) {
+            return Ok((input, StringPart::Literal(literal)));
+        }
+
+        Construct::interpolated(input)
+    }
+
+    /// Parse a string containing at least one interpolated expression. The expressions
+    /// are evaluated and concatenated with the rest of the string on execution
+    ///
+    /// `" [ <string_chars> | { <instruction> } ]* "`
+    pub(crate) fn string_interpolation(input: &str) -> ParseResult<StringInterpolation> {
+        let (input, _) = Token::double_quote(input)?;
+        let (input, parts) = many0(Construct::string_part)(input)?;
+        let (input, _) = Token::double_quote(input)?;
+
+        match parts
+            .iter()
+            .any(|part| matches!(part, StringPart::Expression(_)))
+        {
+            true => Ok((input, StringInterpolation::new(parts))),
+            false => Err(nom::Err::Error((input, nom::error::ErrorKind::Many1))),
+        }
+    }
+
+    /// Parse a type and the generic arguments that follow it, if any, or an array type
+    ///
+    /// `<identifier> [ < <type_id> [ , <type_id> ]* > ] | [ <type_id> ]`
+    pub(crate) fn type_id(input: &str) -> ParseResult<TypeId> {
+        if let Ok((input, _)) = Token::left_bracket(input) {
+            let (input, _) = Token::maybe_consume_extra(input)?;
+            let (input, element) = Construct::type_id(input)?;
+            let (input, _) = Token::maybe_consume_extra(input)?;
+            let (input, _) = Token::right_bracket(input)?;
+
+            return Ok((input, TypeId::array(element)));
+        }
+
+        let (input, id) = Token::identifier(input)?;
+        let (input, generics) = opt(Construct::generic_list)(input)?;
+
+        Ok((input, TypeId::generic(id, generics.unwrap_or_default())))
+    }
+
+    /// Parse a type and the comma that follows it
+    fn type_id_comma(input: &str) -> ParseResult<TypeId> {
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, ty) = Construct::type_id(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, _) = Token::comma(input)?;
+
+        Ok((input, ty))
+    }
+
+    /// Parse a list of generic types. The list must directly follow the identifier it
+    /// applies to, so that `a < b` is not mistaken for the start of a list
+    ///
+    /// `< <type_id> [ , <type_id> ]* >`
+    fn generic_list(input: &str) -> ParseResult<Vec<TypeId>> {
+        let (input, _) = Token::lower_than(input)?;
+        let (input, mut generics) = many0(Construct::type_id_comma)(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        // Parse the last type, which does not have a comma
+        let (input, last) = Construct::type_id(input)?;
+        generics.push(last);
+
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, _) = Token::greater_than(input)?;
+
+        Ok((input, generics))
+    }
+
+    /// Parse the name of a called function, as well as its generic arguments
+    ///
+    /// `<identifier> [ <generic_list> ]`
+    fn function_call_name(input: &str) -> ParseResult<FunctionCall> {
+        let (input, fn_id) = Token::identifier(input)?;
+        let (input, generics) = opt(Construct::generic_list)(input)?;
+
+        let mut fn_call = FunctionCall::new(fn_id);
+        fn_call.set_generics(generics.unwrap_or_default());
+
+        Ok((input, fn_call))
     }
 
     /// Parse a function call with no arguments
     ///
-    /// `<identifier> ( )`
+    /// `<identifier> [ <generic_list> ] ( )`
     fn function_call_no_args(input: &str) -> ParseResult<FunctionCall> {
-        let (input, fn_id) = Token::identifier(input)?;
+        let (input, fn_call) = Construct::function_call_name(input)?;
         let (input, _) = Token::left_parenthesis(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, _) = Token::right_parenthesis(input)?;
 
-        Ok((input, FunctionCall::new(fn_id.to_owned())))
+        Ok((input, fn_call))
     }
 
     /// Parse an argument given to a function. Consumes the whitespaces before and after
@@ -137,11 +269,9 @@ impl Construct {
 
     /// Parse a function call with arguments
     fn function_call_args(input: &str) -> ParseResult<FunctionCall> {
-        let (input, fn_id) = Token::identifier(input)?;
+        let (input, mut fn_call) = Construct::function_call_name(input)?;
         let (input, _) = Token::left_parenthesis(input)?;
 
-        let mut fn_call = FunctionCall::new(fn_id.to_owned());
-
         let (input, mut arg_vec) = Construct::args_list(input)?;
         let (input, _) = Token::right_parenthesis(input)?;
 
@@ -150,6 +280,69 @@ impl Construct {
         Ok((input, fn_call))
     }
 
+    /// Parse an array literal, containing a list of elements separated by commas
+    ///
+    /// `[ [ <instruction> [ , <instruction> ]* ] ]`
+    pub fn array_literal(input: &str) -> ParseResult<ArrayLiteral> {
+        let (input, _) = Token::left_bracket(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        if let Ok((input, _)) = Token::right_bracket(input) {
+            return Ok((input, ArrayLiteral::new(vec![])));
+        }
+
+        let (input, elements) = Construct::args_list(input)?;
+        let (input, _) = Token::right_bracket(input)?;
+
+        Ok((input, ArrayLiteral::new(elements)))
+    }
+
+    /// Parse an entry of a map literal
+    ///
+    /// `<instruction> : <instruction>`
+    fn map_entry(input: &str) -> ParseResult<MapEntry> {
+        let (input, key) = Construct::arg(input)?;
+        let (input, _) = Token::colon(input)?;
+        let (input, value) = Construct::arg(input)?;
+
+        Ok((input, (key, value)))
+    }
+
+    /// Parse an entry of a map literal and the comma that follows it
+    fn map_entry_comma(input: &str) -> ParseResult<MapEntry> {
+        let (input, entry) = Construct::map_entry(input)?;
+        let (input, _) = Token::comma(input)?;
+
+        Ok((input, entry))
+    }
+
+    /// Parse a map literal, containing a non empty list of entries separated by commas
+    ///
+    /// `{ <instruction> : <instruction> [ , <instruction> : <instruction> ]* }`
+    pub fn map_literal(input: &str) -> ParseResult<MapLiteral> {
+        let (input, _) = Token::left_curly_bracket(input)?;
+        let (input, mut entries) = many0(Construct::map_entry_comma)(input)?;
+
+        // Parse the last entry, which does not have a comma
+        let (input, last) = Construct::map_entry(input)?;
+        entries.push(last);
+
+        let (input, _) = Token::right_curly_bracket(input)?;
+
+        Ok((input, MapLiteral::new(entries)))
+    }
+
+    /// Parse the index given to an array, directly following it
+    ///
+    /// `[ <instruction> ]`
+    fn array_index(input: &str) -> ParseResult<Box<dyn Instruction>> {
+        let (input, _) = Token::left_bracket(input)?;
+        let (input, index) = Construct::arg(input)?;
+        let (input, _) = Token::right_bracket(input)?;
+
+        Ok((input, index))
+    }
+
     /// When a type is instantiated in the source code.
     ///
     /// ```
@@ -157,15 +350,20 @@ impl Construct {
     /// val = A(1); // Instantiate a new A type variable
     /// ```
     /// `<arg_list> := [(<constant> | <variable> | <expression>)*]`
-    /// `<identifier> ( <arg_list> )`
+    /// `<type_id> ( <arg_list> )`
     pub fn type_instantiation(input: &str) -> ParseResult<TypeInstantiation> {
-        let (input, type_name) = Token::identifier(input)?;
-        let type_id = TypeId::new(type_name);
+        let (input, type_id) = Construct::type_id(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, _) = Token::left_curly_bracket(input)?;
 
         let mut type_instantiation = TypeInstantiation::new(type_id);
 
+        // Variants of sum types can be instantiated without any field
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        if let Ok((input, _)) = Token::right_curly_bracket(input) {
+            return Ok((input, type_instantiation));
+        }
+
         let (input, mut arg_vec) = Construct::args_list(input)?;
         let (input, _) = Token::right_curly_bracket(input)?;
 
@@ -185,7 +383,7 @@ impl Construct {
     /// ```
     ///
     /// `<arg_list> := [(<constant> | <variable> | <instruction>)*]`
-    /// `<identifier> ( <arg_list> )`
+    /// `<identifier> [ <generic_list> ] ( <arg_list> )`
     pub(crate) fn function_call(input: &str) -> ParseResult<FunctionCall> {
         alt((
             Construct::function_call_no_args,
@@ -238,6 +436,30 @@ impl Construct {
         }
     }
 
+    /// Parse an assignment to one of the fields of a variable's instance. The field
+    /// can be nested inside other fields
+    ///
+    /// `<identifier> ( . <identifier> )+ = <instruction>`
+    pub(crate) fn field_assignment(input: &str) -> ParseResult<FieldAssign> {
+        let (input, id) = Token::identifier(input)?;
+        let (input, fields) = many1(Construct::dot_identifier)(input)?;
+        let (input, _) = opt(Token::consume_whitespaces)(input)?;
+        let (input, _) = Token::equal(input)?;
+        let (input, _) = opt(Token::consume_whitespaces)(input)?;
+        let (input, value) = Construct::instruction(input)?;
+
+        Ok((input, FieldAssign::new(id, fields, value)))
+    }
+
+    /// Parse a dot and the identifier that follows
+    ///
+    /// `. <identifier>`
+    fn dot_identifier(input: &str) -> ParseResult<String> {
+        let (input, _) = Token::dot(input)?;
+
+        Token::identifier(input)
+    }
+
     /// Parse a valid variable name
     ///
     /// `<identifier>`
@@ -280,7 +502,10 @@ impl Construct {
         let (input, (instructions, last)) = Construct::stmts_and_maybe_last(input)?;
 
         let (input, _) = Token::maybe_consume_extra(input)?;
-        let (input, _) = Token::right_curly_bracket(input)?;
+
+        // Once a block is opened, failing to close it means one of its instructions is
+        // invalid: Report that position instead of trying other constructs
+        let (input, _) = cut(Token::right_curly_bracket)(input)?;
 
         Ok((input, (instructions, last)))
     }
@@ -309,11 +534,13 @@ impl Construct {
     ///
     /// `{ [ <instruction> ; ]* [ <instruction> ] }`
     pub(crate) fn block(input: &str) -> ParseResult<Block> {
+        let start = input;
         let (input, (instructions, last)) = Construct::block_instructions(input)?;
 
         let mut block = Block::new();
         block.set_instructions(instructions);
         block.set_last(last);
+        block.set_location(Parser::location(start, input));
 
         Ok((input, block))
     }
@@ -331,15 +558,15 @@ impl Construct {
 
     /// Parse an identifier then its type
     ///
-    /// `<identifier> : <type>`
+    /// `<identifier> : <type_id>`
     fn identifier_type(input: &str) -> ParseResult<DecArg> {
         let (input, id) = Token::identifier(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, _) = Token::colon(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
-        let (input, ty) = Token::identifier(input)?;
+        let (input, ty) = Construct::type_id(input)?;
 
-        Ok((input, DecArg::new(id.to_owned(), TypeId::new(ty))))
+        Ok((input, DecArg::new(id.to_owned(), ty)))
     }
 
     /// Parse an identifer as well as the type and comma that follows
@@ -394,10 +621,10 @@ impl Construct {
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, _) = Token::arrow(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
-        let (input, ty) = Token::identifier(input)?;
+        let (input, ty) = Construct::type_id(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
 
-        Ok((input, Some(TypeId::from(ty.as_str()))))
+        Ok((input, Some(ty)))
     }
 
     /// Parse the return type of a function. Can be void
@@ -407,10 +634,11 @@ impl Construct {
 
     /// Parses the content of a function declaration
     ///
-    /// `<identifier> <args_dec> <return_type> <block>`
+    /// `<identifier> [ <generic_list> ] <args_dec> <return_type> <block>`
     fn function_content(input: &str) -> ParseResult<FunctionDec> {
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, fn_name) = Token::identifier(input)?;
+        let (input, generics) = opt(Construct::generic_list)(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
 
         let (input, args) = Construct::args_dec(input)?;
@@ -419,6 +647,7 @@ impl Construct {
 
         let mut function = FunctionDec::new(fn_name.to_owned(), ty);
 
+        function.set_generics(generics.unwrap_or_default());
         function.set_args(args);
         function.set_block(block);
 
@@ -505,7 +734,7 @@ impl Construct {
     /// External functions cannot have an associated block. The function's code resides
     /// in a native program, for example a shared C library or a Rust crate.
     ///
-    /// `<ext> <func> <identifier> ( <typed_arg_list> ) [ -> <type> ] ;`
+    /// `<ext> <func> <identifier> [ <generic_list> ] ( <typed_arg_list> ) [ -> <type> ] ;`
     pub(crate) fn ext_declaration(input: &str) -> ParseResult<FunctionDec> {
         let (input, _) = Token::ext_tok(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
@@ -513,6 +742,7 @@ impl Construct {
         let (input, _) = Token::maybe_consume_extra(input)?;
 
         let (input, fn_name) = Token::identifier(input)?;
+        let (input, generics) = opt(Construct::generic_list)(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
 
         let (input, args) = Construct::args_dec(input)?;
@@ -522,6 +752,7 @@ impl Construct {
 
         let mut function = FunctionDec::new(fn_name.to_owned(), ty);
 
+        function.set_generics(generics.unwrap_or_default());
         function.set_args(args);
 
         function.set_kind(FunctionKind::Ext);
@@ -547,7 +778,7 @@ impl Construct {
         let (input, _) = Token::if_tok(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
 
-        let (input, condition) = Construct::instruction(input)?;
+        let (input, condition) = Construct::condition(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
 
         let (input, if_body) = Construct::block(input)?;
@@ -559,6 +790,132 @@ impl Construct {
         Ok((input, if_else))
     }
 
+    /// Parse a binding of a pattern, which is either a new variable or `_` to ignore
+    /// the field
+    ///
+    /// `<identifier> | _`
+    fn binding(input: &str) -> ParseResult<String> {
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        if let Ok((input, id)) = Token::identifier(input) {
+            let (input, _) = Token::maybe_consume_extra(input)?;
+            return Ok((input, id));
+        }
+
+        let (input, _) = Token::underscore(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        Ok((input, String::from("_")))
+    }
+
+    /// Parse a binding of a pattern and the comma that follows it
+    fn binding_comma(input: &str) -> ParseResult<String> {
+        let (input, binding) = Construct::binding(input)?;
+        let (input, _) = Token::comma(input)?;
+
+        Ok((input, binding))
+    }
+
+    /// Parse the list of bindings given to the fields of a variant
+    ///
+    /// `( [ <binding> [ , <binding> ]* ] )`
+    fn bindings(input: &str) -> ParseResult<Vec<String>> {
+        let (input, _) = Token::left_parenthesis(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        if let Ok((input, _)) = Token::right_parenthesis(input) {
+            return Ok((input, vec![]));
+        }
+
+        let (input, mut bindings) = many0(Construct::binding_comma)(input)?;
+
+        // Parse the last binding, which does not have a comma
+        let (input, last) = Construct::binding(input)?;
+        bindings.push(last);
+
+        let (input, _) = Token::right_parenthesis(input)?;
+
+        Ok((input, bindings))
+    }
+
+    /// Parse the pattern of a match arm: a variant and its bindings, or a wildcard
+    ///
+    /// `<identifier> [ <bindings> ] | _`
+    fn pattern(input: &str) -> ParseResult<Pattern> {
+        if let Ok((input, name)) = Token::identifier(input) {
+            let (input, bindings) = opt(Construct::bindings)(input)?;
+
+            return Ok((input, Pattern::Variant(name, bindings.unwrap_or_default())));
+        }
+
+        let (input, _) = Token::underscore(input)?;
+
+        Ok((input, Pattern::Wildcard))
+    }
+
+    /// Parse the destructuring of a value using the pattern of one of its variants.
+    /// The bindings are mandatory, so that assignments to variables are not mistaken
+    /// for destructurings
+    ///
+    /// `<identifier> <bindings> = <instruction>`
+    pub(crate) fn destructure(input: &str) -> ParseResult<Destructure> {
+        let (input, name) = Token::identifier(input)?;
+        let (input, bindings) = Construct::bindings(input)?;
+
+        let (input, _) = opt(Token::consume_whitespaces)(input)?;
+        let (input, _) = Token::equal(input)?;
+        let (input, _) = opt(Token::consume_whitespaces)(input)?;
+        let (input, value) = Construct::instruction(input)?;
+
+        Ok((
+            input,
+            Destructure::new(Pattern::Variant(name, bindings), value),
+        ))
+    }
+
+    /// Parse an arm of a match
+    ///
+    /// `<pattern> => <instruction>`
+    fn match_arm(input: &str) -> ParseResult<MatchArm> {
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, pattern) = Construct::pattern(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, _) = Token::fat_arrow(input)?;
+        let (input, body) = Construct::instruction(input)?;
+
+        Ok((input, MatchArm::new(pattern, body)))
+    }
+
+    /// Parse an arm of a match and the comma that follows it
+    fn match_arm_comma(input: &str) -> ParseResult<MatchArm> {
+        let (input, arm) = Construct::match_arm(input)?;
+        let (input, _) = Token::comma(input)?;
+
+        Ok((input, arm))
+    }
+
+    /// Parse a match on a value, and its arms. The last arm can be followed by a comma
+    ///
+    /// `<match> <instruction> { [ <match_arm> , ]* [ <match_arm> ] }`
+    pub(crate) fn match_expr(input: &str) -> ParseResult<Match> {
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, _) = Token::match_tok(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        let (input, value) = Construct::condition(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        let (input, _) = Token::left_curly_bracket(input)?;
+        let (input, mut arms) = many0(Construct::match_arm_comma)(input)?;
+        let (input, last) = opt(Construct::match_arm)(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, _) = Token::right_curly_bracket(input)?;
+
+        arms.extend(last);
+
+        Ok((input, Match::new(value, arms)))
+    }
+
     /// Parse an audit block. This consists in the the audit keyword and the following
     /// block. Audit blocks are useful to relax the interpreter and develop faster. For
     /// example, you're allowed to ignore return values in an audit block.
@@ -573,37 +930,65 @@ impl Construct {
         Ok((input, Audit::new(block)))
     }
 
-    /// Parse a loop block, meaning the `loop` keyword and a corresponding block
+    /// Parse the instruction preceding the block of an `if`, `while` or `for`
+    /// construct. Type instantiations are not allowed there, as they would be
+    /// ambiguous with the block that follows: `if value { 0 }` could otherwise be parsed
+    /// as an instantiation of the type `value`.
+    ///
+    /// `<binary_op> | <member_access> | <function_call> | <block> | <variable> | <constant>`
+    fn condition(input: &str) -> ParseResult<Box<dyn Instruction>> {
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        alt((
+            Construct::binary_op,
+            Construct::member_access,
+            BoxConstruct::array_literal,
+            BoxConstruct::function_call,
+            BoxConstruct::map_literal,
+            BoxConstruct::block,
+            BoxConstruct::variable,
+            Construct::constant,
+        ))(input)
+    }
+
+    /// Parse a loop block, meaning the `loop` keyword and a corresponding block. Like
+    /// every other loop, it can declare the type of the value it yields
     ///
-    /// `<loop> <block>`
+    /// `<loop> [ -> <type> ] <block>`
     fn loop_block(input: &str) -> ParseResult<Loop> {
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, _) = Token::loop_tok(input)?;
-        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, ty) = Construct::return_type(input)?;
         let (input, block) = Construct::block(input)?;
 
-        Ok((input, Loop::new(LoopKind::Loop, block)))
+        let mut loop_block = Loop::new(LoopKind::Loop, block);
+        loop_block.set_ty(ty);
+
+        Ok((input, loop_block))
     }
 
     /// Parse a while block. A while block consists of a high bound, or instruction, as
     /// well as a block
     ///
-    /// `<while> <instruction> <block>`
+    /// `<while> <instruction> [ -> <type> ] <block>`
     fn while_block(input: &str) -> ParseResult<Loop> {
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, _) = Token::while_tok(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
-        let (input, condition) = Construct::instruction(input)?;
-        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, condition) = Construct::condition(input)?;
+        let (input, ty) = Construct::return_type(input)?;
         let (input, block) = Construct::block(input)?;
 
-        Ok((input, Loop::new(LoopKind::While(condition), block)))
+        let mut loop_block = Loop::new(LoopKind::While(condition), block);
+        loop_block.set_ty(ty);
+
+        Ok((input, loop_block))
     }
 
     /// Construct a for block, which consists of a variable, a range instruction, and
     /// a block to execute
     ///
-    /// `<for> <variable> <in> <instruction> <block>`
+    /// `<for> <variable> <in> <instruction> [ -> <type> ] <block>`
     fn for_block(input: &str) -> ParseResult<Loop> {
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, _) = Token::for_tok(input)?;
@@ -615,15 +1000,40 @@ impl Construct {
         let (input, _) = Token::in_tok(input)?;
 
         let (input, _) = Token::maybe_consume_extra(input)?;
-        let (input, instruction) = Construct::instruction(input)?;
+        let (input, instruction) = Construct::condition(input)?;
 
-        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, ty) = Construct::return_type(input)?;
         let (input, block) = Construct::block(input)?;
 
-        Ok((
-            input,
-            Loop::new(LoopKind::For(variable, instruction), block),
-        ))
+        let mut loop_block = Loop::new(LoopKind::For(Box::new(variable), instruction), block);
+        loop_block.set_ty(ty);
+
+        Ok((input, loop_block))
+    }
+
+    /// Parse a `break` or a `continue`. A `break` can carry a value, which then becomes
+    /// the value of the loop
+    ///
+    /// `<break> [ <instruction> ]` | `<continue>`
+    pub(crate) fn loop_control(input: &str) -> ParseResult<LoopControl> {
+        if let Ok((input, _)) = Token::continue_tok(input) {
+            return Ok((input, LoopControl::new(LoopControlKind::Continue)));
+        }
+
+        let (input, _) = Token::break_tok(input)?;
+        let (input, value) = opt(Construct::instruction)(input)?;
+
+        Ok((input, LoopControl::new(LoopControlKind::Break(value))))
+    }
+
+    /// Parse a `return`, and the value it returns if there is one
+    ///
+    /// `<return> [ <instruction> ]`
+    pub(crate) fn return_statement(input: &str) -> ParseResult<Return> {
+        let (input, _) = Token::return_tok(input)?;
+        let (input, value) = opt(Construct::instruction)(input)?;
+
+        Ok((input, Return::new(value)))
     }
 
     /// Parse any loop construct: For, While or Loop
@@ -660,24 +1070,70 @@ impl Construct {
     /// a > 2; // Is a greater than 2?
     /// ```
     pub(crate) fn binary_op(input: &str) -> ParseResult<Box<dyn Instruction>> {
-        ShuntingYard::parse(input)
+        let start = input;
+        let (input, mut expr) = ShuntingYard::parse(input)?;
+
+        expr.set_location(Parser::location(start, input));
+
+        Ok((input, expr))
     }
 
-    /// Parse a user-defined custom type
+    /// Parse a variant of a sum type, and the fields it carries if any
     ///
-    /// `<type> <TypeName> ( <typed_arg_list> ) ;`
+    /// `<identifier> [ ( <typed_arg_list> ) ]`
+    fn variant(input: &str) -> ParseResult<Variant> {
+        let (input, _) = Token::maybe_consume_extra(input)?;
+        let (input, name) = Token::identifier(input)?;
+        let (input, fields) = opt(Construct::args_dec)(input)?;
+        let (input, _) = Token::maybe_consume_extra(input)?;
+
+        Ok((input, Variant::new(name, fields.unwrap_or_default())))
+    }
+
+    /// Parse a variant of a sum type and the pipe that follows it
+    fn variant_pipe(input: &str) -> ParseResult<Variant> {
+        let (input, variant) = Construct::variant(input)?;
+        let (input, _) = Token::bit_or(input)?;
+
+        Ok((input, variant))
+    }
+
+    /// Parse the variants of a sum type
+    ///
+    /// `= <variant> [ | <variant> ]*`
+    fn variants(input: &str) -> ParseResult<Vec<Variant>> {
+        let (input, _) = Token::equal(input)?;
+        let (input, mut variants) = many0(Construct::variant_pipe)(input)?;
+
+        // Parse the last variant, which is not followed by a pipe
+        let (input, last) = Construct::variant(input)?;
+        variants.push(last);
+
+        Ok((input, variants))
+    }
+
+    /// Parse a user-defined custom type, which is either a product type containing
+    /// fields or a sum type containing variants
+    ///
+    /// `<type> <TypeName> [ <generic_list> ] ( ( <typed_arg_list> ) | <variants> ) ;`
     pub(crate) fn type_declaration(input: &str) -> ParseResult<TypeDec> {
         let (input, _) = Token::maybe_consume_extra(input)?;
         let (input, _) = Token::_type_tok(input)?;
         let (input, _) = Token::maybe_consume_extra(input)?;
 
         let (input, type_name) = Token::identifier(input)?;
+        let (input, generics) = opt(Construct::generic_list)(input)?;
 
         let (input, _) = Token::maybe_consume_extra(input)?;
 
-        let (input, fields) = Construct::args_dec_non_empty(input)?;
-
-        let type_declaration = TypeDec::new(type_name.to_owned(), fields);
+        let (input, mut type_declaration) = match Construct::variants(input) {
+            Ok((input, variants)) => (input, TypeDec::sum(type_name, variants)),
+            Err(_) => {
+                let (input, fields) = Construct::args_dec_non_empty(input)?;
+                (input, TypeDec::new(type_name, fields))
+            }
+        };
+        type_declaration.set_generics(generics.unwrap_or_default());
 
         Ok((input, type_declaration))
     }
@@ -728,17 +1184,15 @@ impl Construct {
         Ok((input, incl))
     }
 
-    /// Parse a viable caller for a method call
+    /// Parse a viable caller for a method call or a field access
     fn method_caller(input: &str) -> ParseResult<Box<dyn Instruction>> {
-        // FIXME: Right now, we cannot chain method calls and no error is produced:
-        // `1.double().double()` returns 2 instead of the expected 4, since
-        // only one call is resolved and the remaining input (`.double()`) is
-        // silently ignored
         alt((
             BoxConstruct::function_call,
             BoxConstruct::variable,
             Construct::constant,
+            BoxConstruct::array_literal,
             BoxConstruct::if_else,
+            BoxConstruct::map_literal,
             BoxConstruct::block,
             BoxConstruct::any_loop,
             BoxConstruct::jinko_inst,
@@ -746,16 +1200,49 @@ impl Construct {
         ))(input)
     }
 
-    /// Parse a method like function call, that shall be desugared
-    /// to a simple function call later on
+    /// Parse a chain of method calls, field accesses and array indexes. Each element
+    /// of the chain applies to the result of the previous one: `a.inner.get()` calls
+    /// the `get` method on the `inner` field of `a`. Method like function calls shall
+    /// be desugared to simple function calls later on
     ///
-    /// `<identifier>.<identifier>()`
-    pub fn method_call(input: &str) -> ParseResult<MethodCall> {
-        let (input, caller) = Construct::method_caller(input)?;
-        let (input, _) = Token::dot(input)?;
-        let (input, method) = Construct::function_call(input)?;
+    /// `<caller> ( . <function_call> | . <identifier> | [ <instruction> ] )?`
+    pub(crate) fn member_access(input: &str) -> ParseResult<Box<dyn Instruction>> {
+        let start = input;
+        let (mut input, mut value) = Construct::method_caller(input)?;
+        let mut chained = false;
+
+        loop {
+            if let Ok((next, index)) = Construct::array_index(input) {
+                input = next;
+                value = Box::new(ArrayIndex::new(value, index));
+                value.set_location(Parser::location(start, input));
+                chained = true;
+                continue;
+            }
+
+            let next = match Token::dot(input) {
+                Ok((next, _)) => next,
+                Err(_) => break,
+            };
+
+            value = if let Ok((next, method)) = Construct::function_call(next) {
+                input = next;
+                Box::new(MethodCall::new(value, method))
+            } else if let Ok((next, field)) = Token::identifier(next) {
+                input = next;
+                Box::new(FieldAccess::new(value, field))
+            } else {
+                break;
+            };
+
+            value.set_location(Parser::location(start, input));
+            chained = true;
+        }
 
-        Ok((input, MethodCall::new(caller, method)))
+        match chained {
+            true => Ok((input, value)),
+            false => Err(nom::Err::Error((input, nom::error::ErrorKind::Many1))),
+        }
     }
 }
 
@@ -841,6 +1328,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn t_var_assign_negative() {
+        assert!(Construct::var_assignment("x = -1;").is_ok());
+        assert!(Construct::var_assignment("x = -a * -(b - 1.5);").is_ok());
+        assert!(Construct::var_assignment("x = !-f(-1);").is_ok());
+    }
+
     #[test]
     fn t_var_assign_invalid() {
         match Construct::var_assignment("mutable x = 12") {
@@ -1198,6 +1692,14 @@ mod tests {
         };
     }
 
+    #[test]
+    fn t_if_else_variable_condition() {
+        // The condition must not be parsed as a type instantiation
+        let (input, _) = Construct::if_else("if condition { 0 } else { 1 }").unwrap();
+
+        assert_eq!(input, "");
+    }
+
     #[test]
     fn t_audit_simple() {
         match Construct::audit("audit {}") {
@@ -1256,6 +1758,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn t_typed_loops_valid() {
+        assert_eq!(Construct::loop_block("loop -> int { 12 }").unwrap().0, "");
+        assert_eq!(
+            Construct::while_block("while x -> int { 12 }").unwrap().0,
+            ""
+        );
+        assert_eq!(
+            Construct::for_block("for x in y -> int { x }").unwrap().0,
+            ""
+        );
+    }
+
+    #[test]
+    fn t_loop_control_valid() {
+        assert_eq!(Construct::loop_control("break").unwrap().1.print(), "break");
+        assert_eq!(
+            Construct::loop_control("continue").unwrap().1.print(),
+            "continue"
+        );
+
+        let (input, control) = Construct::loop_control("break 12 + 4;").unwrap();
+        assert_eq!(input, ";");
+        assert_eq!(control.print(), "break 12 + 4");
+    }
+
+    #[test]
+    fn t_return_valid() {
+        assert_eq!(
+            Construct::return_statement("return").unwrap().1.print(),
+            "return"
+        );
+
+        let (input, ret) = Construct::return_statement("return 12 + 4;").unwrap();
+        assert_eq!(input, ";");
+        assert_eq!(ret.print(), "return 12 + 4");
+    }
+
+    #[test]
+    fn t_return_invalid() {
+        assert!(Construct::return_statement("returned").is_err());
+    }
+
+    #[test]
+    fn t_loop_control_invalid() {
+        assert!(Construct::loop_control("breaker").is_err());
+        assert!(Construct::loop_control("continue_loop").is_err());
+    }
+
     #[test]
     fn t_for_invalid() {
         match Construct::for_block("for {}") {
@@ -1341,6 +1892,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn t_type_declaration_sum() {
+        let (input, dec) =
+            Construct::type_declaration("type Option<T> = Some(value: T) | None;").unwrap();
+
+        assert_eq!(input, ";");
+        assert!(dec.is_sum());
+        assert_eq!(dec.variants().len(), 2);
+        assert_eq!(dec.variants()[0].fields()[0].get_type().id(), "T");
+        assert!(dec.variants()[1].fields().is_empty());
+
+        assert!(Construct::type_declaration("type Empty = ;").is_err());
+        assert!(Construct::type_declaration("type Pipe = A | ;").is_err());
+    }
+
+    #[test]
+    fn t_match_expr() {
+        let (input, match_expr) =
+            Construct::match_expr("match f(x) { Some(a, _) => a + 1, None => { 0 }, _ => 2, }")
+                .unwrap();
+
+        assert_eq!(input, "");
+        assert_eq!(match_expr.arms().len(), 3);
+
+        assert!(Construct::match_expr("match x { Some(a) => }").is_err());
+        assert!(Construct::match_expr("match x { Some(a) 1 }").is_err());
+
+        let (_, match_expr) = Construct::instruction("match x { _ => 0 }").unwrap();
+        assert!(match_expr.downcast_ref::<Match>().is_some());
+    }
+
     #[test]
     fn t_type_instantiation_valid() {
         match Construct::type_instantiation("Custom { 1 }") {
@@ -1357,6 +1939,146 @@ mod tests {
         }
     }
 
+    #[test]
+    fn t_type_instantiation_empty() {
+        let (input, instantiation) = Construct::type_instantiation("Empty {} rest").unwrap();
+
+        assert_eq!(input, " rest");
+        assert_eq!(instantiation.print(), "Empty()");
+    }
+
+    #[test]
+    fn t_destructure() {
+        let (input, destructure) = Construct::destructure("Rect(w, _) = shape").unwrap();
+
+        assert_eq!(input, "");
+        assert_eq!(
+            destructure.pattern(),
+            &Pattern::Variant("Rect".to_owned(), vec!["w".to_owned(), "_".to_owned()])
+        );
+
+        let (_, destructure) = Construct::instruction("Circle(r) = Circle { 1 }").unwrap();
+        assert!(destructure.downcast_ref::<Destructure>().is_some());
+
+        let (_, assignment) = Construct::instruction("x = 12").unwrap();
+        assert!(assignment.downcast_ref::<VarAssign>().is_some());
+    }
+
+    #[test]
+    fn t_string_interpolation() {
+        let (input, interpolation) =
+            Construct::string_interpolation("\"{a} + {f(\"b\")} = \\{c}\" rest").unwrap();
+
+        assert_eq!(input, " rest");
+        assert_eq!(interpolation.parts().len(), 4);
+
+        assert!(Construct::string_interpolation("\"no interpolation\"").is_err());
+        assert!(Construct::string_interpolation("\"{unclosed\"").is_err());
+        assert!(Construct::string_interpolation("\"{}\"").is_err());
+
+        let (_, constant) = Construct::instruction("\"a {b}\"").unwrap();
+        assert!(constant.downcast_ref::<StringInterpolation>().is_some());
+    }
+
+    #[test]
+    fn t_type_id_generic() {
+        let (input, ty) = Construct::type_id("Map<int, Vec<T>> rest").unwrap();
+
+        assert_eq!(input, " rest");
+        assert_eq!(ty.id(), "Map<int, Vec<T>>");
+        assert_eq!(ty.generics().len(), 2);
+        assert_eq!(ty.generics()[1].generics(), &vec![TypeId::from("T")]);
+    }
+
+    #[test]
+    fn t_type_id_not_generic() {
+        assert_eq!(Construct::type_id("a < b").unwrap().0, " < b");
+        assert_eq!(Construct::type_id("a<b").unwrap().0, "<b");
+        assert!(Construct::type_id("<int>").is_err());
+    }
+
+    #[test]
+    fn t_type_id_array() {
+        let (input, ty) = Construct::type_id("[[ Node<int> ]] rest").unwrap();
+
+        assert_eq!(input, " rest");
+        assert_eq!(ty.id(), "[[Node<int>]]");
+        assert_eq!(ty.element().unwrap().element().unwrap().id(), "Node<int>");
+    }
+
+    #[test]
+    fn t_array_literal() {
+        let (input, array) = Construct::array_literal("[1, a + 2, f()]").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(array.elements().len(), 3);
+
+        let (input, array) = Construct::array_literal("[ ]").unwrap();
+        assert_eq!(input, "");
+        assert!(array.elements().is_empty());
+
+        assert!(Construct::array_literal("[1, 2").is_err());
+    }
+
+    #[test]
+    fn t_map_literal() {
+        let (input, map) = Construct::map_literal("{ \"a\": 1, b: f() }").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(map.entries().len(), 2);
+
+        assert!(Construct::map_literal("{ }").is_err());
+        assert!(Construct::map_literal("{ a }").is_err());
+
+        // Blocks must not be mistaken for map literals
+        let (_, block) = Construct::instruction("{ a }").unwrap();
+        assert!(block.downcast_ref::<Block>().is_some());
+        let (_, map) = Construct::instruction("{ a + 1: 2 }").unwrap();
+        assert!(map.downcast_ref::<MapLiteral>().is_some());
+    }
+
+    #[test]
+    fn t_array_index() {
+        let (input, index) = Construct::member_access("a[i + 1][0].len()").unwrap();
+
+        assert_eq!(input, "");
+        assert!(index.downcast_ref::<MethodCall>().is_some());
+
+        let (input, index) = Construct::instruction("[1, 2][0]").unwrap();
+
+        assert_eq!(input, "");
+        assert!(index.downcast_ref::<ArrayIndex>().is_some());
+    }
+
+    #[test]
+    fn t_type_declaration_generic() {
+        let (_, dec) =
+            Construct::type_declaration("type Node<T>(value: T, next: Node<T>);").unwrap();
+
+        assert_eq!(dec.generics(), &vec![TypeId::from("T")]);
+        assert_eq!(dec.fields()[1].get_type().id(), "Node<T>");
+    }
+
+    #[test]
+    fn t_type_instantiation_generic() {
+        let (input, inst) =
+            Construct::type_instantiation("Pair<int, string> { 1, \"a\" }").unwrap();
+
+        assert_eq!(input, "");
+        assert_eq!(inst.name().id(), "Pair<int, string>");
+        assert_eq!(inst.fields().len(), 2);
+    }
+
+    #[test]
+    fn t_function_generic() {
+        let (_, dec) =
+            Construct::function_declaration("func id<T>(value: T) -> T { value }").unwrap();
+        assert_eq!(dec.generics(), &vec![TypeId::from("T")]);
+
+        let (input, call) = Construct::function_call("id<Node<int>>(n)").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(call.generics()[0].id(), "Node<int>");
+        assert_eq!(call.print(), "id<Node<int>>(n /* :  =  */)");
+    }
+
     #[test]
     fn t_type_instantiation_no_name() {
         match Construct::type_instantiation("{ 1 }") {
@@ -1454,19 +2176,19 @@ mod tests {
     #[test]
     fn t_method_call_simple() {
         assert!(
-            Construct::method_call("a.b()").is_ok(),
+            Construct::member_access("a.b()").is_ok(),
             "Valid to have simple identifiers"
         );
         assert!(
-            Construct::method_call("135.method()").is_ok(),
+            Construct::member_access("135.method()").is_ok(),
             "Valid to have constant as caller"
         );
         assert!(
-            Construct::method_call("{ hey }.method()").is_ok(),
+            Construct::member_access("{ hey }.method()").is_ok(),
             "Valid to have block as caller"
         );
         assert!(
-            Construct::method_call("func_call().method()").is_ok(),
+            Construct::member_access("func_call().method()").is_ok(),
             "Valid to have call as caller"
         );
     }
@@ -1474,17 +2196,60 @@ mod tests {
     #[test]
     fn t_method_call_invalid() {
         assert!(
-            Construct::method_call("a.b").is_err(),
-            "Missing parentheses"
-        );
-        assert!(
-            Construct::method_call("a.()").is_err(),
+            Construct::member_access("a.()").is_err(),
             "Missing method name"
         );
         assert!(
-            Construct::method_call(".method()").is_err(),
+            Construct::member_access(".method()").is_err(),
             "Missing caller"
         );
+        assert!(Construct::member_access("a").is_err(), "Missing member");
+    }
+
+    #[test]
+    fn t_method_call_chained() {
+        let (input, call) = Construct::member_access("1.double().double()").unwrap();
+
+        assert_eq!(input, "");
+        assert_eq!(call.print(), "1.double().double()");
+    }
+
+    #[test]
+    fn t_field_assignment() {
+        let (input, assign) = Construct::field_assignment("p.start.x = 3 + 4").unwrap();
+
+        assert_eq!(input, "");
+        assert_eq!(assign.symbol(), "p");
+        assert_eq!(assign.print(), "p.start.x = 3 + 4");
+    }
+
+    #[test]
+    fn t_field_assignment_invalid() {
+        assert!(Construct::field_assignment("p = 3").is_err());
+        assert!(Construct::field_assignment("p. = 3").is_err());
+        assert!(Construct::field_assignment("p.x == 3").is_err());
+    }
+
+    #[test]
+    fn t_field_access() {
+        let (input, access) = Construct::member_access("a.b").unwrap();
+
+        assert_eq!(input, "");
+        assert_eq!(
+            access.downcast_ref::<FieldAccess>().unwrap().field_name(),
+            "b"
+        );
+    }
+
+    #[test]
+    fn t_field_access_chained() {
+        let (input, access) = Construct::member_access("t.value.get().value + 1").unwrap();
+
+        assert_eq!(input, " + 1");
+        assert_eq!(
+            access.downcast_ref::<FieldAccess>().unwrap().field_name(),
+            "value"
+        );
     }
 
     #[test]
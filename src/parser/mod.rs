@@ -2,9 +2,13 @@
 //! entry is created for the "main" function of the program. Including modules adds
 //! instructions to that main entry.
 
-use crate::{InstrKind, Interpreter, JkError};
+use crate::error::{JkErrSpaceLocation, Source};
+use crate::{InstrKind, Instruction, Interpreter, JkErrKind, JkError};
 
-use nom::multi::many0;
+use nom::error::ErrorKind;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
 
 mod box_construct;
 mod constant_construct;
@@ -18,17 +22,41 @@ pub use constructs::Construct;
 pub use shunting_yard::ShuntingYard;
 pub use tokens::Token;
 
+thread_local! {
+    /// Source currently being parsed. Constructs use it to locate the instructions they
+    /// build
+    static SOURCE: RefCell<Option<Rc<Source>>> = const { RefCell::new(None) };
+}
+
 pub struct Parser;
 
 impl Parser {
     /// Parses the entire user input and returns a hashmap corresponding to the user
-    /// program
-    pub fn parse(input: &str) -> Result<Interpreter, JkError> {
+    /// program. All the errors found in the input are returned at once
+    #[cfg(test)]
+    pub fn parse(input: &str) -> Result<Interpreter, Vec<JkError>> {
+        Parser::parse_source(Source::new(input.to_owned(), None))
+    }
+
+    /// Read and parse the file located at `path`. The interpreter is then aware of the
+    /// file it is executing
+    pub fn parse_file(path: &PathBuf) -> Result<Interpreter, Vec<JkError>> {
+        let input = std::fs::read_to_string(path).map_err(|e| {
+            vec![JkError::from(e).with_note(format!("while reading {}", path.display()))]
+        })?;
+
+        let mut interpreter = Parser::parse_source(Source::new(input, Some(path.clone())))?;
+        interpreter.set_path(Some(path.clone()));
+
+        Ok(interpreter)
+    }
+
+    fn parse_source(source: Source) -> Result<Interpreter, Vec<JkError>> {
         let mut interpreter = Interpreter::new();
 
         let entry_block = interpreter.entry_point.block_mut().unwrap();
 
-        let (_, instructions) = many0(Construct::instruction_maybe_semicolon)(input)?;
+        let instructions = Parser::with_source(source, Parser::instructions)?;
 
         entry_block.set_instructions(instructions);
 
@@ -45,4 +73,263 @@ impl Parser {
 
         Ok(interpreter)
     }
+
+    /// Parse all the instructions contained in the input. When an instruction cannot be
+    /// parsed, an error is emitted and the parser skips to the end of the invalid
+    /// instruction before carrying on. This allows reporting as many errors as possible
+    /// at once. No input is ever left unparsed
+    pub fn instructions(input: &str) -> Result<Vec<Box<dyn Instruction>>, Vec<JkError>> {
+        let mut instructions = Vec::new();
+        let mut errors = Vec::new();
+
+        let mut input = Parser::skip_extra(input);
+
+        while !input.is_empty() {
+            input = match Construct::instruction_maybe_semicolon(input) {
+                Ok((rest, instruction)) if rest != input => {
+                    instructions.push(instruction);
+                    rest
+                }
+                // Report the position where the parser actually stopped, which can be
+                // deep inside the instruction
+                Err(e) => {
+                    let rest = Parser::synchronize(input);
+                    let (msg, loc) = Parser::describe(e);
+
+                    errors.push(Parser::invalid_instruction(input, rest, msg, loc));
+
+                    rest
+                }
+                _ => {
+                    let rest = Parser::synchronize(input);
+
+                    errors.push(Parser::invalid_instruction(
+                        input,
+                        rest,
+                        "invalid instruction",
+                        None,
+                    ));
+
+                    rest
+                }
+            };
+
+            input = Parser::skip_extra(input);
+        }
+
+        match errors.is_empty() {
+            true => Ok(instructions),
+            false => Err(errors),
+        }
+    }
+
+    /// Describe why the parser stopped, and where. Most errors only tell where the
+    /// parser stopped, except for the few failures which have a specific meaning
+    fn describe(e: nom::Err<(&str, ErrorKind)>) -> (&'static str, Option<JkErrSpaceLocation>) {
+        match e {
+            nom::Err::Failure((digits, ErrorKind::TooLarge)) => {
+                let rest = digits.trim_start_matches(|c: char| c.is_ascii_digit());
+
+                (
+                    "integer literal out of range",
+                    Parser::location(digits, rest),
+                )
+            }
+            e => ("invalid instruction", JkError::from(e).loc().cloned()),
+        }
+    }
+
+    /// Error emitted for an instruction which could not be parsed, going from `input`
+    /// until `rest`. The error is located where the parser stopped if it is known, and
+    /// on the whole instruction otherwise
+    fn invalid_instruction(
+        input: &str,
+        rest: &str,
+        msg: &str,
+        loc: Option<JkErrSpaceLocation>,
+    ) -> JkError {
+        JkError::new(
+            JkErrKind::Parsing,
+            msg.to_owned(),
+            loc.or_else(|| Parser::location(input, rest)),
+            input[..input.len() - rest.len()].to_owned(),
+        )
+    }
+
+    /// Skip whitespaces and comments until actual code is found
+    fn skip_extra(mut input: &str) -> &str {
+        while let Ok((rest, _)) = Token::maybe_consume_extra(input) {
+            if rest == input {
+                break;
+            }
+
+            input = rest;
+        }
+
+        input
+    }
+
+    /// Skip input until the end of the current instruction: Either a semicolon or the
+    /// closing bracket of a block, as well as the semicolon that might follow it.
+    /// Semicolons contained in blocks, strings or characters do not end the instruction
+    fn synchronize(input: &str) -> &str {
+        let mut depth = 0usize;
+        let mut quote = None;
+        let mut escaped = false;
+
+        for (idx, c) in input.char_indices() {
+            match (quote, c) {
+                (Some(_), _) if escaped => escaped = false,
+                (Some(_), '\\') => escaped = true,
+                (Some(q), _) if q == c => quote = None,
+                (Some(_), _) => {}
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (None, '{') => depth += 1,
+                (None, ';') if depth == 0 => return &input[idx + 1..],
+                (None, '}') if depth <= 1 => {
+                    let rest = Parser::skip_extra(&input[idx + 1..]);
+
+                    return rest.strip_prefix(';').unwrap_or(rest);
+                }
+                (None, '}') => depth -= 1,
+                _ => {}
+            }
+        }
+
+        &input[input.len()..]
+    }
+
+    /// Parse the content of a source using the given parsing function. Instructions
+    /// built while parsing are located in that source
+    pub fn with_source<T>(source: Source, parse: impl FnOnce(&str) -> T) -> T {
+        let source = Rc::new(source);
+        let previous = SOURCE.with(|s| s.replace(Some(source.clone())));
+
+        let result = parse(source.content());
+
+        SOURCE.with(|s| s.replace(previous));
+
+        result
+    }
+
+    /// Locate the input consumed between `start` and `end`, two slices of the source
+    /// currently being parsed. Trailing whitespaces are not part of the location.
+    /// Returns `None` if no source is being parsed or if the slices do not belong to it
+    pub fn location(start: &str, end: &str) -> Option<JkErrSpaceLocation> {
+        SOURCE.with(|s| {
+            let source = s.borrow().clone()?;
+            let content = source.content();
+
+            let base = content.as_ptr() as usize;
+            let start_ptr = start.as_ptr() as usize;
+            let end_ptr = end.as_ptr() as usize;
+
+            if start_ptr < base || end_ptr < start_ptr || end_ptr > base + content.len() {
+                return None;
+            }
+
+            let start = start_ptr - base;
+            let end = start + content[start..end_ptr - base].trim_end().len();
+
+            Some(JkErrSpaceLocation::new(source, start, end))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_instructions_are_located() {
+        let interpreter = Parser::parse("a = 1;\n\nb = a + 2;\nfunc f() {}\n").unwrap();
+        let block = interpreter.entry_point.block().unwrap();
+
+        let locations: Vec<(usize, usize, &str)> = block
+            .instructions()
+            .iter()
+            .map(|inst| {
+                let loc = inst.location().unwrap();
+                (loc.line(), loc.column(), loc.snippet())
+            })
+            .collect();
+
+        assert_eq!(
+            locations,
+            vec![(1, 1, "a = 1"), (3, 1, "b = a + 2"), (4, 1, "func f() {}")]
+        );
+    }
+
+    #[test]
+    fn t_binary_op_operands_are_located() {
+        Parser::with_source(Source::new("12 + var".to_owned(), None), |input| {
+            let (_, binop) = Construct::binary_op(input).unwrap();
+
+            assert_eq!(binop.location().unwrap().snippet(), "12 + var");
+        });
+    }
+
+    #[test]
+    fn t_collect_multiple_errors() {
+        let errors = Parser::parse("a = 1;\nb = ;\nc = 2;\nd = $;\ne = 3;")
+            .err()
+            .unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].loc().unwrap().line(), 2);
+        assert_eq!(errors[1].loc().unwrap().line(), 4);
+    }
+
+    #[test]
+    fn t_trailing_input_is_an_error() {
+        assert!(Parser::parse("a = 1; $").is_err());
+    }
+
+    #[test]
+    fn t_synchronize_on_blocks() {
+        let errors = Parser::parse("func f() { a = ; b = 2; }\nc = 2;\n$")
+            .err()
+            .unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].loc().unwrap().line(), 1);
+        assert_eq!(errors[0].loc().unwrap().column(), 14);
+        assert_eq!(errors[1].loc().unwrap().line(), 3);
+    }
+
+    #[test]
+    fn t_error_nested_in_block() {
+        let input = "func f() -> int {\n    a = 1;\n    if a == 1 {\n        c = a + ;\n    };\n    a\n}\nf()";
+        let errors = Parser::parse(input).err().unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].loc().unwrap().line(), 4);
+        assert_eq!(errors[0].loc().unwrap().column(), 15);
+    }
+
+    #[test]
+    fn t_int_literal_out_of_range() {
+        let errors = Parser::parse("a = 1;\nb = a + 9223372036854775808;")
+            .err()
+            .unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].msg(), "integer literal out of range");
+        assert_eq!(errors[0].loc().unwrap().line(), 2);
+        assert_eq!(errors[0].loc().unwrap().snippet(), "9223372036854775808");
+    }
+
+    #[test]
+    fn t_synchronize_ignores_strings() {
+        assert_eq!(Parser::synchronize("f(\"a;b}\"); rest"), " rest");
+        assert_eq!(Parser::synchronize("{ a; { b; } c; }; rest"), " rest");
+        assert_eq!(Parser::synchronize("no end"), "");
+    }
+
+    #[test]
+    fn t_no_location_outside_of_source() {
+        let (_, inst) = Construct::instruction("a = 1").unwrap();
+
+        assert!(inst.location().is_none());
+    }
 }
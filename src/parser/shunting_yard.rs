@@ -1,9 +1,11 @@
 //! ShuntingYard parses operators and operands according to operator precedence,
-//! returning a BinaryOp in the end
+//! returning a BinaryOp in the end. Unary operators such as `!`, `~` or `-` are
+//! recognized when placed where an operand is expected, and produce a UnaryOp instead
 
-use crate::instruction::{BinaryOp, Instruction, Operator};
-use crate::parser::{BoxConstruct, Construct, Token};
+use crate::instruction::{BinaryOp, Instruction, Operator, UnaryOp};
+use crate::parser::{BoxConstruct, Construct, Parser, Token};
 use crate::utils::{Queue, Stack};
+use crate::{JkErrSpaceLocation, JkInt};
 
 use nom::{branch::alt, Err, IResult};
 
@@ -11,50 +13,120 @@ use nom::{branch::alt, Err, IResult};
 /// this queue is created in an "infix" way, but using SyPair instead of traditional
 /// strings. This infix notation is then transformed into an AST
 enum SyPair {
-    Op(Operator),
+    Op(Operator, Option<JkErrSpaceLocation>),
     Num(Box<dyn Instruction>),
 }
 
 pub struct ShuntingYard {
-    operators: Stack<Operator>,
+    operators: Stack<(Operator, Option<JkErrSpaceLocation>)>,
     output: Queue<SyPair>,
+
+    /// Is the next token expected to be an operand. This is used to recognize
+    /// unary operators, which are placed where an operand should be
+    expect_operand: bool,
+
+    /// Has an operator been parsed. A lone operand is not a binary expression, and is
+    /// left to the other parsers
+    has_operator: bool,
 }
 
 impl ShuntingYard {
+    /// Operator currently on top of the operator stack
+    fn top(&self) -> Option<Operator> {
+        self.operators.peek().map(|(op, _)| *op)
+    }
+
+    /// Move the operator on top of the operator stack to the output queue
+    fn pop_operator(&mut self) {
+        if let Some((op, location)) = self.operators.pop() {
+            self.output.push(SyPair::Op(op, location));
+        }
+    }
+
     fn operator<'i>(&mut self, input: &'i str) -> IResult<&'i str, ()> {
         let (input, _) = Token::maybe_consume_extra(input)?;
 
+        let start = input;
+
+        // Two-characters operators need to be tried first, since their first character
+        // might be an operator on its own
         let (input, op) = alt((
-            Token::add,
-            Token::sub,
-            Token::mul,
-            Token::div,
-            Token::left_parenthesis,
-            Token::right_parenthesis,
+            alt((
+                Token::equals,
+                Token::not_equals,
+                Token::lower_or_equal,
+                Token::greater_or_equal,
+                Token::left_shift,
+                Token::right_shift,
+                Token::and,
+                Token::or,
+                Token::pow,
+            )),
+            alt((
+                Token::lower_than,
+                Token::greater_than,
+                Token::not,
+                Token::bit_and,
+                Token::bit_or,
+                Token::bit_xor,
+                Token::bit_not,
+            )),
+            alt((
+                Token::add,
+                Token::sub,
+                Token::mul,
+                Token::div,
+                Token::modulo,
+                Token::left_parenthesis,
+                Token::right_parenthesis,
+            )),
         ))(input)?;
 
+        let location = Parser::location(start, input);
+
         let (input, _) = Token::maybe_consume_extra(input)?;
 
         let op = Operator::new(op);
+        self.has_operator = true;
+
+        // Operators placed where an operand is expected are unary ones, such as the
+        // `-` in `2 * -a`
+        let op = match (self.expect_operand, op.as_unary()) {
+            (true, Some(unary)) => unary,
+            _ => op,
+        };
+
+        if op.is_unary() {
+            if !self.expect_operand {
+                return Err(nom::Err::Error((
+                    "Unary operator used as a binary one",
+                    nom::error::ErrorKind::OneOf,
+                )));
+            }
+
+            // A unary operator applies to the operand following it: It cannot pop any
+            // operator from the stack
+            self.operators.push((op, location));
+        } else if op != Operator::LeftParenthesis && op != Operator::RightParenthesis {
+            while let Some(top) = self.top() {
+                if top == Operator::LeftParenthesis
+                    || !(top.precedence() > op.precedence()
+                        || (top.precedence() == op.precedence() && op.is_left_associative()))
+                {
+                    break;
+                }
 
-        if op != Operator::LeftParenthesis && op != Operator::RightParenthesis {
-            while !self.operators.is_empty()
-                && (self.operators.peek().unwrap().precedence() > op.precedence()
-                    || (self.operators.peek().unwrap().precedence() == op.precedence()
-                        && op.is_left_associative()))
-                && (self.operators.peek() != Some(&Operator::LeftParenthesis))
-            {
-                // We can unwrap safely since we checked that self.operators is not
-                // empty
-                self.output.push(SyPair::Op(self.operators.pop().unwrap()));
+                self.pop_operator();
             }
 
-            self.operators.push(op);
+            self.operators.push((op, location));
+            self.expect_operand = true;
         } else if op == Operator::LeftParenthesis {
-            self.operators.push(op);
+            self.operators.push((op, location));
+            self.expect_operand = true;
         } else if op == Operator::RightParenthesis {
-            while self.operators.peek() != Some(&Operator::LeftParenthesis) {
-                match self.operators.pop() {
+            while self.top() != Some(Operator::LeftParenthesis) {
+                match self.top() {
                     None => {
                         return Err(nom::Err::Error((
                             "Unclosed right parenthesis",
@@ -62,13 +134,12 @@ impl ShuntingYard {
                         )))
                     }
 
-                    Some(op) => self.output.push(SyPair::Op(op)),
+                    Some(_) => self.pop_operator(),
                 }
             }
 
-            if self.operators.peek() == Some(&Operator::LeftParenthesis) {
-                self.operators.pop();
-            }
+            self.operators.pop();
+            self.expect_operand = false;
         }
 
         Ok((input, ()))
@@ -76,17 +147,45 @@ impl ShuntingYard {
 
     fn operand<'i>(&mut self, input: &'i str) -> IResult<&'i str, ()> {
         let (input, expr) = alt((
-            BoxConstruct::method_call,
+            Construct::member_access,
             BoxConstruct::function_call,
             Construct::constant,
             BoxConstruct::variable,
+            BoxConstruct::array_literal,
         ))(input)?;
 
         self.output.push(SyPair::Num(expr));
+        self.expect_operand = false;
 
         Ok((input, ()))
     }
 
+    /// `-9223372036854775808` is the only integer whose digits do not fit in an `int`
+    /// on their own. Its minus sign is part of the literal instead of being a unary
+    /// operator
+    fn smallest_int<'i>(&mut self, input: &'i str) -> IResult<&'i str, ()> {
+        let (rest, value) = Token::int_constant(input)?;
+
+        if value != i64::MIN {
+            return Err(nom::Err::Error((input, nom::error::ErrorKind::TooLarge)));
+        }
+
+        let mut constant = JkInt::from(value);
+        constant.set_location(Parser::location(input, rest));
+
+        self.output.push(SyPair::Num(Box::new(constant)));
+        self.expect_operand = false;
+
+        Ok((rest, ()))
+    }
+
+    /// Is the input at the end of the binary expression. This is the case when
+    /// encountering the beginning or end of a block, the end of an instruction,
+    /// the end of an argument, an array element or a map key, or a return type
+    fn is_finished(input: &str) -> bool {
+        input.starts_with(['{', '}', ';', ',', ']', ':']) || input.starts_with("->")
+    }
+
     fn handle_token<'i>(&mut self, input: &'i str) -> IResult<&'i str, ()> {
         let (input, _) = Token::maybe_consume_extra(input)?;
 
@@ -99,7 +198,7 @@ impl ShuntingYard {
             }
             Some(c) => {
                 // Return early if a finishing character is found
-                if c == '}' || c == ';' {
+                if ShuntingYard::is_finished(input) {
                     return Err(nom::Err::Error((
                         "Finished binary expression",
                         nom::error::ErrorKind::OneOf,
@@ -107,6 +206,9 @@ impl ShuntingYard {
                 }
 
                 match Token::is_operator(c) {
+                    true if self.expect_operand && c == '-' => {
+                        self.smallest_int(input).or_else(|_| self.operator(input))?
+                    }
                     true => self.operator(input)?,
                     false => self.operand(input)?,
                 }
@@ -123,25 +225,55 @@ impl ShuntingYard {
         ShuntingYard {
             operators: Stack::new(),
             output: Queue::new(),
+            expect_operand: true,
+            has_operator: false,
         }
     }
 
     /// Reduce the RPN produced by the ShuntingYard to an Expression
     fn reduce<'i>(input: &'i str, rpn: Queue<SyPair>) -> IResult<&'i str, Box<dyn Instruction>> {
-        let mut stack = Stack::new();
+        let missing_operand =
+            || nom::Err::Error(("Missing operator or operand", nom::error::ErrorKind::OneOf));
+
+        let mut stack: Stack<Box<dyn Instruction>> = Stack::new();
 
         for sy_pair in rpn.into_iter() {
             match sy_pair {
                 SyPair::Num(num) => {
                     stack.push(num);
                 }
-                SyPair::Op(op) => {
-                    if let Some(rhs) = stack.pop() {
-                        if let Some(lhs) = stack.pop() {
-                            stack.push(Box::new(BinaryOp::new(lhs, rhs, op)));
-                            continue;
-                        }
-                    }
+                SyPair::Op(Operator::LeftParenthesis, _) => {
+                    return Err(nom::Err::Error((
+                        "Unclosed left parenthesis",
+                        nom::error::ErrorKind::OneOf,
+                    )))
+                }
+                SyPair::Op(op, op_location) if op.is_unary() => {
+                    let operand = stack.pop().ok_or_else(missing_operand)?;
+
+                    let location = match (op_location, operand.location()) {
+                        (Some(o), Some(v)) => Some(o.until(v)),
+                        _ => None,
+                    };
+
+                    let mut unop = UnaryOp::new(operand, op);
+                    unop.set_location(location);
+
+                    stack.push(Box::new(unop));
+                }
+                SyPair::Op(op, _) => {
+                    let rhs = stack.pop().ok_or_else(missing_operand)?;
+                    let lhs = stack.pop().ok_or_else(missing_operand)?;
+
+                    let location = match (lhs.location(), rhs.location()) {
+                        (Some(l), Some(r)) => Some(l.until(r)),
+                        _ => None,
+                    };
+
+                    let mut binop = BinaryOp::new(lhs, rhs, op);
+                    binop.set_location(location);
+
+                    stack.push(Box::new(binop));
                 }
             }
         }
@@ -163,12 +295,7 @@ impl ShuntingYard {
         // wasn't a valid mathematical expression
         match stack.pop() {
             None => {}
-            Some(_) => {
-                return Err(nom::Err::Error((
-                    "Missing operator or operand",
-                    nom::error::ErrorKind::OneOf,
-                )))
-            }
+            Some(_) => return Err(missing_operand()),
         }
 
         Ok((input, value))
@@ -209,9 +336,16 @@ impl ShuntingYard {
             }
         }
 
+        if !sy.has_operator {
+            return Err(Err::Error((
+                "Not a valid binary expression",
+                nom::error::ErrorKind::Many1,
+            )));
+        }
+
         // We are done, pop everything from the operator stack
         while !sy.operators.is_empty() {
-            sy.output.push(SyPair::Op(sy.operators.pop().unwrap()));
+            sy.pop_operator();
         }
 
         ShuntingYard::reduce(input, sy.output)
@@ -303,6 +437,121 @@ mod tests {
         );
     }
 
+    #[test]
+    fn t_sy_modulo_and_power() {
+        sy_assert("17 % 5 + 1", 3);
+        sy_assert("2 * 3 ** 2", 18);
+        sy_assert("12 - 4 % 3", 11);
+    }
+
+    #[test]
+    fn t_sy_power_right_associative() {
+        sy_assert("2 ** 3 ** 2", 512);
+        sy_assert("(2 ** 3) ** 2", 64);
+    }
+
+    #[test]
+    fn t_sy_bitwise_precedence() {
+        sy_assert("1 | 6 & 3", 1 | 6 & 3);
+        sy_assert("5 ^ 3 | 8", 5 ^ 3 | 8);
+        sy_assert("1 << 2 + 1", 1 << (2 + 1));
+        sy_assert("256 >> 2 & 240", 256 >> 2 & 240);
+        sy_assert("~5 & 7", !5 & 7);
+    }
+
+    #[test]
+    fn t_sy_unary_minus() {
+        sy_assert("2 * -3", -6);
+        sy_assert("-2 * 3 + 1", -5);
+        sy_assert("4 - -1", 5);
+        sy_assert("-(1 + 2) * 2", -6);
+        sy_assert("+2 - +1", 1);
+    }
+
+    #[test]
+    fn t_sy_unary_minus_and_power() {
+        sy_assert("-2 ** 2 + 1", -3);
+        sy_assert("2 ** 2 * -1", -4);
+    }
+
+    #[test]
+    fn t_sy_smallest_int() {
+        sy_assert("-9223372036854775808 + 1", i64::MIN + 1);
+        sy_assert("-9223372036854775807 - 1", i64::MIN);
+        assert!(ShuntingYard::parse("1 + 9223372036854775808").is_err());
+    }
+
+    #[test]
+    fn t_sy_unary_minus_on_variable() {
+        let (_, output) = ShuntingYard::parse("-a").unwrap();
+        let unop = output.downcast_ref::<UnaryOp>().unwrap();
+
+        assert_eq!(unop.operator(), Operator::UnaryMinus);
+    }
+
+    fn sy_assert_bool(input: &str, result: bool) {
+        use crate::instance::ToObjectInstance;
+        use crate::{InstrKind, Interpreter};
+
+        let output = ShuntingYard::parse(input).unwrap().1;
+
+        let mut i = Interpreter::new();
+
+        assert_eq!(
+            output.execute(&mut i).unwrap(),
+            InstrKind::Expression(Some(JkBool::from(result).to_instance()))
+        );
+    }
+
+    #[test]
+    fn t_sy_comparison_after_arithmetic() {
+        sy_assert_bool("1 + 2 * 3 == 7", true);
+        sy_assert_bool("2 * 3 < 4 + 1", false);
+        sy_assert_bool("12 >= 12", true);
+        sy_assert_bool("1.5 != 1.5", false);
+    }
+
+    #[test]
+    fn t_sy_logical_precedence() {
+        sy_assert_bool("true || false && false", true);
+        sy_assert_bool("(true || false) && false", false);
+        sy_assert_bool("1 < 2 && 3 > 4 || 5 <= 5", true);
+    }
+
+    #[test]
+    fn t_sy_not() {
+        sy_assert_bool("!true", false);
+        sy_assert_bool("!false && false", false);
+        sy_assert_bool("!(false && false)", true);
+        sy_assert_bool("!!true", true);
+    }
+
+    #[test]
+    fn t_sy_not_is_unary() {
+        let (_, output) = ShuntingYard::parse("!a").unwrap();
+
+        assert!(output.downcast_ref::<UnaryOp>().is_some());
+        assert!(ShuntingYard::parse("a ! b").unwrap().0.starts_with('!'));
+    }
+
+    #[test]
+    fn t_sy_stops_before_block() {
+        let (input, _) = ShuntingYard::parse("a < 2 { 1 }").unwrap();
+
+        assert_eq!(input, "{ 1 }");
+    }
+
+    #[test]
+    fn t_sy_lone_operand() {
+        assert!(ShuntingYard::parse("a").is_err());
+    }
+
+    #[test]
+    fn t_sy_missing_operand() {
+        assert!(ShuntingYard::parse("1 +").is_err());
+        assert!(ShuntingYard::parse("(1 + 2").is_err());
+    }
+
     #[test]
     fn t_sy_eager_consume_error() {
         // https://github.com/CohenArthur/jinko/issues/172
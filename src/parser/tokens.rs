@@ -3,20 +3,23 @@
 //! and so on. This module consists of a lot of uninteresting helper/wrapper functions
 
 use nom::{
-    branch::alt, bytes::complete::is_not, bytes::complete::tag, bytes::complete::take_until,
-    bytes::complete::take_while, bytes::complete::take_while1, character::complete::anychar,
-    character::complete::char, character::is_alphabetic, character::is_alphanumeric,
-    character::is_digit, combinator::opt, combinator::peek, error::ErrorKind, multi::many0,
-    sequence::delimited, sequence::pair, IResult,
+    branch::alt, bytes::complete::tag, bytes::complete::take_until, bytes::complete::take_while,
+    bytes::complete::take_while1, bytes::complete::take_while_m_n, character::complete::anychar,
+    character::complete::char, character::complete::none_of, character::is_alphabetic,
+    character::is_alphanumeric, character::is_digit, combinator::opt, combinator::peek,
+    error::ErrorKind, multi::many0, multi::many1, sequence::pair, sequence::preceded, IResult,
 };
 
 /// Reserved Keywords by jinko
-const RESERVED_KEYWORDS: [&str; 13] = [
+const RESERVED_KEYWORDS: [&str; 17] = [
     "func", "test", "mock", "type", "ext", "for", "while", "loop", "mut", "true", "false", "incl",
-    "as",
+    "as", "break", "continue", "return", "match",
 ];
 
-const OPERATORS: [&str; 6] = ["+", "-", "*", "/", "(", ")"];
+const OPERATORS: [&str; 23] = [
+    "+", "-", "*", "/", "%", "**", "(", ")", "==", "!=", "<", "<=", ">", ">=", "&&", "||", "!",
+    "&", "|", "^", "~", "<<", ">>",
+];
 
 pub struct Token;
 
@@ -47,7 +50,10 @@ impl Token {
                     char('\n'),
                     char(' '),
                     char('{'),
+                    char('}'),
                     char(')'),
+                    char(']'),
+                    char(','),
                     char(';'),
                 )))(input)?;
                 Ok((input, tag))
@@ -79,11 +85,11 @@ impl Token {
         Token::specific_char(input, '}')
     }
 
-    pub fn _left_bracket(input: &str) -> IResult<&str, char> {
+    pub fn left_bracket(input: &str) -> IResult<&str, char> {
         Token::specific_char(input, '[')
     }
 
-    pub fn _right_bracket(input: &str) -> IResult<&str, char> {
+    pub fn right_bracket(input: &str) -> IResult<&str, char> {
         Token::specific_char(input, ']')
     }
 
@@ -99,6 +105,10 @@ impl Token {
         Token::specific_char(input, '@')
     }
 
+    pub fn underscore(input: &str) -> IResult<&str, char> {
+        Token::specific_char(input, '_')
+    }
+
     pub fn func_tok(input: &str) -> IResult<&str, &str> {
         Token::specific_token(input, "func")
     }
@@ -131,6 +141,18 @@ impl Token {
         Token::specific_token(input, "in")
     }
 
+    pub fn break_tok(input: &str) -> IResult<&str, &str> {
+        Token::specific_token(input, "break")
+    }
+
+    pub fn continue_tok(input: &str) -> IResult<&str, &str> {
+        Token::specific_token(input, "continue")
+    }
+
+    pub fn return_tok(input: &str) -> IResult<&str, &str> {
+        Token::specific_token(input, "return")
+    }
+
     pub fn mut_tok(input: &str) -> IResult<&str, &str> {
         Token::specific_token(input, "mut")
     }
@@ -143,6 +165,10 @@ impl Token {
         Token::specific_token(input, "else")
     }
 
+    pub fn match_tok(input: &str) -> IResult<&str, &str> {
+        Token::specific_token(input, "match")
+    }
+
     pub fn audit_tok(input: &str) -> IResult<&str, &str> {
         Token::specific_token(input, "audit ")
     }
@@ -175,6 +201,14 @@ impl Token {
         Token::token(input, "/")
     }
 
+    pub fn modulo(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "%")
+    }
+
+    pub fn pow(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "**")
+    }
+
     pub fn left_parenthesis(input: &str) -> IResult<&str, &str> {
         Token::token(input, "(")
     }
@@ -183,10 +217,66 @@ impl Token {
         Token::token(input, ")")
     }
 
-    pub fn _left_shift(input: &str) -> IResult<&str, &str> {
+    pub fn equals(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "==")
+    }
+
+    pub fn not_equals(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "!=")
+    }
+
+    pub fn lower_than(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "<")
+    }
+
+    pub fn lower_or_equal(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "<=")
+    }
+
+    pub fn greater_than(input: &str) -> IResult<&str, &str> {
+        Token::token(input, ">")
+    }
+
+    pub fn greater_or_equal(input: &str) -> IResult<&str, &str> {
+        Token::token(input, ">=")
+    }
+
+    pub fn and(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "&&")
+    }
+
+    pub fn or(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "||")
+    }
+
+    pub fn not(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "!")
+    }
+
+    pub fn bit_and(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "&")
+    }
+
+    pub fn bit_or(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "|")
+    }
+
+    pub fn bit_xor(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "^")
+    }
+
+    pub fn bit_not(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "~")
+    }
+
+    pub fn left_shift(input: &str) -> IResult<&str, &str> {
         Token::token(input, "<<")
     }
 
+    pub fn right_shift(input: &str) -> IResult<&str, &str> {
+        Token::token(input, ">>")
+    }
+
     pub fn true_tok(input: &str) -> IResult<&str, &str> {
         let (input, t) = Token::specific_token(input, "true")?;
 
@@ -203,6 +293,10 @@ impl Token {
         Token::specific_token(input, "->")
     }
 
+    pub fn fat_arrow(input: &str) -> IResult<&str, &str> {
+        Token::token(input, "=>")
+    }
+
     pub fn comment_multi_start(input: &str) -> IResult<&str, &str> {
         tag("/*")(input)
     }
@@ -291,7 +385,7 @@ impl Token {
 
     pub fn float_constant(input: &str) -> IResult<&str, f64> {
         let (input, negative_sign) = opt(char('-'))(input)?;
-        let (input, whole) = Token::int_constant(input)?;
+        let (input, whole) = Token::non_neg_num(input)?;
         let (input, _) = char('.')(input)?;
         let (input, decimal) = Token::non_neg_num(input)?;
 
@@ -308,35 +402,87 @@ impl Token {
         }
     }
 
+    /// Parse an integer, along with its sign. Integers which do not fit in an `int`
+    /// are a failure located on their digits
     pub fn int_constant(input: &str) -> IResult<&str, i64> {
-        let (input, negative_sign) = opt(char('-'))(input)?;
-        let (input, num) = Token::non_neg_num(input)?;
+        let start = input;
+        let (input, _) = opt(char('-'))(input)?;
+        let (rest, _) = Token::non_neg_num(input)?;
+
+        // The sign is parsed along with the digits, so that the smallest integer can
+        // be written
+        match start[..start.len() - rest.len()].parse::<i64>() {
+            Ok(value) => Ok((rest, value)),
+            Err(_) => Err(nom::Err::Failure((input, ErrorKind::TooLarge))),
+        }
+    }
 
-        match num.parse::<i64>() {
-            Ok(value) => match negative_sign {
-                Some(_) => Ok((input, -value)),
-                None => Ok((input, value)),
-            },
-            // FIXME: Return better error with err message
-            Err(_) => Err(nom::Err::Error(("Invalid integer", ErrorKind::OneOf))),
+    /// Parse an escape sequence, without its leading backslash, and return the
+    /// character it stands for
+    ///
+    /// `n | t | r | 0 | \\ | " | ' | { | } | u{<hex_digit>+}`
+    fn escape_sequence(input: &str) -> IResult<&str, char> {
+        let (input, escaped) = anychar(input)?;
+
+        match escaped {
+            'n' => Ok((input, '\n')),
+            't' => Ok((input, '\t')),
+            'r' => Ok((input, '\r')),
+            '0' => Ok((input, '\0')),
+            '\\' | '"' | '\'' | '{' | '}' => Ok((input, escaped)),
+            'u' => Token::unicode_escape(input),
+            _ => Err(nom::Err::Error((input, ErrorKind::Escaped))),
+        }
+    }
+
+    /// Parse the code point of a unicode escape sequence, such as `{1F980}` in
+    /// `\u{1F980}`
+    fn unicode_escape(input: &str) -> IResult<&str, char> {
+        let (input, _) = Token::left_curly_bracket(input)?;
+        let (input, code) = take_while_m_n(1, 6, |c: char| c.is_ascii_hexdigit())(input)?;
+        let (input, _) = Token::right_curly_bracket(input)?;
+
+        // The code point only contains hexadecimal digits
+        match char::from_u32(u32::from_str_radix(code, 16).unwrap()) {
+            Some(character) => Ok((input, character)),
+            None => Err(nom::Err::Error((input, ErrorKind::Escaped))),
         }
     }
 
+    /// Parse a character which might be escaped
+    fn maybe_escaped_char(input: &str) -> IResult<&str, char> {
+        alt((preceded(char('\\'), Token::escape_sequence), anychar))(input)
+    }
+
     /// Parse a single character constant and return the character inside the quotes
     pub fn char_constant(input: &str) -> IResult<&str, char> {
         let (input, _) = Token::single_quote(input)?;
-        let (input, character) = anychar(input)?;
+        let (input, character) = Token::maybe_escaped_char(input)?;
         let (input, _) = Token::single_quote(input)?;
 
-        // FIXME: Handle escaping as well
-
         Ok((input, character))
     }
 
-    /// Parse a string constant and return the characters between the double quotes
-    pub fn string_constant(input: &str) -> IResult<&str, &str> {
-        // FIXME: This does not allow for string escaping yet
-        delimited(Token::double_quote, is_not("\""), Token::double_quote)(input)
+    /// Parse one or more characters of a string constant, and return them once
+    /// escaped. Opening curly brackets are only valid when escaped, since they
+    /// start an interpolation
+    pub fn string_chars(input: &str) -> IResult<&str, String> {
+        let (input, chars) = many1(alt((
+            preceded(char('\\'), Token::escape_sequence),
+            none_of("\"\\{"),
+        )))(input)?;
+
+        Ok((input, chars.into_iter().collect()))
+    }
+
+    /// Parse a string constant and return the escaped characters between the double
+    /// quotes
+    pub fn string_constant(input: &str) -> IResult<&str, String> {
+        let (input, _) = Token::double_quote(input)?;
+        let (input, string) = opt(Token::string_chars)(input)?;
+        let (input, _) = Token::double_quote(input)?;
+
+        Ok((input, string.unwrap_or_default()))
     }
 
     fn is_whitespace(c: char) -> bool {
@@ -380,10 +526,10 @@ impl Token {
 
     /// Consumes all kinds of comments: Multi-line or single-line
     fn maybe_consume_comment(input: &str) -> IResult<&str, &str> {
-        let (input, _) = alt((
-            opt(Token::consume_single_comment),
-            opt(Token::consume_multi_comment),
-        ))(input)?;
+        let (input, _) = opt(alt((
+            Token::consume_single_comment,
+            Token::consume_multi_comment,
+        )))(input)?;
 
         Ok((input, ""))
     }
@@ -407,8 +553,19 @@ mod tests {
     fn t_char_constant_valid() {
         assert_eq!(Token::char_constant("'a'"), Ok(("", 'a')));
         assert_eq!(Token::char_constant("'9'"), Ok(("", '9')));
+        assert_eq!(Token::char_constant("'{'"), Ok(("", '{')));
+    }
+
+    #[test]
+    fn t_char_constant_escaped() {
+        assert_eq!(Token::char_constant("'\\n'"), Ok(("", '\n')));
+        assert_eq!(Token::char_constant("'\\''"), Ok(("", '\'')));
+        assert_eq!(Token::char_constant("'\\\\'"), Ok(("", '\\')));
+        assert_eq!(Token::char_constant("'\\u{1F980}'"), Ok(("", '🦀')));
 
-        // FIXME: Add escaping
+        assert!(Token::char_constant("'\\q'").is_err());
+        assert!(Token::char_constant("'\\u{D800}'").is_err());
+        assert!(Token::char_constant("'\\u{}'").is_err());
     }
 
     #[test]
@@ -423,11 +580,39 @@ mod tests {
     #[test]
     fn t_string_constant() {
         // Simple string
-        assert_eq!(Token::string_constant("\"a str\""), Ok(("", "a str")));
-        assert_eq!(Token::string_constant("\"999 89 9\""), Ok(("", "999 89 9")));
-        assert_eq!(Token::string_constant("\"4.01f\""), Ok(("", "4.01f")));
+        assert_eq!(
+            Token::string_constant("\"a str\""),
+            Ok(("", String::from("a str")))
+        );
+        assert_eq!(
+            Token::string_constant("\"999 89 9\""),
+            Ok(("", String::from("999 89 9")))
+        );
+        assert_eq!(
+            Token::string_constant("\"4.01f\""),
+            Ok(("", String::from("4.01f")))
+        );
+        assert_eq!(Token::string_constant("\"\""), Ok(("", String::new())));
+    }
+
+    #[test]
+    fn t_string_constant_escaped() {
+        assert_eq!(
+            Token::string_constant("\"a\\tb\\n\\\"c\\\"\""),
+            Ok(("", String::from("a\tb\n\"c\"")))
+        );
+        assert_eq!(
+            Token::string_constant("\"\\{not interpolated}\\\\\""),
+            Ok(("", String::from("{not interpolated}\\")))
+        );
+        assert_eq!(
+            Token::string_constant("\"\\u{e9}t\\u{E9}\""),
+            Ok(("", String::from("été")))
+        );
 
-        // FIXME: Fix string escaping
+        // Unescaped interpolations are not part of constants
+        assert!(Token::string_constant("\"a {b}\"").is_err());
+        assert!(Token::string_constant("\"\\x\"").is_err());
     }
 
     #[test]
@@ -443,6 +628,21 @@ mod tests {
     fn t_int_constant_valid() {
         assert_eq!(Token::int_constant("12"), Ok(("", 12)));
         assert_eq!(Token::int_constant("-45"), Ok(("", -45)));
+        assert_eq!(
+            Token::int_constant("-9223372036854775808"),
+            Ok(("", i64::MIN))
+        );
+    }
+
+    #[test]
+    fn t_int_constant_out_of_range() {
+        assert_eq!(
+            Token::int_constant("9223372036854775808"),
+            Err(nom::Err::Failure((
+                "9223372036854775808",
+                ErrorKind::TooLarge
+            )))
+        );
     }
 
     #[test]
@@ -560,6 +760,11 @@ mod tests {
             Ok(_) => assert!(true),
             Err(_) => assert!(false, "Valid to have tons of text and stuff"),
         };
+
+        assert_eq!(
+            Token::maybe_consume_comment("/**\n * doc\n */\nfunc"),
+            Ok(("\nfunc", ""))
+        );
     }
 
     #[test]
@@ -602,6 +807,13 @@ mod tests {
         assert_eq!(Token::loop_tok("loop{}"), Ok(("{}", "loop")));
     }
 
+    #[test]
+    fn t_keyword_next_to_separator() {
+        assert_eq!(Token::true_tok("true, 1"), Ok((", 1", "true")));
+        assert_eq!(Token::false_tok("false}"), Ok(("}", "false")));
+        assert_eq!(Token::true_tok("true]"), Ok(("]", "true")));
+    }
+
     #[test]
     fn t_dot_token() {
         assert_eq!(Token::dot("."), Ok(("", ".")));
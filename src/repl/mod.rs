@@ -7,46 +7,41 @@ use prompt::Prompt;
 use linefeed::{Interface, ReadResult};
 
 use crate::args::Args;
-use crate::{
-    parser::Construct, FromObjectInstance, InstrKind, Instruction, Interpreter, JkConstant,
-    JkError, ObjectInstance,
-};
+use crate::{error::Source, parser::Parser, InstrKind, Instruction, Interpreter, JkError, TypeCtx};
 
 /// Empty struct for the Repl methods
 pub struct Repl;
 
-// FIXME:
-// - Is Display really how we want to go about it?
-// - Cleanup the code
-impl std::fmt::Display for ObjectInstance {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self.ty() {
-                Some(ty) => match ty.name() {
-                    "int" => JkConstant::<i64>::from_instance(self).print(),
-                    "float" => JkConstant::<f64>::from_instance(self).print(),
-                    "char" => JkConstant::<char>::from_instance(self).print(),
-                    "string" => JkConstant::<String>::from_instance(self).print(),
-                    "bool" => JkConstant::<bool>::from_instance(self).print(),
-                    _ => format!("{:?}", self),
-                },
-                None => format!(""),
-            }
-        )
+impl Repl {
+    /// Parse a new input, which can contain multiple instructions
+    fn parse_instructions(input: &str) -> Result<Vec<Box<dyn Instruction>>, Vec<JkError>> {
+        Parser::with_source(Source::new(input.to_owned(), None), Parser::instructions)
     }
-}
 
-impl Repl {
-    /// Parse a new input, adding it to an existing interpreter
-    fn parse_instruction(input: &str) -> Result<Option<Box<dyn Instruction>>, JkError> {
-        match input.is_empty() {
-            true => Ok(None),
-            false => match Construct::instruction(input) {
-                Ok((_, value)) => Ok(Some(value)),
-                Err(e) => Err(JkError::from(e)),
-            },
+    /// Typecheck and execute an instruction, displaying its result. Returns `false` if
+    /// an error occured
+    fn run_instruction(
+        inst: Box<dyn Instruction>,
+        interpreter: &mut Interpreter,
+        ctx: &mut TypeCtx,
+    ) -> bool {
+        inst.hoist(ctx);
+        inst.resolve_type(ctx);
+        if let Err(errors) = ctx.finish() {
+            errors.iter().for_each(|e| println!("{}", e));
+            return false;
+        }
+
+        match inst.execute(interpreter) {
+            Ok(InstrKind::Expression(None)) | Ok(InstrKind::Statement) => true,
+            Ok(InstrKind::Expression(Some(result))) => {
+                println!("{}", result.display(interpreter));
+                true
+            }
+            Err(e) => {
+                println!("{}", e);
+                false
+            }
         }
     }
 
@@ -57,28 +52,27 @@ impl Repl {
         let mut interpreter = Interpreter::new();
         interpreter.set_debug(args.debug());
 
+        // The typing context is kept alive between inputs, just like the interpreter
+        let mut ctx = TypeCtx::new();
+
         // FIXME: Add actual prompt
         line_reader.set_prompt(&Prompt::get(&interpreter))?;
 
         while let ReadResult::Input(input) = line_reader.read_line()? {
-            let inst = match Repl::parse_instruction(&input) {
-                Ok(i) => i,
-                Err(e) => {
-                    println!("{}", e.to_string());
+            let instructions = match Repl::parse_instructions(&input) {
+                Ok(instructions) => instructions,
+                Err(errors) => {
+                    errors.iter().for_each(|e| println!("{}", e));
                     continue;
                 }
             };
 
-            let inst = match inst {
-                Some(i) => i,
-                None => continue,
-            };
-
-            match inst.execute(&mut interpreter) {
-                Ok(InstrKind::Expression(None)) | Ok(InstrKind::Statement) => {}
-                Ok(InstrKind::Expression(Some(result))) => println!("{}", result),
-                Err(e) => println!("{}", e.to_string()),
-            };
+            // Stop executing the input as soon as an error occurs
+            for inst in instructions {
+                if !Repl::run_instruction(inst, &mut interpreter, &mut ctx) {
+                    break;
+                }
+            }
 
             line_reader.set_prompt(&Prompt::get(&interpreter))?;
         }
@@ -0,0 +1,758 @@
+//! The typechecker runs over the instructions of a program before executing them. Each
+//! instruction resolves its own type, checks the types of the instructions it contains,
+//! and reports any error it finds to the typing context. A program containing type
+//! errors is never executed.
+//!
+//! Types are resolved against a `TypeCtx`, which mirrors what the interpreter keeps
+//! track of during execution. Instead of keeping variables around, it only keeps the
+//! type of said variables.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use crate::builtins::Builtins;
+use crate::instruction::{FunctionDec, TypeDec, TypeId, PRIMITIVE_TYPES};
+use crate::interpreter::ScopeMap;
+use crate::{Instruction, JkErrKind, JkErrSpaceLocation, JkError};
+
+/// The result of typechecking an instruction. An instruction either has a type that
+/// we were able to resolve, is a statement and therefore `void`, or has a type that
+/// could not be figured out because of a previous error. Instructions which never
+/// complete, such as `return`, are of type `never`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CheckedType {
+    Resolved(TypeId),
+    Void,
+    Unknown,
+    Never,
+}
+
+impl CheckedType {
+    /// Create a new resolved type from a type's name
+    pub fn resolved(name: &str) -> CheckedType {
+        CheckedType::Resolved(TypeId::from(name))
+    }
+
+    /// Check if two types are compatible. Unknown types are compatible with anything,
+    /// since an error has already been emitted when they were resolved. So are
+    /// instructions which never complete, since they never produce a value
+    pub fn compatible(&self, other: &CheckedType) -> bool {
+        match (self, other) {
+            (CheckedType::Unknown, _) | (_, CheckedType::Unknown) => true,
+            (CheckedType::Never, _) | (_, CheckedType::Never) => true,
+            (lhs, rhs) => lhs == rhs,
+        }
+    }
+}
+
+impl std::fmt::Display for CheckedType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckedType::Resolved(ty) => write!(f, "{}", ty.id()),
+            CheckedType::Void => write!(f, "void"),
+            CheckedType::Unknown => write!(f, "{{unknown}}"),
+            CheckedType::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// The `TypeCheck` trait is implemented by every instruction. Resolving the type of an
+/// instruction also typechecks the instructions it contains.
+pub trait TypeCheck {
+    /// Resolve the type of the instruction. Errors are not returned but reported to the
+    /// context, so that as many of them as possible are found in a single pass
+    fn resolve_type(&self, ctx: &mut TypeCtx) -> CheckedType;
+
+    /// Declare the functions and types declared by the instruction, without checking
+    /// them. This is done for every instruction of a block before any of them gets
+    /// checked, so that functions and types can be used before their declaration
+    fn hoist(&self, _ctx: &mut TypeCtx) {}
+}
+
+/// Context of the function currently being checked
+pub struct FunctionCtx {
+    /// Type returned by the function
+    return_ty: Option<CheckedType>,
+
+    /// Loops currently being checked, from the outermost to the innermost one
+    loops: Vec<LoopCtx>,
+}
+
+/// Context of a loop being checked
+struct LoopCtx {
+    /// Type yielded by the loop. Loops without a return type yield `void`
+    ty: CheckedType,
+
+    /// Whether the loop contains a `break` leaving it
+    broken: bool,
+}
+
+/// The typing context keeps track of the types of variables, as well as the declared
+/// functions and types, scope per scope.
+pub struct TypeCtx {
+    /// Types of the variables, as well as functions and custom types declared so far
+    scope_map: ScopeMap<CheckedType, FunctionDec, TypeDec>,
+
+    /// Names of the variants declared so far, along with the sum type declaring them.
+    /// Their constructors are functions named after them, which cannot be redeclared
+    variants: HashMap<String, String>,
+
+    /// Errors encountered during typechecking
+    errors: Vec<JkError>,
+
+    /// Path of the source currently being typechecked. This is needed to resolve
+    /// included sources the same way the interpreter does
+    path: Option<PathBuf>,
+
+    /// Sources included so far
+    included: HashSet<PathBuf>,
+
+    /// Function currently being checked. Outside of any function, nothing can be
+    /// returned
+    function: FunctionCtx,
+}
+
+impl TypeCtx {
+    /// Create a new typing context, aware of the primitive types and builtins
+    pub fn new() -> TypeCtx {
+        let mut ctx = TypeCtx {
+            scope_map: ScopeMap::new(),
+            variants: HashMap::new(),
+            errors: Vec::new(),
+            path: None,
+            included: HashSet::new(),
+            function: FunctionCtx {
+                return_ty: None,
+                loops: Vec::new(),
+            },
+        };
+
+        ctx.scope_enter();
+
+        PRIMITIVE_TYPES
+            .iter()
+            .for_each(|ty_name| ctx.declare_type(TypeDec::from(*ty_name)));
+
+        Builtins::types()
+            .into_iter()
+            .for_each(|ty| ctx.declare_type(ty));
+        Builtins::declarations()
+            .into_iter()
+            .for_each(|function| ctx.declare_function(function));
+
+        // The constructors of builtin types are part of the builtin declarations
+        Builtins::types()
+            .iter()
+            .for_each(|ty| ctx.reserve_variants(ty));
+
+        ctx
+    }
+
+    /// Report a new type error, located on the faulty instruction
+    pub fn error(&mut self, msg: String, loc: Option<&JkErrSpaceLocation>, input: String) {
+        self.report(JkError::new(
+            JkErrKind::TypeChecker,
+            msg,
+            loc.cloned(),
+            input,
+        ))
+    }
+
+    /// Report an error that is not a type error, but prevents the program from
+    /// running anyway, such as an invalid include
+    pub fn report(&mut self, error: JkError) {
+        self.errors.push(error)
+    }
+
+    /// Consume the errors emitted so far. `Ok` if the typechecking was succesful
+    pub fn finish(&mut self) -> Result<(), Vec<JkError>> {
+        match self.errors.is_empty() {
+            true => Ok(()),
+            false => Err(self.errors.drain(..).collect()),
+        }
+    }
+
+    /// Create a new empty scope
+    pub fn scope_enter(&mut self) {
+        self.scope_map.scope_enter()
+    }
+
+    /// Create the scope of a function's body. Assignments made inside of it cannot
+    /// modify the variables of enclosing scopes
+    pub fn function_scope_enter(&mut self) {
+        self.scope_map.function_scope_enter()
+    }
+
+    /// Exit the latest created scope
+    pub fn scope_exit(&mut self) {
+        self.scope_map.scope_exit()
+    }
+
+    /// Declare a new variable, or change the type of an existing one in the current
+    /// scope
+    pub fn declare_var(&mut self, name: &str, ty: CheckedType) {
+        let _ = self.scope_map.remove_variable(name);
+
+        // We can unwrap since the variable was removed beforehand
+        self.scope_map.add_variable(name.to_owned(), ty).unwrap();
+    }
+
+    /// Get the type of a variable, if it has been declared
+    pub fn get_var(&self, name: &str) -> Option<&CheckedType> {
+        self.scope_map.get_variable(name)
+    }
+
+    /// Get the type of a variable which can be assigned to from the current scope.
+    /// Variables declared outside of the current function are not returned, since
+    /// assigning to them creates a new variable local to the function
+    pub fn get_assignable_var(&mut self, name: &str) -> Option<&CheckedType> {
+        self.scope_map.get_variable_mut(name).map(|ty| &*ty)
+    }
+
+    /// Declare a new function. Redeclaring a function is an error
+    pub fn declare_function(&mut self, function: FunctionDec) {
+        let name = function.name().to_owned();
+        let loc = function.location().cloned();
+
+        if let Some(owner) = self.variants.get(&name) {
+            let msg = format!(
+                "function name already used by a variant of type `{}`: {}",
+                owner, name
+            );
+            return self.error(msg, loc.as_ref(), name);
+        }
+
+        if self.scope_map.add_function(name.clone(), function).is_err() {
+            self.error(
+                format!("function already declared: {}", name),
+                loc.as_ref(),
+                name,
+            );
+        }
+    }
+
+    /// Declare the constructors of the variants of a sum type. A variant cannot share
+    /// its name with a variant of another type or with a function
+    pub fn declare_constructors(&mut self, type_dec: &TypeDec) {
+        for constructor in type_dec.constructors() {
+            let name = constructor.name().to_owned();
+
+            match self.variants.get(&name) {
+                Some(owner) => {
+                    let msg = format!("variant name already used by type `{}`: {}", owner, name);
+                    self.error(msg, type_dec.location(), name);
+                }
+                None => self.declare_function(constructor),
+            }
+        }
+
+        self.reserve_variants(type_dec);
+    }
+
+    /// Keep track of the variants of a sum type whose constructors are declared
+    fn reserve_variants(&mut self, type_dec: &TypeDec) {
+        for variant in type_dec.variants() {
+            self.variants
+                .entry(variant.name().to_owned())
+                .or_insert_with(|| type_dec.name().to_owned());
+        }
+    }
+
+    /// Get the declaration of a function, if it has been declared
+    pub fn get_function(&self, name: &str) -> Option<Rc<FunctionDec>> {
+        self.scope_map.get_function(name).cloned()
+    }
+
+    /// Declare a new type. Redeclaring a type is an error
+    pub fn declare_type(&mut self, type_dec: TypeDec) {
+        let name = type_dec.name().to_owned();
+        let loc = type_dec.location().cloned();
+
+        if self.scope_map.add_type(name.clone(), type_dec).is_err() {
+            self.error(
+                format!("type already declared: {}", name),
+                loc.as_ref(),
+                name,
+            );
+        }
+    }
+
+    /// Get the declaration of a type, if it has been declared
+    pub fn get_type(&self, type_id: &TypeId) -> Option<Rc<TypeDec>> {
+        self.scope_map.get_type_id(type_id)
+    }
+
+    /// Explain why a generic type could not be found, if the reason is that the wrong
+    /// number of generic arguments was given
+    pub fn generics_mismatch(&self, type_id: &TypeId) -> Option<String> {
+        self.scope_map
+            .get_type(type_id.base())
+            .and_then(|type_dec| type_dec.check_generics(type_id).err())
+    }
+
+    /// Resolve a type written in the source code, such as a function's return type.
+    /// No type at all means `void`. Reports an error, located on the instruction using
+    /// the type, if the type does not exist
+    pub fn resolve(
+        &mut self,
+        ty: Option<&TypeId>,
+        loc: Option<&JkErrSpaceLocation>,
+    ) -> CheckedType {
+        match ty {
+            None => CheckedType::Void,
+            Some(ty) => match self.get_type(ty) {
+                Some(_) => CheckedType::Resolved(ty.clone()),
+                None => {
+                    let msg = self.generics_mismatch(ty);
+
+                    self.error(
+                        msg.unwrap_or_else(|| format!("type not found: {}", ty.id())),
+                        loc,
+                        ty.id().to_owned(),
+                    );
+                    CheckedType::Unknown
+                }
+            },
+        }
+    }
+
+    /// Look up a type written in the source code without reporting any error. This is
+    /// useful for types that have already been resolved once, such as the types of
+    /// a function's arguments when checking a call to that function
+    pub fn lookup(&self, ty: Option<&TypeId>) -> CheckedType {
+        match ty {
+            None => CheckedType::Void,
+            Some(ty) => match self.get_type(ty) {
+                Some(_) => CheckedType::Resolved(ty.clone()),
+                None => CheckedType::Unknown,
+            },
+        }
+    }
+
+    /// Enter a loop yielding values of type `ty`
+    pub fn loop_enter(&mut self, ty: CheckedType) {
+        self.function.loops.push(LoopCtx { ty, broken: false })
+    }
+
+    /// Exit the innermost loop. Returns whether a `break` can leave it
+    pub fn loop_exit(&mut self) -> bool {
+        matches!(
+            self.function.loops.pop(),
+            Some(LoopCtx { broken: true, .. })
+        )
+    }
+
+    /// Type yielded by the innermost loop, if inside of one
+    pub fn current_loop(&self) -> Option<&CheckedType> {
+        self.function.loops.last().map(|ctx| &ctx.ty)
+    }
+
+    /// Mark the innermost loop as being left by a `break`
+    pub fn loop_break(&mut self) {
+        if let Some(ctx) = self.function.loops.last_mut() {
+            ctx.broken = true;
+        }
+    }
+
+    /// Enter a function returning values of type `return_ty`. The context of the
+    /// enclosing function is returned, and must be given back to `function_exit()`.
+    /// Loops cannot be controlled from inside a function declared in their body
+    pub fn function_enter(&mut self, return_ty: CheckedType) -> FunctionCtx {
+        std::mem::replace(
+            &mut self.function,
+            FunctionCtx {
+                return_ty: Some(return_ty),
+                loops: Vec::new(),
+            },
+        )
+    }
+
+    /// Exit the current function, and resume checking the enclosing one
+    pub fn function_exit(&mut self, enclosing: FunctionCtx) {
+        self.function = enclosing;
+    }
+
+    /// Type returned by the function being checked, if inside of one
+    pub fn return_ty(&self) -> Option<&CheckedType> {
+        self.function.return_ty.as_ref()
+    }
+
+    /// Get a reference to the path of the source being typechecked
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// Change the path of the source being typechecked
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        self.path = path;
+
+        if let Some(p) = &self.path {
+            self.included.insert(p.clone());
+        }
+    }
+
+    /// Check if a source has already been typechecked or not
+    pub fn is_included(&self, source: &PathBuf) -> bool {
+        self.included.contains(source)
+    }
+}
+
+impl Default for TypeCtx {
+    fn default() -> Self {
+        TypeCtx::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn typecheck(input: &str) -> Result<(), Vec<JkError>> {
+        Parser::parse(input).unwrap().typecheck()
+    }
+
+    #[test]
+    fn t_valid_program() {
+        assert!(typecheck("func add(a: int, b: int) -> int { a + b } add(1, 2)").is_ok());
+    }
+
+    #[test]
+    fn t_binop_mismatched_types() {
+        assert!(typecheck("1 + \"2.4\"").is_err());
+        assert!(typecheck("true == 1").is_err());
+    }
+
+    #[test]
+    fn t_binop_int_float_promotion() {
+        assert!(typecheck("func f(a: int) -> float { a + 2.4 }").is_ok());
+        assert!(typecheck("func f(a: int) -> bool { 2.4 < a }").is_ok());
+        assert!(typecheck("func f(a: int) -> int { a * 2.4 }").is_err());
+        assert!(typecheck("1 << 2.0").is_err());
+    }
+
+    #[test]
+    fn t_comparison_is_bool() {
+        assert!(typecheck("func f(a: int) -> bool { a < 2 && !(a == 0) }").is_ok());
+        assert!(typecheck("func f(a: int) -> int { a < 2 }").is_err());
+    }
+
+    #[test]
+    fn t_operator_not_implemented() {
+        assert!(typecheck("true < false").is_err());
+        assert!(typecheck("\"a\" + \"b\"").is_err());
+        assert!(typecheck("1 || 2").is_err());
+        assert!(typecheck("!1").is_err());
+        assert!(typecheck("1.5 & 2.5").is_err());
+        assert!(typecheck("~true").is_err());
+        assert!(typecheck("1.5 ** 2.5 % 2.0").is_ok());
+    }
+
+    #[test]
+    fn t_wrong_argument_type() {
+        assert!(typecheck("func f(a: int) -> int { a } f(1.2)").is_err());
+    }
+
+    #[test]
+    fn t_wrong_return_type() {
+        assert!(typecheck("func f() -> int { 1.5 }").is_err());
+    }
+
+    #[test]
+    fn t_missing_return_value() {
+        assert!(typecheck("func f() -> int { a = 1; }").is_err());
+    }
+
+    #[test]
+    fn t_unknown_type() {
+        assert!(typecheck("func f(a: Custom) {}").is_err());
+    }
+
+    #[test]
+    fn t_unknown_function() {
+        assert!(typecheck("unknown_func()").is_err());
+    }
+
+    #[test]
+    fn t_declarations_hoisted() {
+        assert!(typecheck("func b() -> int { a() } func a() -> int { 3 } b()").is_ok());
+        assert!(typecheck("p = Point { 1, 2 }; type Point(x: int, y: int);").is_ok());
+        assert!(typecheck("type Line(start: Point); type Point(x: int);").is_ok());
+        assert!(typecheck("func b() -> int { a() } func a() -> char { 'a' }").is_err());
+        assert!(typecheck("{ func a() {} } a()").is_err());
+    }
+
+    #[test]
+    fn t_mutual_recursion() {
+        let input = "func is_even(n: int) -> bool { if n == 0 { true } else { is_odd(n - 1) } }
+            func is_odd(n: int) -> bool { if n == 0 { false } else { is_even(n - 1) } }
+            is_even(4)";
+
+        assert!(typecheck(input).is_ok());
+    }
+
+    #[test]
+    fn t_assign_void() {
+        assert!(typecheck("func f() {} a = f();").is_err());
+    }
+
+    #[test]
+    fn t_if_else_different_types() {
+        assert!(typecheck("x = if true { 1 } else { 'c' };").is_err());
+    }
+
+    #[test]
+    fn t_condition_not_bool() {
+        assert!(typecheck("if 1 { 2 } else { 3 }").is_err());
+    }
+
+    #[test]
+    fn t_type_instantiation() {
+        assert!(typecheck("type Point(x: int, y: int); p = Point { 1, 2 };").is_ok());
+        assert!(typecheck("type Point(x: int, y: int); p = Point { 1, 'c' };").is_err());
+    }
+
+    #[test]
+    fn t_generic_type_instantiation() {
+        assert!(typecheck("type Node<T>(v: T); n = Node<int> { 1 };").is_ok());
+        assert!(typecheck("type Node<T>(v: T); n = Node<int> { 'c' };").is_err());
+        assert!(typecheck("type Node<T>(v: T); n = Node<int, int> { 1 };").is_err());
+        assert!(typecheck("type Node<T>(v: T); n = Node { 1 };").is_err());
+    }
+
+    #[test]
+    fn t_generic_function() {
+        assert!(typecheck("func id<T>(v: T) -> T { v } id<int>(1) + 1").is_ok());
+        assert!(typecheck("func id<T>(v: T) -> T { v } id<int>('c')").is_err());
+        assert!(typecheck("func id<T>(v: T) -> T { v } id<char>('c') + 1").is_err());
+        assert!(typecheck("func id<T>(v: T) -> T { v } id(1) + 1").is_ok());
+        assert!(typecheck("func id<T>(v: T) -> T { v } id('c') + 1").is_err());
+        assert!(typecheck("ext func default<T>() -> T; default()").is_err());
+        assert!(typecheck("func add<T>(a: T, b: T) -> T { a + b }").is_err());
+    }
+
+    #[test]
+    fn t_arrays() {
+        assert!(typecheck("a = [1, 2]; b = a[0] + a.len();").is_ok());
+        assert!(typecheck("a = [[1], [2, 3]]; b = a[1][0] + 1;").is_ok());
+        assert!(typecheck("a = ['a']; b = a[0] + 1;").is_err());
+        assert!(typecheck("mut a = [1]; a.push('c');").is_err());
+        assert!(typecheck("a = [1, 'c'];").is_err());
+        assert!(typecheck("a = [];").is_err());
+        assert!(typecheck("mut a = array::new<int>(); a.push(1);").is_ok());
+        assert!(typecheck("mut a = [1]; b = a.pop().unwrap() + 1;").is_ok());
+        assert!(typecheck("mut s = 0; for i in [1, 2] { s = s + i; }").is_ok());
+    }
+
+    #[test]
+    fn t_maps() {
+        assert!(typecheck("mut m = { 'a': 1 }; m.insert('b', 2); v = m.get('a') + 1;").is_ok());
+        assert!(typecheck("mut m = { 'a': 1 }; v = m.remove('a').unwrap() + 1;").is_ok());
+        assert!(typecheck("m = { 'a': 1 }; v = m.get(1);").is_err());
+        assert!(typecheck("m = { 'a': 1, 2: 1 };").is_err());
+        assert!(typecheck("m = { 'a': 1, 'b': 'c' };").is_err());
+        assert!(typecheck("m = { [1]: 1 };").is_err());
+        assert!(typecheck("mut m = map::new<string, [int]>(); m.insert(\"a\", [1]);").is_ok());
+        assert!(typecheck("m = { 'a': 1 }; k = m.keys;").is_err());
+        assert!(typecheck("mut s = 0; for k in { 1: 'a' } { s = s + k; }").is_ok());
+    }
+
+    #[test]
+    fn t_sum_types() {
+        let shape = "type Shape = Circle(r: int) | Rect(w: int, h: int) | Empty;";
+        let check = |input: &str| typecheck(&format!("{} {}", shape, input));
+
+        assert!(
+            check("a = match Circle(2) { Circle(r) => r, Rect(w, _) => w, Empty => 0 } + 1;")
+                .is_ok()
+        );
+        assert!(check("a = match Empty() { Circle(r) => r, _ => 0 };").is_ok());
+        assert!(check("a = Circle(1.5);").is_err());
+        // Non-exhaustive match
+        assert!(check("a = match Empty() { Circle(r) => r, Empty => 0 };").is_err());
+        // Unreachable arms
+        assert!(check("a = match Empty() { _ => 1, Empty => 0 };").is_err());
+        assert!(check("a = match Empty() { Empty => 1, Empty => 0, _ => 2 };").is_err());
+        // Wrong variants and bindings
+        assert!(check("a = match Empty() { Square(s) => s, _ => 0 };").is_err());
+        assert!(check("a = match Empty() { Rect(w) => w, _ => 0 };").is_err());
+        // Incompatible arms
+        assert!(check("a = match Empty() { Circle(r) => r, _ => 'c' };").is_err());
+        assert!(check("a = match 12 { _ => 0 };").is_err());
+    }
+
+    #[test]
+    fn t_sum_types_instantiation() {
+        let shape = "type Shape = Circle(r: int) | Rect(w: int, h: int) | Empty;";
+        let check = |input: &str| typecheck(&format!("{} {}", shape, input));
+
+        assert!(check("s = Rect { 2, 3 }; Rect(w, h) = s; a = w + h;").is_ok());
+        assert!(check("s = Empty {}; Circle(_) = s;").is_ok());
+        assert!(check("o = Some<int> { 1 }; a = o.unwrap() + 1;").is_ok());
+        assert!(check("s = Shape { 1 };").is_err());
+        assert!(check("s = Circle { 'c' };").is_err());
+        assert!(check("s = Rect { 1 };").is_err());
+        // Destructuring non-sum types, unknown variants and wrong bindings
+        assert!(check("Circle(r) = 12;").is_err());
+        assert!(check("Square(s) = Empty {};").is_err());
+        assert!(check("Rect(w) = Empty {};").is_err());
+    }
+
+    #[test]
+    fn t_variant_name_collisions() {
+        let errors = typecheck("type A = X | Y; type B = X | Z;").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .msg()
+            .contains("variant name already used by type `A`"));
+
+        assert!(typecheck("type A = X | Y; func X() {}").is_err());
+        assert!(typecheck("func Some(x: int) -> int { x }").is_err());
+        assert!(typecheck("type Maybe = Some(x: int) | Nothing;").is_err());
+    }
+
+    #[test]
+    fn t_options() {
+        assert!(typecheck("a = Some(1).unwrap() + 1;").is_ok());
+        assert!(typecheck("a = match Some('c') { Some(c) => c, None => 'd' };").is_ok());
+        assert!(typecheck("a = Some('c').unwrap_or(1);").is_err());
+        assert!(typecheck("a = None();").is_err());
+        assert!(typecheck("o = None<int>(); a = o.unwrap_or(2) + o.ok();").is_err());
+        assert!(typecheck("a = Ok<int, string>(1).ok().unwrap() + 1;").is_ok());
+        assert!(typecheck(
+            "func f(x: int) -> Result<int, string> { \
+                if x > 0 { Ok<int, string>(x) } else { Err<int, string>(\"neg\") } \
+            }"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn t_string_interpolation() {
+        assert!(typecheck("a = 1; s = \"{a + 1} {'c'}\" == \"2 c\";").is_ok());
+        assert!(typecheck("s = \"{a}\";").is_err());
+        assert!(typecheck("s = \"{1 + 'c'}\";").is_err());
+        assert!(typecheck("s = \"{b = 1}\";").is_err());
+        assert!(typecheck("s = \"{1}\" + 1;").is_err());
+    }
+
+    #[test]
+    fn t_reassign_different_type() {
+        assert!(typecheck("mut a = 1; a = 2;").is_ok());
+        assert!(typecheck("mut a = 1; a = 2.5;").is_err());
+    }
+
+    #[test]
+    fn t_assign_in_function_is_local() {
+        assert!(typecheck("mut x = 1; func f() { x = \"s\"; }").is_ok());
+        assert!(typecheck("mut x = 1; func f() -> int { x = \"s\"; x }").is_err());
+        assert!(typecheck("mut x = 1; func f() { x = 2.5; if true { x = 2; } }").is_err());
+    }
+
+    #[test]
+    fn t_multiple_errors_reported() {
+        assert_eq!(
+            typecheck("a = 1 + \"2.4\"; b = 'c' + 2;")
+                .unwrap_err()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn t_for_loop_item_type() {
+        assert!(typecheck("mut s = 0; for i in range(0, 4) { s = s + i; }").is_ok());
+        assert!(typecheck("for c in \"jinko\" { x = c + 1; }").is_err());
+    }
+
+    #[test]
+    fn t_for_loop_not_iterable() {
+        assert!(typecheck("for i in 12 { }").is_err());
+    }
+
+    #[test]
+    fn t_for_loop_custom_type() {
+        let input = "type Countdown(n: int);
+            func Countdown::next(c: Countdown) -> Option<int> { Some(c.n) }
+            func Countdown::advance(c: Countdown) -> Countdown { Countdown { c.n - 1 } }
+            start = Countdown { 3 }; for n in start { x = n + 1; }";
+
+        assert!(typecheck(input).is_ok());
+    }
+
+    #[test]
+    fn t_for_loop_invalid_next() {
+        let input = "type Countdown(n: int);
+            func Countdown::next(c: Countdown) -> int { c.n }
+            func Countdown::advance(c: Countdown) -> Countdown { Countdown { c.n - 1 } }
+            start = Countdown { 3 }; for c in start { }";
+
+        assert!(typecheck(input).is_err());
+
+        let input = "type Countdown(n: int);
+            func Countdown::next(c: Countdown) -> Option<int> { Some(c.n) }
+            start = Countdown { 3 }; for n in start { }";
+
+        assert!(typecheck(input).is_err());
+    }
+
+    #[test]
+    fn t_loop_control_outside_of_loop() {
+        assert!(typecheck("break;").is_err());
+        assert!(typecheck("continue;").is_err());
+        assert!(typecheck("loop { func f() { break; } }").is_err());
+    }
+
+    #[test]
+    fn t_typed_loop() {
+        assert!(typecheck("x = loop -> int { break 12; }; y = x + 1;").is_ok());
+        assert!(typecheck("x = for i in range(0, 2) -> int { 'c' };").is_err());
+        assert!(typecheck("x = loop -> int { break 1.5; };").is_err());
+    }
+
+    #[test]
+    fn t_infinite_loop_never_terminates() {
+        assert!(typecheck("func f() -> int { loop { return 7; } }").is_ok());
+        assert!(typecheck("func f() -> int { loop { for i in range(0, 2) { break; } } }").is_ok());
+        assert!(typecheck("func f() -> int { loop { break; } }").is_err());
+        assert!(typecheck("func f() -> int { while true { return 7; } }").is_err());
+    }
+
+    #[test]
+    fn t_break_value_untyped_loop() {
+        assert!(typecheck("loop { break 12; }").is_err());
+    }
+
+    #[test]
+    fn t_return() {
+        assert!(typecheck("func f() -> int { return 1; }").is_ok());
+        assert!(typecheck("func f(a: int) -> int { if true { return a; }; 0 }").is_ok());
+        assert!(typecheck("func f(a: int) -> int { if true { return a; } else { 1 } }").is_ok());
+        assert!(typecheck("func f() { return; }").is_ok());
+    }
+
+    #[test]
+    fn t_return_wrong_type() {
+        assert!(typecheck("func f() -> int { return 1.5; }").is_err());
+        assert!(typecheck("func f() -> int { return; }").is_err());
+        assert!(typecheck("func f() { return 12; }").is_err());
+    }
+
+    #[test]
+    fn t_return_outside_of_function() {
+        assert!(typecheck("return 12;").is_err());
+    }
+    #[test]
+    fn t_mock_signature() {
+        assert!(typecheck("func f(a: int) -> int { a } mock f(b: int) -> int { 1 }").is_ok());
+        assert!(typecheck("mock read_line() -> Option<string> { Some(\"jinko\") }").is_ok());
+        assert!(typecheck("func f() -> int { 1 } mock f(x: string) -> int { 1 }").is_err());
+        assert!(typecheck("func f() -> int { 1 } mock f() { }").is_err());
+    }
+
+    #[test]
+    fn t_mock_undeclared_function() {
+        assert!(typecheck("mock nothing() -> int { 1 }").is_err());
+    }
+}
@@ -2,6 +2,10 @@
 
 mod queue;
 mod stack;
+#[cfg(test)]
+mod testing;
 
 pub use queue::Queue;
 pub use stack::Stack;
+#[cfg(test)]
+pub use testing::{execute, execute_in};
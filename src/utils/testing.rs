@@ -0,0 +1,23 @@
+//! Helpers shared by the unit tests, to execute jinko code without going through
+//! the whole program pipeline
+
+use crate::parser::Parser;
+use crate::{InstrKind, Interpreter, JkError};
+
+/// Execute each instruction of the input in order, in the given interpreter. Stops at
+/// the first error, and returns the result of the last instruction otherwise
+pub fn execute_in(input: &str, interpreter: &mut Interpreter) -> Result<InstrKind, JkError> {
+    let instructions = Parser::instructions(input).unwrap();
+    let mut result = Ok(InstrKind::Statement);
+
+    for instruction in instructions.iter() {
+        result = Ok(instruction.execute(interpreter)?);
+    }
+
+    result
+}
+
+/// Execute each instruction of the input in order, in a new interpreter
+pub fn execute(input: &str) -> Result<InstrKind, JkError> {
+    execute_in(input, &mut Interpreter::new())
+}
@@ -1,7 +1,7 @@
 use crate::instruction::{InstrKind, Instruction, Operator, TypeDec};
 use crate::{
-    FromObjectInstance, Interpreter, JkError, JkString, ObjectInstance, Rename, ToObjectInstance,
-    Value,
+    CheckedType, FromObjectInstance, Interpreter, JkBool, JkErrKind, JkErrSpaceLocation, JkError,
+    JkString, ObjectInstance, Rename, ToObjectInstance, TypeCheck, TypeCtx, Value,
 };
 
 use std::convert::TryFrom;
@@ -9,8 +9,9 @@ use std::convert::TryFrom;
 #[derive(Clone)]
 /// A JkConstant represents a primitive type in Jinko. It is used in order to
 /// implement integers, floating point numbers, characters, booleans and strings, as
-/// well as raw byte values later for custom types.
-pub struct JkConstant<T>(pub(crate) T);
+/// well as raw byte values later for custom types. Constants parsed from the source
+/// code also keep their location.
+pub struct JkConstant<T>(pub(crate) T, Option<JkErrSpaceLocation>);
 
 // We can do a generic implementation instead of copy pasting it 5 times.
 // However, this part of the rust compiler is still not ready
@@ -91,6 +92,14 @@ macro_rules! jk_primitive {
                 InstrKind::Expression(None)
             }
 
+            fn location(&self) -> Option<&JkErrSpaceLocation> {
+                self.1.as_ref()
+            }
+
+            fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+                self.1 = location;
+            }
+
             fn print(&self) -> String {
                 self.0.to_string()
             }
@@ -107,6 +116,12 @@ macro_rules! jk_primitive {
                 Ok(InstrKind::Expression(Some(self.to_instance())))
             }
         }
+
+        impl TypeCheck for JkConstant<bool> {
+            fn resolve_type(&self, _ctx: &mut TypeCtx) -> CheckedType {
+                CheckedType::resolved("bool")
+            }
+        }
     };
     ($t:ty, $s:expr) => {
         impl ToObjectInstance for JkConstant<$t> {
@@ -141,6 +156,14 @@ macro_rules! jk_primitive {
                 InstrKind::Expression(None)
             }
 
+            fn location(&self) -> Option<&JkErrSpaceLocation> {
+                self.1.as_ref()
+            }
+
+            fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+                self.1 = location;
+            }
+
             fn print(&self) -> String {
                 self.0.to_string()
             }
@@ -153,6 +176,12 @@ macro_rules! jk_primitive {
                 Ok(InstrKind::Expression(Some(self.to_instance())))
             }
         }
+
+        impl TypeCheck for JkConstant<$t> {
+            fn resolve_type(&self, _ctx: &mut TypeCtx) -> CheckedType {
+                CheckedType::resolved($s)
+            }
+        }
     };
 }
 
@@ -165,14 +194,96 @@ jk_primitive!(f64, "float");
 jk_primitive!(char, "char");
 jk_primitive!(bool);
 
+impl<T: PartialOrd> JkConstant<T> {
+    /// Compare two constants using a comparison operator. Returns `None` if the
+    /// operator is not a comparison one
+    fn compare(&self, other: &Self, op: Operator) -> Option<ObjectInstance> {
+        let result = match op {
+            Operator::Equals => self.0 == other.0,
+            Operator::NotEquals => self.0 != other.0,
+            Operator::LowerThan => self.0 < other.0,
+            Operator::LowerOrEqual => self.0 <= other.0,
+            Operator::GreaterThan => self.0 > other.0,
+            Operator::GreaterOrEqual => self.0 >= other.0,
+            _ => return None,
+        };
+
+        Some(JkBool::from(result).to_instance())
+    }
+}
+
+impl JkConstant<i64> {
+    /// Create a new instance from the result of a checked integer operation. A missing
+    /// result means that the operation overflowed or that it was invalid, such as a
+    /// division by zero
+    fn checked(
+        &self,
+        other: &Self,
+        op: Operator,
+        result: Option<i64>,
+    ) -> Result<ObjectInstance, JkError> {
+        if let Some(value) = result {
+            return Ok(JkConstant::from(value).to_instance());
+        }
+
+        let reason = match op {
+            Operator::Div | Operator::Mod if other.0 == 0 => "division by zero",
+            Operator::Pow if other.0 < 0 => "negative exponent in integer power",
+            Operator::LeftShift | Operator::RightShift => "invalid shift amount",
+            _ => "integer overflow",
+        };
+        let input = format!("{} {} {}", self.0, op.to_str(), other.0);
+
+        Err(JkError::new(
+            JkErrKind::Interpreter,
+            format!("{}: {}", reason, input),
+            None,
+            input,
+        ))
+    }
+}
+
 impl Value for JkConstant<i64> {
     fn do_op(&self, other: &Self, op: Operator) -> Result<ObjectInstance, JkError> {
+        let (lhs, rhs) = (self.0, other.0);
+
+        let result = match op {
+            Operator::Add => lhs.checked_add(rhs),
+            Operator::Sub => lhs.checked_sub(rhs),
+            Operator::Mul => lhs.checked_mul(rhs),
+            Operator::Div => lhs.checked_div(rhs),
+            Operator::Mod => lhs.checked_rem(rhs),
+            Operator::Pow => u32::try_from(rhs).ok().and_then(|exp| lhs.checked_pow(exp)),
+            Operator::BitAnd => Some(lhs & rhs),
+            Operator::BitOr => Some(lhs | rhs),
+            Operator::BitXor => Some(lhs ^ rhs),
+            Operator::LeftShift => u32::try_from(rhs).ok().and_then(|n| lhs.checked_shl(n)),
+            Operator::RightShift => u32::try_from(rhs).ok().and_then(|n| lhs.checked_shr(n)),
+            _ => {
+                return match self.compare(other, op) {
+                    Some(result) => Ok(result),
+                    None => self.no_op(other, op),
+                }
+            }
+        };
+
+        self.checked(other, op, result)
+    }
+
+    fn do_unary_op(&self, op: Operator) -> Result<ObjectInstance, JkError> {
         match op {
-            Operator::Add => Ok(JkConstant::from(self.0 + other.0).to_instance()),
-            Operator::Sub => Ok(JkConstant::from(self.0 - other.0).to_instance()),
-            Operator::Mul => Ok(JkConstant::from(self.0 * other.0).to_instance()),
-            Operator::Div => Ok(JkConstant::from(self.0 / other.0).to_instance()),
-            _ => self.no_op(other, op),
+            Operator::BitNot => Ok(JkConstant::from(!self.0).to_instance()),
+            Operator::UnaryMinus => match self.0.checked_neg() {
+                Some(value) => Ok(JkConstant::from(value).to_instance()),
+                None => Err(JkError::new(
+                    JkErrKind::Interpreter,
+                    format!("integer overflow: -{}", self.0),
+                    None,
+                    format!("-{}", self.0),
+                )),
+            },
+            Operator::UnaryPlus => Ok(self.to_instance()),
+            _ => self.no_unary_op(op),
         }
     }
 }
@@ -184,9 +295,57 @@ impl Value for JkConstant<f64> {
             Operator::Sub => Ok(JkConstant::from(self.0 - other.0).to_instance()),
             Operator::Mul => Ok(JkConstant::from(self.0 * other.0).to_instance()),
             Operator::Div => Ok(JkConstant::from(self.0 / other.0).to_instance()),
+            Operator::Mod => Ok(JkConstant::from(self.0 % other.0).to_instance()),
+            Operator::Pow => Ok(JkConstant::from(self.0.powf(other.0)).to_instance()),
+            _ => match self.compare(other, op) {
+                Some(result) => Ok(result),
+                None => self.no_op(other, op),
+            },
+        }
+    }
+
+    fn do_unary_op(&self, op: Operator) -> Result<ObjectInstance, JkError> {
+        match op {
+            Operator::UnaryMinus => Ok(JkConstant::from(-self.0).to_instance()),
+            Operator::UnaryPlus => Ok(self.to_instance()),
+            _ => self.no_unary_op(op),
+        }
+    }
+}
+
+impl Value for JkConstant<char> {
+    fn do_op(&self, other: &Self, op: Operator) -> Result<ObjectInstance, JkError> {
+        match self.compare(other, op) {
+            Some(result) => Ok(result),
+            None => self.no_op(other, op),
+        }
+    }
+}
+
+impl Value for JkString {
+    fn do_op(&self, other: &Self, op: Operator) -> Result<ObjectInstance, JkError> {
+        match self.compare(other, op) {
+            Some(result) => Ok(result),
+            None => self.no_op(other, op),
+        }
+    }
+}
+
+impl Value for JkConstant<bool> {
+    fn do_op(&self, other: &Self, op: Operator) -> Result<ObjectInstance, JkError> {
+        match op {
+            // Booleans are not ordered
+            Operator::Equals | Operator::NotEquals => Ok(self.compare(other, op).unwrap()),
             _ => self.no_op(other, op),
         }
     }
+
+    fn do_unary_op(&self, op: Operator) -> Result<ObjectInstance, JkError> {
+        match op {
+            Operator::Not => Ok(JkConstant::from(!self.0).to_instance()),
+            _ => self.no_unary_op(op),
+        }
+    }
 }
 
 impl ToObjectInstance for JkString {
@@ -212,6 +371,14 @@ impl Instruction for JkString {
         InstrKind::Expression(None)
     }
 
+    fn location(&self) -> Option<&JkErrSpaceLocation> {
+        self.1.as_ref()
+    }
+
+    fn set_location(&mut self, location: Option<JkErrSpaceLocation>) {
+        self.1 = location;
+    }
+
     fn print(&self) -> String {
         format!("\"{}\"", self.0.clone())
     }
@@ -223,14 +390,20 @@ impl Instruction for JkString {
     }
 }
 
+impl TypeCheck for JkString {
+    fn resolve_type(&self, _ctx: &mut TypeCtx) -> CheckedType {
+        CheckedType::resolved("string")
+    }
+}
+
 impl From<&str> for JkConstant<String> {
     fn from(s: &str) -> Self {
-        JkConstant(s.to_string())
+        JkConstant(s.to_string(), None)
     }
 }
 
 impl<T> From<T> for JkConstant<T> {
     fn from(rust_value: T) -> Self {
-        JkConstant(rust_value)
+        JkConstant(rust_value, None)
     }
 }
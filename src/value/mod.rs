@@ -1,7 +1,7 @@
 //! A `Value` is a number instance in jinko. It refers to arithmetic primtive types, such
 //! as Ints and Floats
 
-use crate::{instruction::Operator, Instruction, JkError, ObjectInstance};
+use crate::{instruction::Operator, Instruction, JkErrKind, JkError, ObjectInstance};
 
 mod jk_constant;
 
@@ -17,14 +17,22 @@ pub type JkString = JkConstant<String>;
 pub trait Value: Instruction {
     /// Call this function when an operation is not implemented, rather than implementing
     /// your own. This will format the error nicely.
-    fn no_op(&self, _other: &Self, _op: Operator) -> Result<ObjectInstance, JkError> {
-        unreachable!("NOP") // FIXME
+    fn no_op(&self, other: &Self, op: Operator) -> Result<ObjectInstance, JkError> {
+        let input = format!("{} {} {}", self.print(), op.to_str(), other.print());
+
+        Err(JkError::new(
+            JkErrKind::Interpreter,
+            format!("operator `{}` is not implemented: {}", op.to_str(), input),
+            None,
+            input,
+        ))
     }
 
     /// Realize any operation implemented by the type, and return a new instance
-    /// of a valid type. You cannot add multiple types together, except in one case:
-    /// Adding a floating point number and an integer together. Doing that will
-    /// return a new JkFloat.
+    /// of a valid type. You cannot realize an operation on multiple types, except in
+    /// one case: Adding a floating point number and an integer together. The integer is
+    /// then promoted to a JkFloat by the `BinaryOp` before calling this method, which
+    /// returns a new JkFloat.
     ///
     /// ```
     /// let interpreter = Interpreter::new();
@@ -32,16 +40,34 @@ pub trait Value: Instruction {
     /// let a = JkInt::from(126);
     /// let b = JkInt::from(4);
     ///
-    /// let res = a.do_op(b, Operator::Add); // JkInt(130)
+    /// let res = a.do_op(&b, Operator::Add); // JkInt(130)
     /// assert_eq!(res.ty(), interpreter.get_type("int"));
     ///
     /// let f = JkFloat::from(4.0);
     ///
-    /// let res = a.do_op(f, Operator::Add); // JkFloat(130.0)
+    /// let res = JkFloat::from(a.0 as f64).do_op(&f, Operator::Add); // JkFloat(130.0)
     /// assert_eq!(res.ty(), interpreter.get_type("float"));
     /// ```
-    // FIXME: Implement behavior defined here ^
     fn do_op(&self, other: &Self, op: Operator) -> Result<ObjectInstance, JkError> {
         self.no_op(other, op)
     }
+
+    /// Call this function when a unary operation is not implemented, rather than
+    /// implementing your own. This will format the error nicely.
+    fn no_unary_op(&self, op: Operator) -> Result<ObjectInstance, JkError> {
+        let input = format!("{}{}", op.to_str(), self.print());
+
+        Err(JkError::new(
+            JkErrKind::Interpreter,
+            format!("operator `{}` is not implemented: {}", op.to_str(), input),
+            None,
+            input,
+        ))
+    }
+
+    /// Realize any unary operation implemented by the type, such as the logical
+    /// negation of a boolean, and return a new instance of the same type
+    fn do_unary_op(&self, op: Operator) -> Result<ObjectInstance, JkError> {
+        self.no_unary_op(op)
+    }
 }